@@ -0,0 +1,474 @@
+//! Generates the messages of `proto/knorpelsolve.proto` for prost, the way
+//! `prost-build` would, and their JSON form for serde. The schema only uses
+//! a small part of the language: enums, messages of scalars, enums, other
+//! messages and `map<string, _>`, and one `oneof`, so it is read here
+//! rather than with `protoc`.
+//!
+//! A message with a single repeated field is a JSON list of its items, and
+//! a message whose comment says "The JSON form is the array" is a JSON
+//! array of its fields. The JSON names of enum values are the names without
+//! the prefix of the enum in lower case, unless a comment gives one.
+
+use std::fmt::Write;
+use std::{env, fs};
+
+const SCHEMA: &str = "proto/knorpelsolve.proto";
+
+fn main() {
+    println!("cargo::rerun-if-changed={SCHEMA}");
+    let schema = fs::read_to_string(SCHEMA).expect("the schema can be read");
+    let mut out = String::from("// Generated by build.rs from proto/knorpelsolve.proto.\n");
+    let items = parse(&schema);
+    let enums: Vec<_> = items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Enum(enumeration) => Some(enumeration.name.clone()),
+            Item::Message(_) => None,
+        })
+        .collect();
+    for item in &items {
+        match item {
+            Item::Enum(enumeration) => write_enum(enumeration, &mut out),
+            Item::Message(message) => write_message(message, &enums, &mut out),
+        }
+    }
+    let path = format!(
+        "{}/knorpelsolve.rs",
+        env::var("OUT_DIR").expect("set by cargo")
+    );
+    fs::write(path, out).expect("the messages can be written");
+}
+
+enum Item {
+    Enum(Enum),
+    Message(Message),
+}
+
+struct Enum {
+    docs: Vec<String>,
+    name: String,
+    values: Vec<EnumValue>,
+}
+
+struct EnumValue {
+    variant: String,
+    number: u32,
+    json: String,
+}
+
+struct Message {
+    docs: Vec<String>,
+    name: String,
+    fields: Vec<Field>,
+    oneof: Option<(String, Vec<Field>)>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Label {
+    Plain,
+    Optional,
+    Repeated,
+}
+
+struct Field {
+    label: Label,
+    kind: String,
+    name: String,
+    number: u32,
+}
+
+fn parse(schema: &str) -> Vec<Item> {
+    let mut items = vec![];
+    let mut docs = vec![];
+    let (mut enumeration, mut message) = (None::<Enum>, None::<Message>);
+    let mut oneof = None::<(String, Vec<Field>)>;
+    for line in schema.lines() {
+        let (code, comment) = line.split_once("//").unwrap_or((line, ""));
+        let (code, comment) = (code.trim(), comment.trim());
+        if code.is_empty() {
+            // comments right above an item are its documentation
+            if line.trim().is_empty() {
+                docs.clear();
+            } else {
+                docs.push(comment.to_owned());
+            }
+            continue;
+        }
+        if let Some(name) = code.strip_prefix("enum ") {
+            enumeration = Some(Enum {
+                docs: std::mem::take(&mut docs),
+                name: name.trim_end_matches(" {").to_owned(),
+                values: vec![],
+            });
+        } else if let Some(name) = code.strip_prefix("message ") {
+            message = Some(Message {
+                docs: std::mem::take(&mut docs),
+                name: name.trim_end_matches(" {").to_owned(),
+                fields: vec![],
+                oneof: None,
+            });
+        } else if let Some(name) = code.strip_prefix("oneof ") {
+            oneof = Some((name.trim_end_matches(" {").to_owned(), vec![]));
+        } else if code == "}" {
+            if let Some(oneof) = oneof.take() {
+                message.as_mut().expect("in a message").oneof = Some(oneof);
+            } else if let Some(enumeration) = enumeration.take() {
+                items.push(Item::Enum(enumeration));
+            } else if let Some(message) = message.take() {
+                items.push(Item::Message(message));
+            }
+        } else if let Some(enumeration) = &mut enumeration {
+            let (value, number) = code.split_once(" = ").expect("an enum value");
+            let number = number.trim_end_matches(';').parse().expect("a number");
+            let prefix = format!("{}_", snake(&enumeration.name).to_uppercase());
+            let value = value.strip_prefix(&prefix).expect("values have the prefix");
+            let json = match comment.strip_prefix('"') {
+                Some(quoted) => quoted.trim_end_matches('"').to_owned(),
+                None => value.to_lowercase(),
+            };
+            enumeration.values.push(EnumValue {
+                variant: camel(value),
+                number,
+                json,
+            });
+        } else if let Some(message) = &mut message {
+            let (declaration, number) = code.split_once(" = ").expect("a field");
+            let number = number.trim_end_matches(';').parse().expect("a number");
+            let (kind, name) = declaration.rsplit_once(' ').expect("a type and a name");
+            let (label, kind) = if let Some(kind) = kind.strip_prefix("repeated ") {
+                (Label::Repeated, kind)
+            } else if let Some(kind) = kind.strip_prefix("optional ") {
+                (Label::Optional, kind)
+            } else {
+                (Label::Plain, kind)
+            };
+            let field = Field {
+                label,
+                kind: kind.to_owned(),
+                name: name.to_owned(),
+                number,
+            };
+            match &mut oneof {
+                Some((_, fields)) => fields.push(field),
+                None => message.fields.push(field),
+            }
+        }
+    }
+    items
+}
+
+fn write_docs(docs: &[String], indent: &str, out: &mut String) {
+    for line in docs {
+        let _ = writeln!(
+            out,
+            "{indent}///{}{line}",
+            if line.is_empty() { "" } else { " " }
+        );
+    }
+}
+
+fn write_enum(enumeration: &Enum, out: &mut String) {
+    let name = &enumeration.name;
+    write_docs(&enumeration.docs, "", out);
+    out.push_str("#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]\n");
+    let _ = writeln!(out, "#[repr(i32)]\npub enum {name} {{");
+    for value in &enumeration.values {
+        let _ = writeln!(out, "    {} = {},", value.variant, value.number);
+    }
+    out.push_str("}\n");
+    // the unspecified value has no JSON name
+    let names: Vec<_> = enumeration.values[1..]
+        .iter()
+        .map(|value| format!("{:?}", value.json))
+        .collect();
+    let _ = writeln!(
+        out,
+        "impl super::json::Names for {name} {{\n    const NAMES: &'static [&'static str] = &[{}];\n}}",
+        names.join(", ")
+    );
+}
+
+fn write_message(message: &Message, enums: &[String], out: &mut String) {
+    let name = &message.name;
+    let tuple = message
+        .docs
+        .iter()
+        .any(|line| line.starts_with("The JSON form is the array"));
+    let list = message.fields.len() == 1 && message.fields[0].label == Label::Repeated;
+    write_docs(&message.docs, "", out);
+    if tuple || list {
+        out.push_str("#[derive(Clone, PartialEq, ::prost::Message)]\n");
+    } else if message.oneof.is_some() {
+        out.push_str("#[derive(Clone, PartialEq, ::prost::Message, ::serde::Serialize, ::serde::Deserialize)]\n");
+        out.push_str("#[serde(default)]\n");
+    } else {
+        out.push_str("#[derive(Clone, PartialEq, ::prost::Message, ::serde::Serialize, ::serde::Deserialize)]\n");
+        out.push_str("#[serde(default, deny_unknown_fields)]\n");
+    }
+    let _ = writeln!(out, "pub struct {name} {{");
+    for field in &message.fields {
+        write_field(field, !(tuple || list), enums, out);
+    }
+    if let Some((oneof, fields)) = &message.oneof {
+        let tags: Vec<_> = fields.iter().map(|f| f.number.to_string()).collect();
+        let _ = writeln!(
+            out,
+            "    #[prost(oneof = \"{}::{}\", tags = \"{}\")]\n    #[serde(flatten)]\n    pub {oneof}: Option<{0}::{1}>,",
+            snake(name),
+            camel(oneof),
+            tags.join(", ")
+        );
+    }
+    out.push_str("}\n");
+    if tuple {
+        write_tuple(message, out);
+    } else if list {
+        write_list(message, enums, out);
+    }
+    if let Some((oneof, fields)) = &message.oneof {
+        let _ = writeln!(out, "pub mod {} {{", snake(name));
+        out.push_str("    #[derive(Clone, PartialEq, ::prost::Oneof, ::serde::Serialize, ::serde::Deserialize)]\n");
+        let _ = writeln!(
+            out,
+            "    #[serde(rename_all = \"snake_case\")]\n    pub enum {} {{",
+            camel(oneof)
+        );
+        for field in fields {
+            let _ = writeln!(
+                out,
+                "        #[prost(message, tag = \"{}\")]\n        {}(super::{}),",
+                field.number,
+                camel(&field.name),
+                field.kind
+            );
+        }
+        out.push_str("    }\n}\n");
+    }
+}
+
+/// The Rust types of the scalar types.
+fn scalar(kind: &str) -> Option<&'static str> {
+    Some(match kind {
+        "double" => "f64",
+        "bool" => "bool",
+        "uint32" => "u32",
+        "uint64" => "u64",
+        "int32" => "i32",
+        "int64" => "i64",
+        "string" => "String",
+        _ => return None,
+    })
+}
+
+/// The functions that give a field its JSON form, for doubles with
+/// infinities and for enums by their JSON names: whether the field is
+/// repeated or not, `Some` of the serialising and the deserialising one.
+fn json_form(label: Label, kind: &str, enums: &[String]) -> Option<(String, String)> {
+    let module = match (label, kind) {
+        (Label::Repeated, "double") => "doubles",
+        (Label::Optional, "double") => "optional_double",
+        (Label::Plain, "double") => "double",
+        (Label::Repeated, _) if enums.iter().any(|e| e == kind) => {
+            return Some((
+                format!("super::json::enum_names::<{kind}, _>"),
+                format!("super::json::enum_values::<{kind}, _>"),
+            ));
+        }
+        (Label::Plain, _) if enums.iter().any(|e| e == kind) => {
+            return Some((
+                format!("super::json::enum_name::<{kind}, _>"),
+                format!("super::json::enum_value::<{kind}, _>"),
+            ));
+        }
+        _ => return None,
+    };
+    Some((
+        format!("super::json::{module}::serialize"),
+        format!("super::json::{module}::deserialize"),
+    ))
+}
+
+fn write_field(field: &Field, serde: bool, enums: &[String], out: &mut String) {
+    let (label, kind, number) = (field.label, field.kind.as_str(), field.number);
+    let is_enum = enums.iter().any(|e| e == kind);
+    let (prost, rust) = if let Some(value) = kind
+        .strip_prefix("map<string, ")
+        .and_then(|rest| rest.strip_suffix('>'))
+    {
+        let rust = scalar(value).expect("maps of scalars");
+        (
+            format!("btree_map = \"string, {value}\""),
+            format!("::std::collections::BTreeMap<String, {rust}>"),
+        )
+    } else {
+        let (prost, rust) = match scalar(kind) {
+            Some(rust) => (kind.to_owned(), rust.to_owned()),
+            None if is_enum => (format!("enumeration = \"{kind}\""), "i32".to_owned()),
+            None => ("message".to_owned(), kind.to_owned()),
+        };
+        match label {
+            Label::Repeated => (format!("{prost}, repeated"), format!("Vec<{rust}>")),
+            Label::Optional => (format!("{prost}, optional"), format!("Option<{rust}>")),
+            // messages are always optional
+            Label::Plain if prost == "message" => {
+                (format!("{prost}, optional"), format!("Option<{rust}>"))
+            }
+            Label::Plain => (prost, rust),
+        }
+    };
+    let _ = writeln!(out, "    #[prost({prost}, tag = \"{number}\")]");
+    if serde {
+        let mut attributes = vec![];
+        if kind == "map<string, double>" {
+            attributes.push("with = \"super::json::double_map\"".to_owned());
+        } else if let Some((serialize, deserialize)) = json_form(label, kind, enums) {
+            attributes.push(format!("serialize_with = \"{serialize}\""));
+            attributes.push(format!("deserialize_with = \"{deserialize}\""));
+        }
+        // repeated fields are always there, the others only if they differ
+        // from their default
+        if kind.starts_with("map<") {
+            attributes.push(
+                "skip_serializing_if = \"::std::collections::BTreeMap::is_empty\"".to_owned(),
+            );
+        } else if rust.starts_with("Option<") {
+            attributes.push("skip_serializing_if = \"Option::is_none\"".to_owned());
+        } else if label == Label::Plain {
+            attributes.push("skip_serializing_if = \"super::json::is_default\"".to_owned());
+        }
+        if !attributes.is_empty() {
+            let _ = writeln!(out, "    #[serde({})]", attributes.join(", "));
+        }
+    }
+    let _ = writeln!(out, "    pub {}: {rust},", ident(&field.name));
+}
+
+/// A message that is an array of its fields in JSON. Infinite doubles are
+/// null in the JSON of responses, which leaves the first field of a range
+/// at -inf and the others at inf.
+fn write_tuple(message: &Message, out: &mut String) {
+    let name = &message.name;
+    let fields = &message.fields;
+    assert!(
+        fields
+            .iter()
+            .all(|f| f.label == Label::Plain && scalar(&f.kind).is_some()),
+        "the fields of {name} are scalars"
+    );
+    let double = |f: &Field| f.kind == "double";
+    let values: Vec<_> = fields
+        .iter()
+        .map(|f| {
+            if double(f) {
+                format!("super::json::Double(self.{}),", ident(&f.name))
+            } else {
+                format!("self.{},", ident(&f.name))
+            }
+        })
+        .collect();
+    let types: Vec<_> = fields
+        .iter()
+        .map(|f| {
+            if double(f) {
+                "Option<super::json::Double>,".to_owned()
+            } else {
+                format!("{},", scalar(&f.kind).expect("a scalar"))
+            }
+        })
+        .collect();
+    let names: Vec<_> = fields
+        .iter()
+        .map(|f| format!("{},", ident(&f.name)))
+        .collect();
+    let assignments: Vec<_> = fields
+        .iter()
+        .enumerate()
+        .map(|(i, f)| {
+            let name = ident(&f.name);
+            match (double(f), i) {
+                (true, 0) => format!("{name}: {name}.map_or(f64::NEG_INFINITY, |end| end.0),"),
+                (true, _) => format!("{name}: {name}.map_or(f64::INFINITY, |end| end.0),"),
+                (false, _) => format!("{name},"),
+            }
+        })
+        .collect();
+    let _ = writeln!(
+        out,
+        "impl ::serde::Serialize for {name} {{
+    fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {{
+        ::serde::Serialize::serialize(&({}), serializer)
+    }}
+}}
+impl<'de> ::serde::Deserialize<'de> for {name} {{
+    fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {{
+        let ({}): ({}) = ::serde::Deserialize::deserialize(deserializer)?;
+        Ok(Self {{ {} }})
+    }}
+}}",
+        values.join(" "),
+        names.join(" "),
+        types.join(" "),
+        assignments.join(" ")
+    );
+}
+
+/// A message that is the list of the items of its only field in JSON.
+fn write_list(message: &Message, enums: &[String], out: &mut String) {
+    let name = &message.name;
+    let field = &message.fields[0];
+    let (serialize, deserialize) =
+        json_form(field.label, &field.kind, enums).unwrap_or_else(|| {
+            (
+                "::serde::Serialize::serialize".to_owned(),
+                "::serde::Deserialize::deserialize".to_owned(),
+            )
+        });
+    let field = ident(&field.name);
+    let _ = writeln!(
+        out,
+        "impl ::serde::Serialize for {name} {{
+    fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {{
+        {serialize}(&self.{field}, serializer)
+    }}
+}}
+impl<'de> ::serde::Deserialize<'de> for {name} {{
+    fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {{
+        Ok(Self {{ {field}: {deserialize}(deserializer)? }})
+    }}
+}}"
+    );
+}
+
+/// The name of a field in Rust, which is a raw identifier for keywords.
+fn ident(name: &str) -> String {
+    const KEYWORDS: &[&str] = &["type", "match", "ref", "mod", "use", "impl", "in", "move"];
+    if KEYWORDS.contains(&name) {
+        format!("r#{name}")
+    } else {
+        name.to_owned()
+    }
+}
+/// `SCREAMING_SNAKE` or `snake_case` to `CamelCase`.
+fn camel(name: &str) -> String {
+    name.split('_')
+        .map(|word| {
+            let word = word.to_lowercase();
+            let mut chars = word.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_uppercase().chain(chars).collect()
+            })
+        })
+        .collect()
+}
+
+/// `CamelCase` to `snake_case`.
+fn snake(name: &str) -> String {
+    let mut snake = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            snake.push('_');
+        }
+        snake.extend(c.to_lowercase());
+    }
+    snake
+}
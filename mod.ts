@@ -384,12 +384,33 @@ function parse(
   }
 }
 
+/** solution status reported by the solver */
+export type Status =
+  | "optimal"
+  | "feasible"
+  | "unbounded"
+  | "infeasible"
+  | "timelimit"
+  | "nodelimit"
+  | "interrupted"
+  | "stageunbounded"
+  | "stageinfeasible"
+  | "iterationlimit"
+  | "solutionlimit";
+
 /** MILP solution as a map from each {@link Variable.name} to its value */
 export interface Solution {
-  /** solution status */
-  status: "optimal" | "unbounded" | "infeasible";
+  /**
+   * solution status, "feasible" if the solver stopped early with a solution,
+   * one of the limits if it stopped early because of that limit, and one of
+   * the stage statuses if a later objective could not be solved once the
+   * earlier ones were optimal
+   */
+  status: Status;
   /** solution values, may be empty if no solution was found */
   values: Map<string, number>;
+  /** objective value including its constant, absent if no solution was found */
+  objective?: number;
 }
 
 /**
@@ -612,8 +633,9 @@ interface MessageProblem {
   verbose: boolean;
 }
 interface MessageSolution {
-  status: "optimal" | "unbounded" | "infeasible";
+  status: Status;
   values: number[];
+  objective: number | null;
 }
 
 function solve(
@@ -672,26 +694,37 @@ function solve(
   return {
     status: solution.status,
     values: new Map(variables.map((v, i) => [v.name, solution.values[i]])),
+    objective: solution.objective ?? undefined,
   };
 }
 
 const ffi = {
   solve: { parameters: ["buffer", "usize"], result: "pointer" },
-  free: { parameters: ["pointer"], result: "void" },
+  free_string: { parameters: ["pointer"], result: "void" },
 } as const;
 type Ffi = ReturnType<typeof Deno.dlopen<typeof ffi>>["symbols"];
 
+interface MessageError {
+  status: "error";
+  kind: "parse" | "validation" | "solver" | "internal" | "resource_limit";
+  message: string;
+}
+
 function io(ffi: Ffi, msg: MessageProblem): MessageSolution {
   const buf = new TextEncoder().encode(JSON.stringify(msg));
   const ptr = ffi.solve(buf, BigInt(buf.length));
   if (ptr === null) throw new Error("rcv bad buffer");
+  let res: MessageSolution | MessageError;
   try {
     const str = Deno.UnsafePointerView.getCString(ptr);
-    const res: MessageSolution = JSON.parse(str);
-    return res;
+    res = JSON.parse(str);
   } finally {
-    ffi.free(ptr);
+    ffi.free_string(ptr);
+  }
+  if (res.status === "error") {
+    throw new Error(`${res.kind} error: ${res.message}`);
   }
+  return res;
 }
 
 /** options for loading the library */
@@ -0,0 +1,107 @@
+//! Command line interface that solves a problem from a file or stdin, for
+//! shell pipelines and debugging without the C ABI.
+
+use knorpelsolve::{Encoding, solve_bytes};
+use serde_json::{Map, Value};
+use std::io::{self, Read, Write};
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+usage: knorpelsolve [--pretty] [--format json|msgpack] [--time-limit SECONDS] [FILE]
+
+Reads a problem from FILE, or from stdin if FILE is absent or -, and writes
+the solution to stdout. If the problem cannot be solved, the error is written
+to stderr instead and the exit code is 1. Both use the encoding of --format,
+which is json by default.
+
+With the feature server, `knorpelsolve --serve ADDRESS [--time-limit SECONDS]`
+answers POST /solve requests instead.";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    run(&args).unwrap_or_else(|message| {
+        eprintln!("knorpelsolve: {message}\n\n{USAGE}");
+        ExitCode::from(2)
+    })
+}
+
+fn run(args: &[String]) -> Result<ExitCode, String> {
+    let mut encoding = Encoding::Json;
+    let mut overrides = Map::new();
+    let mut path = None;
+    #[cfg(feature = "server")]
+    let mut serve = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| args.next().ok_or(format!("{flag} needs a value"));
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(ExitCode::SUCCESS);
+            }
+            "--pretty" => {
+                overrides.insert("pretty_output".to_owned(), Value::Bool(true));
+            }
+            "--format" => {
+                encoding = match value(arg)?.as_str() {
+                    "json" => Encoding::Json,
+                    "msgpack" => Encoding::MessagePack,
+                    other => return Err(format!("unknown format '{other}'")),
+                };
+            }
+            "--time-limit" => {
+                let seconds = value(arg)?;
+                let seconds: f64 = seconds
+                    .parse()
+                    .map_err(|_| format!("invalid time limit '{seconds}'"))?;
+                overrides.insert("time_limit_seconds".to_owned(), Value::from(seconds));
+            }
+            #[cfg(feature = "server")]
+            "--serve" => serve = Some(value(arg)?.clone()),
+            _ if arg.starts_with('-') && arg != "-" => {
+                return Err(format!("unknown option '{arg}'"));
+            }
+            _ if path.is_none() => path = Some(arg.as_str()),
+            _ => return Err("expected at most one file".to_owned()),
+        }
+    }
+
+    #[cfg(feature = "server")]
+    if let Some(address) = serve {
+        use knorpelsolve::server::{Config, Server};
+        let config = Config {
+            address,
+            time_limit_seconds: overrides.get("time_limit_seconds").and_then(Value::as_f64),
+            ..Config::default()
+        };
+        Server::new(config)
+            .serve()
+            .map_err(|e| format!("could not serve: {e}"))?;
+        return Ok(ExitCode::SUCCESS);
+    }
+    let input = match path {
+        Some(path) if path != "-" => {
+            std::fs::read(path).map_err(|e| format!("could not read {path}: {e}"))?
+        }
+        _ => {
+            let mut input = vec![];
+            io::stdin()
+                .read_to_end(&mut input)
+                .map_err(|e| format!("could not read stdin: {e}"))?;
+            input
+        }
+    };
+    let (mut bytes, code, mut out): (_, _, Box<dyn Write>) =
+        match solve_bytes(&input, encoding, &overrides) {
+            Ok(bytes) => (bytes, ExitCode::SUCCESS, Box::new(io::stdout())),
+            Err(bytes) => (bytes, ExitCode::FAILURE, Box::new(io::stderr())),
+        };
+    // unless `compress_output` made it gzip
+    if encoding == Encoding::Json && !bytes.starts_with(&[0x1f, 0x8b]) {
+        bytes.push(b'\n');
+    }
+    out.write_all(&bytes)
+        .and_then(|()| out.flush())
+        .map_err(|e| format!("could not write the response: {e}"))?;
+    Ok(code)
+}
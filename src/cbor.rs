@@ -0,0 +1,194 @@
+//! CBOR encoding of the same documents that are otherwise exchanged as JSON,
+//! with `ciborium`, decoded into JSON values like [`crate::msgpack`].
+
+use crate::values::Seed;
+use ciborium::de::Error;
+use serde::de::DeserializeSeed;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+/// Decodes a single CBOR item that spans all of `bytes`, as [`Seed`] reads
+/// it. Tags are ignored, and byte strings are not supported.
+pub fn decode(bytes: &[u8]) -> Result<Value, String> {
+    let mut rest = bytes;
+    let Document(value) = ciborium::from_reader(&mut rest).map_err(|e| match e {
+        Error::Io(_) => format!("unexpected end at offset {}", bytes.len()),
+        Error::Syntax(offset) => format!("invalid CBOR item at offset {offset}"),
+        Error::Semantic(Some(offset), message) => format!("{message} at offset {offset}"),
+        Error::Semantic(None, message) => message,
+        Error::RecursionLimitExceeded => "nesting too deep".to_owned(),
+    })?;
+    if !rest.is_empty() {
+        return Err(format!(
+            "trailing bytes at offset {}",
+            bytes.len() - rest.len()
+        ));
+    }
+    Ok(value)
+}
+
+/// Encodes a document with definite lengths, writing each float in the
+/// shortest precision that holds it exactly.
+pub fn encode(value: &impl Serialize) -> Vec<u8> {
+    let mut out = vec![];
+    ciborium::into_writer(value, &mut out).expect("documents can be encoded as CBOR");
+    out
+}
+
+/// The value of a whole item.
+struct Document(Value);
+
+impl<'de> Deserialize<'de> for Document {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Seed::default().deserialize(deserializer).map(Document)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::values::MAX_DEPTH;
+    use serde_json::json;
+
+    fn hex(text: &str) -> Vec<u8> {
+        let digit = |i| u8::from_str_radix(&text[i..i + 2], 16).unwrap();
+        (0..text.len()).step_by(2).map(digit).collect()
+    }
+
+    // the examples of appendix A of RFC 8949
+    #[test]
+    fn decodes_the_examples_of_the_rfc() {
+        let examples = [
+            ("00", json!(0)),
+            ("17", json!(23)),
+            ("1818", json!(24)),
+            ("1903e8", json!(1000)),
+            ("1b000000e8d4a51000", json!(1_000_000_000_000_u64)),
+            ("20", json!(-1)),
+            ("3863", json!(-100)),
+            ("3903e7", json!(-1000)),
+            ("f93e00", json!(1.5)),
+            ("f97bff", json!(65504.0)),
+            ("f90001", json!(5.960_464_477_539_063e-8)),
+            ("fa47c35000", json!(100_000.0)),
+            ("fb3ff199999999999a", json!(1.1)),
+            ("f97c00", json!("inf")),
+            ("fbfff0000000000000", json!("-inf")),
+            ("f4", json!(false)),
+            ("f5", json!(true)),
+            ("f6", Value::Null),
+            ("f7", Value::Null),
+            ("60", json!("")),
+            ("6449455446", json!("IETF")),
+            ("62c3bc", json!("\u{fc}")),
+            ("63e6b0b4", json!("\u{6c34}")),
+            ("8301820203820405", json!([1, [2, 3], [4, 5]])),
+            ("a26161016162820203", json!({"a": 1, "b": [2, 3]})),
+            ("7f657374726561646d696e67ff", json!("streaming")),
+            ("9fff", json!([])),
+            ("9f018202039f0405ffff", json!([1, [2, 3], [4, 5]])),
+            ("bf61610161629f0203ffff", json!({"a": 1, "b": [2, 3]})),
+            (
+                "c074323031332d30332d32315432303a30343a30305a",
+                json!("2013-03-21T20:04:00Z"),
+            ),
+        ];
+        for (bytes, value) in examples {
+            assert_eq!(decode(&hex(bytes)).as_ref(), Ok(&value), "{bytes}");
+        }
+    }
+
+    #[test]
+    fn encodes_the_shortest_heads() {
+        assert_eq!(encode(&json!(23)), hex("17"));
+        assert_eq!(encode(&json!(1_000_000)), hex("1a000f4240"));
+        assert_eq!(encode(&json!(-10)), hex("29"));
+        assert_eq!(encode(&json!(1.1)), hex("fb3ff199999999999a"));
+        assert_eq!(encode(&json!({"a": [true, null]})), hex("a1616182f5f6"));
+    }
+
+    #[test]
+    fn rejects_what_is_not_supported() {
+        let error = |bytes: &str| decode(&hex(bytes)).unwrap_err();
+        assert_eq!(
+            error("40"),
+            "invalid type: byte array, expected a value that JSON can hold"
+        );
+        assert_eq!(error("f97e00"), "NaN is not a number");
+        assert_eq!(error("1c"), "invalid CBOR item at offset 0");
+        assert_eq!(error("ff"), "invalid type: break, expected non-break");
+        assert_eq!(
+            error("a10101"),
+            "invalid type: integer `1`, expected string"
+        );
+        assert_eq!(error("a2616101616102"), "duplicate key 'a'");
+        assert_eq!(error("8201"), "unexpected end at offset 2");
+        assert_eq!(error("0101"), "trailing bytes at offset 1");
+        assert_eq!(error("7f01ff"), "invalid CBOR item at offset 1");
+        assert_eq!(
+            error("3bffffffffffffffff"),
+            "integer -18446744073709551616 is out of range"
+        );
+        // bignums that fit are read like integers
+        assert_eq!(decode(&hex("c24101")), Ok(json!(1)));
+        let deep = vec![0x81; MAX_DEPTH + 2];
+        assert!(
+            decode(&deep)
+                .unwrap_err()
+                .starts_with("nesting deeper than 128")
+        );
+    }
+
+    #[test]
+    fn round_trips_documents() {
+        let value = json!({
+            "names": ["x", "größe", "a".repeat(300)],
+            "values": (0..100).map(|i| f64::from(i) / 7.0).collect::<Vec<_>>(),
+            "halves": [0.5, 1.5, 65504.0, -0.0],
+            "nested": {"empty": {}, "list": [], "min": i64::MIN, "max": u64::MAX},
+        });
+        assert_eq!(decode(&encode(&value)), Ok(value));
+    }
+
+    /// Solves through the C entry point and decodes the response.
+    fn solve(bytes: &[u8]) -> Value {
+        let mut len = 0;
+        let response = unsafe { crate::solve_cbor(bytes.as_ptr(), bytes.len(), &raw mut len) };
+        let value = decode(unsafe { std::slice::from_raw_parts(response, len) });
+        unsafe { crate::free_cbor(response, len) };
+        value.unwrap()
+    }
+
+    #[test]
+    fn solves_problems_with_unicode_names_and_many_floats() {
+        let names: Vec<_> = (0..500).map(|i| format!("größe_{i}_ß")).collect();
+        let weights: Vec<_> = (0..500).map(|i| 1.0 + f64::from(i) / 3.0).collect();
+        let variables: Vec<_> = names.iter().map(|n| json!({"name": n, "max": 1})).collect();
+        let terms: Vec<_> = names
+            .iter()
+            .zip(&weights)
+            .map(|(n, w)| json!({"name": n, "factor": w}))
+            .collect();
+        let problem = json!({
+            "direction": "max",
+            "variables": variables,
+            "objective": terms,
+            "output": "named",
+            "verbose": false
+        });
+        let solution = solve(&encode(&problem));
+        assert_eq!(solution["status"], "optimal");
+        assert_eq!(solution["values"].as_array().unwrap().len(), 500);
+        assert_eq!(solution["named_values"]["größe_7_ß"], 1.0);
+        let objective = solution["objective"].as_f64().unwrap();
+        assert!((objective - weights.iter().sum::<f64>()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn decoding_errors_are_structured() {
+        let error = solve(&hex("a1"));
+        assert_eq!(error["status"], "error");
+        assert_eq!(error["kind"], "parse");
+        assert_eq!(error["message"], "invalid CBOR: unexpected end at offset 1");
+    }
+}
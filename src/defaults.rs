@@ -0,0 +1,119 @@
+//! Options that apply to every problem of the process, for hosts that would
+//! otherwise add the same settings to each problem. The options of a problem
+//! are merged over them key by key.
+
+use crate::{MessageError, Options};
+use serde::{Deserialize, Deserializer};
+use serde_json::{Map, Value};
+use std::sync::{PoisonError, RwLock};
+
+// the object as it was set and the options that it reads as
+static DEFAULTS: RwLock<Option<(Map<String, Value>, Options)>> = RwLock::new(None);
+
+/// Replaces the defaults by a JSON object with the keys of `options`.
+pub fn set(bytes: &[u8]) -> Result<(), MessageError> {
+    let object: Map<String, Value> = serde_json::from_slice(bytes)
+        .map_err(|e| MessageError::parse(format!("invalid default options: {e}")))?;
+    let mut options = Options::deserialize(&Value::Object(object.clone()))
+        .map_err(|e| MessageError::parse(format!("invalid default options: {e}")))?;
+    options.check()?;
+    options.inherited = object.keys().cloned().collect();
+    *DEFAULTS.write().unwrap_or_else(PoisonError::into_inner) = Some((object, options));
+    Ok(())
+}
+
+/// The defaults as they were set, or an empty object.
+pub fn get() -> String {
+    let defaults = DEFAULTS.read().unwrap_or_else(PoisonError::into_inner);
+    let object = defaults.as_ref().map(|(object, _)| object.clone());
+    Value::Object(object.unwrap_or_default()).to_string()
+}
+
+/// The options of problems without any.
+pub fn options() -> Options {
+    let defaults = DEFAULTS.read().unwrap_or_else(PoisonError::into_inner);
+    defaults
+        .as_ref()
+        .map(|(_, options)| options.clone())
+        .unwrap_or_default()
+}
+
+/// Reads the options of a problem over the defaults, and records which keys
+/// were taken from the defaults.
+pub fn under<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Options, D::Error> {
+    let mut object = Map::<String, Value>::deserialize(deserializer)?;
+    let mut inherited = vec![];
+    if let Some((defaults, _)) = &*DEFAULTS.read().unwrap_or_else(PoisonError::into_inner) {
+        for (key, value) in defaults {
+            if !object.contains_key(key) {
+                object.insert(key.clone(), value.clone());
+                inherited.push(key.clone());
+            }
+        }
+    }
+    let mut options =
+        Options::deserialize(&Value::Object(object)).map_err(serde::de::Error::custom)?;
+    options.inherited = inherited;
+    Ok(options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MessageProblem, prepare};
+    use std::sync::Mutex;
+
+    // the defaults are global, so the tests that set them take turns
+    static SERIAL: Mutex<()> = Mutex::new(());
+
+    /// The integrality tolerance that a problem is solved with under the
+    /// given defaults.
+    fn tolerance(defaults: &str, problem: &str) -> Option<f64> {
+        let _serial = SERIAL.lock().unwrap_or_else(PoisonError::into_inner);
+        set(defaults.as_bytes()).unwrap();
+        let problem = format!(r#"{{"variables": [], "objective": [], {problem}}}"#);
+        let input = serde_json::from_str::<MessageProblem>(&problem);
+        set(b"{}").unwrap();
+        let mut input = input.unwrap();
+        prepare(&mut input).unwrap();
+        input.options.integrality_tolerance
+    }
+
+    #[test]
+    fn options_take_precedence_over_the_problem() {
+        let problem = r#""integrality_tolerance": 0.1, "options": {"integrality_tolerance": 0.2}"#;
+        assert_eq!(tolerance("{}", problem), Some(0.2));
+        let defaults = r#"{"integrality_tolerance": 0.3}"#;
+        assert_eq!(tolerance(defaults, problem), Some(0.2));
+    }
+
+    #[test]
+    fn the_problem_takes_precedence_over_defaults() {
+        let defaults = r#"{"integrality_tolerance": 0.3}"#;
+        let problem = r#""integrality_tolerance": 0.1, "options": {"verbose": false}"#;
+        assert_eq!(tolerance(defaults, problem), Some(0.1));
+        let problem = r#""options": {"verbose": false}"#;
+        assert_eq!(tolerance(defaults, problem), Some(0.3));
+    }
+
+    #[test]
+    fn defaults_apply_without_options() {
+        let defaults = r#"{"integrality_tolerance": 0.3}"#;
+        assert_eq!(tolerance(defaults, r#""id": 1"#), Some(0.3));
+        let problem = r#""integrality_tolerance": 0.1"#;
+        assert_eq!(tolerance(defaults, problem), Some(0.1));
+        assert_eq!(tolerance("{}", r#""id": 1"#), None);
+    }
+
+    #[test]
+    fn defaults_are_returned_as_set() {
+        let _serial = SERIAL.lock().unwrap_or_else(PoisonError::into_inner);
+        set(br#"{"verbose": true}"#).unwrap();
+        assert_eq!(get(), r#"{"verbose":true}"#);
+        assert_eq!(options().inherited, ["verbose"]);
+        assert!(set(br#"{"verbose": 1}"#).is_err());
+        assert_eq!(get(), r#"{"verbose":true}"#);
+        set(b"{}").unwrap();
+        assert_eq!(get(), "{}");
+    }
+}
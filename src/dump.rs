@@ -0,0 +1,494 @@
+//! Writer of the built model in the LP and MPS formats that
+//! [`crate::parse_lp`] and [`crate::parse_mps`] read.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write;
+
+#[derive(Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    Lp,
+    Mps,
+}
+
+/// The model as HiGHS holds it, with the matrix by row.
+pub struct Lp {
+    pub maximise: bool,
+    pub costs: Vec<f64>,
+    pub columns: Vec<(f64, f64)>, // lower and upper bound
+    pub integer: Vec<bool>,
+    pub semicontinuous: Vec<bool>,
+    pub rows: Vec<(f64, f64)>,           // lower and upper bound
+    pub entries: Vec<Vec<(usize, f64)>>, // column index and factor per row
+}
+
+/// The model in a text format, with the names that had to be changed to fit
+/// it.
+#[derive(Serialize)]
+pub struct ModelDump {
+    pub format: Format,
+    pub text: String,
+    // written name to the name in the problem, only for changed names
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub column_names: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub row_names: BTreeMap<String, String>,
+}
+
+/// Writes `lp` with the names of its first columns and rows. The remaining
+/// ones are auxiliary and named after their index, like `_c7` and `_r3`.
+pub fn write(
+    lp: &Lp,
+    format: Format,
+    column_names: &[String],
+    row_names: &[Option<String>],
+    offset: f64,
+) -> ModelDump {
+    let mut names = Names::new(format, &[]);
+    let columns: Vec<String> = (0..lp.columns.len())
+        .map(|j| names.claim(column_names.get(j), || format!("_c{j}")))
+        .collect();
+    let column_names = names.changed;
+    // the objective is a row in MPS and has a label in LP
+    let mut names = Names::new(format, &[OBJECTIVE]);
+    let rows: Vec<String> = (0..lp.rows.len())
+        .map(|i| {
+            names.claim(row_names.get(i).and_then(Option::as_ref), || {
+                format!("_r{i}")
+            })
+        })
+        .collect();
+    let text = match format {
+        Format::Lp => write_lp(lp, &columns, &rows, offset),
+        Format::Mps => write_mps(lp, &columns, &rows, offset),
+    };
+    ModelDump {
+        format,
+        text,
+        column_names,
+        row_names: names.changed,
+    }
+}
+
+const OBJECTIVE: &str = "obj";
+
+/// Names that are unique and valid in a format, and the originals of those
+/// that were changed to get there.
+struct Names {
+    format: Format,
+    used: HashSet<String>,
+    changed: BTreeMap<String, String>,
+}
+impl Names {
+    fn new(format: Format, reserved: &[&str]) -> Self {
+        Names {
+            format,
+            used: reserved.iter().map(|&name| name.to_owned()).collect(),
+            changed: BTreeMap::new(),
+        }
+    }
+    /// Picks a name for `original`, or a generated one if there is none.
+    fn claim(&mut self, original: Option<&String>, generated: impl Fn() -> String) -> String {
+        let wanted = original.map_or_else(&generated, |name| sanitize(name, self.format));
+        let mut name = wanted.clone();
+        let mut n = 0;
+        while !self.used.insert(name.clone()) {
+            n += 1;
+            name = format!("{wanted}_{n}");
+        }
+        if let Some(original) = original.filter(|&original| *original != name) {
+            self.changed.insert(name.clone(), original.clone());
+        }
+        name
+    }
+}
+
+/// Turns `name` into an identifier of `format`. The LP format only allows
+/// some ASCII characters, and names must not look like numbers or keywords.
+/// MPS only forbids whitespace.
+fn sanitize(name: &str, format: Format) -> String {
+    const LP_KEYWORDS: &[&str] = &[
+        "minimize",
+        "minimise",
+        "minimum",
+        "min",
+        "maximize",
+        "maximise",
+        "maximum",
+        "max",
+        "subject",
+        "such",
+        "s.t.",
+        "st",
+        "bounds",
+        "bound",
+        "generals",
+        "general",
+        "gen",
+        "binaries",
+        "binary",
+        "semi-continuous",
+        "end",
+        "free",
+        "inf",
+        "infinity",
+    ];
+    let mut name: String = match format {
+        Format::Lp => name
+            .chars()
+            .map(|c| {
+                let valid = c.is_ascii_alphanumeric() || "_.!\"#$%&()/,;?@'{}|~".contains(c);
+                if valid { c } else { '_' }
+            })
+            .collect(),
+        Format::Mps => name
+            .chars()
+            .map(|c| if c.is_whitespace() { '_' } else { c })
+            .collect(),
+    };
+    let reserved = match format {
+        Format::Lp => {
+            name.starts_with(|c: char| c.is_ascii_digit() || c == '.')
+                || LP_KEYWORDS.contains(&name.to_ascii_lowercase().as_str())
+        }
+        // only marker lines have this in the second field
+        Format::Mps => name == "'MARKER'",
+    };
+    if name.is_empty() || reserved {
+        name.insert(0, '_');
+    }
+    name
+}
+
+/// Formats a number so that it reads back exactly, with an exponent if it
+/// is very large or small.
+fn number(value: f64) -> String {
+    let magnitude = value.abs();
+    if magnitude == f64::INFINITY {
+        if value > 0.0 { "inf" } else { "-inf" }.to_owned()
+    } else if magnitude != 0.0 && !(1e-4..1e15).contains(&magnitude) {
+        format!("{value:e}")
+    } else {
+        format!("{value}")
+    }
+}
+
+#[allow(clippy::float_cmp)] // bounds are given, not computed
+fn is_fixed(lower: f64, upper: f64) -> bool {
+    lower == upper
+}
+/// Whether both bounds are finite and different.
+fn is_range(lower: f64, upper: f64) -> bool {
+    lower.is_finite() && upper.is_finite() && !is_fixed(lower, upper)
+}
+
+/// Writes `factor name` terms joined by their signs, or `0 first` for none.
+fn linear(out: &mut String, terms: impl Iterator<Item = (f64, String)>, first: Option<&str>) {
+    let mut empty = true;
+    for (factor, name) in terms {
+        let sign = if factor < 0.0 { "-" } else { "+" };
+        if empty {
+            let sign = if factor < 0.0 { "-" } else { "" };
+            let _ = write!(out, " {sign}{} {name}", number(factor.abs()));
+        } else {
+            let _ = write!(out, " {sign} {} {name}", number(factor.abs()));
+        }
+        empty = false;
+    }
+    if empty && let Some(first) = first {
+        let _ = write!(out, " 0 {first}");
+    }
+}
+
+fn write_lp(lp: &Lp, columns: &[String], rows: &[String], offset: f64) -> String {
+    let mut out = String::from("\\ written by knorpelsolve\n");
+    out.push_str(if lp.maximise {
+        "Maximize\n"
+    } else {
+        "Minimize\n"
+    });
+    let _ = write!(out, " {OBJECTIVE}:");
+    let costs = lp.costs.iter().zip(columns);
+    let costs = costs.filter(|(cost, _)| **cost != 0.0);
+    linear(
+        &mut out,
+        costs.map(|(&cost, name)| (cost, name.clone())),
+        None,
+    );
+    if offset != 0.0 {
+        let sign = if offset < 0.0 { "-" } else { "+" };
+        let _ = write!(out, " {sign} {}", number(offset.abs()));
+    }
+    out.push_str("\nSubject To\n");
+    let first = columns.first().map(String::as_str);
+    for ((name, &(lower, upper)), entries) in rows.iter().zip(&lp.rows).zip(&lp.entries) {
+        let _ = write!(out, " {name}:");
+        if is_range(lower, upper) {
+            let _ = write!(out, " {} <=", number(lower));
+        }
+        let terms = entries
+            .iter()
+            .map(|&(j, factor)| (factor, columns[j].clone()));
+        linear(&mut out, terms, first);
+        let _ = match (lower, upper) {
+            _ if is_fixed(lower, upper) => writeln!(out, " = {}", number(lower)),
+            _ if upper.is_finite() => writeln!(out, " <= {}", number(upper)),
+            _ => writeln!(out, " >= {}", number(lower)),
+        };
+    }
+    out.push_str("Bounds\n");
+    for (name, &(lower, upper)) in columns.iter().zip(&lp.columns) {
+        let _ = match (lower, upper) {
+            (0.0, f64::INFINITY) => Ok(()),
+            (f64::NEG_INFINITY, f64::INFINITY) => writeln!(out, " {name} free"),
+            _ if is_fixed(lower, upper) => writeln!(out, " {name} = {}", number(lower)),
+            (_, f64::INFINITY) => writeln!(out, " {name} >= {}", number(lower)),
+            // both bounds, so that a negative upper bound keeps the lower one
+            _ => writeln!(out, " {} <= {name} <= {}", number(lower), number(upper)),
+        };
+    }
+    let sections = [
+        ("Generals", &lp.integer),
+        ("Semi-Continuous", &lp.semicontinuous),
+    ];
+    for (section, flags) in sections {
+        if flags.contains(&true) {
+            let _ = writeln!(out, "{section}");
+            for (name, _) in columns.iter().zip(flags).filter(|(_, flag)| **flag) {
+                let _ = writeln!(out, " {name}");
+            }
+        }
+    }
+    out.push_str("End\n");
+    out
+}
+
+fn write_mps(lp: &Lp, columns: &[String], rows: &[String], offset: f64) -> String {
+    let mut out = String::from("NAME knorpelsolve\n");
+    if lp.maximise {
+        out.push_str("OBJSENSE\n    MAX\n");
+    }
+    let _ = writeln!(out, "ROWS\n N {OBJECTIVE}");
+    for (name, &(lower, upper)) in rows.iter().zip(&lp.rows) {
+        let kind = match (lower, upper) {
+            _ if is_fixed(lower, upper) => 'E',
+            (_, f64::INFINITY) => 'G',
+            _ => 'L',
+        };
+        let _ = writeln!(out, " {kind} {name}");
+    }
+
+    // the matrix by column
+    let mut entries = vec![vec![]; columns.len()];
+    for (row, names) in lp.entries.iter().zip(rows) {
+        for &(j, factor) in row {
+            entries[j].push((names.as_str(), factor));
+        }
+    }
+    out.push_str("COLUMNS\n");
+    let mut integer = false;
+    for (j, name) in columns.iter().enumerate() {
+        if lp.integer[j] != integer {
+            integer = lp.integer[j];
+            let marker = if integer { "INTORG" } else { "INTEND" };
+            let _ = writeln!(out, "    MARKER 'MARKER' '{marker}'");
+        }
+        // a column without entries still has to be declared
+        if lp.costs[j] != 0.0 || entries[j].is_empty() {
+            let _ = writeln!(out, "    {name} {OBJECTIVE} {}", number(lp.costs[j]));
+        }
+        for &(row, factor) in &entries[j] {
+            let _ = writeln!(out, "    {name} {row} {}", number(factor));
+        }
+    }
+    if integer {
+        out.push_str("    MARKER 'MARKER' 'INTEND'\n");
+    }
+
+    out.push_str("RHS\n");
+    if offset != 0.0 {
+        // the objective row holds the negated constant
+        let _ = writeln!(out, "    RHS {OBJECTIVE} {}", number(-offset));
+    }
+    let mut ranges = vec![];
+    for (name, &(lower, upper)) in rows.iter().zip(&lp.rows) {
+        let rhs = if upper.is_finite() { upper } else { lower };
+        if rhs != 0.0 {
+            let _ = writeln!(out, "    RHS {name} {}", number(rhs));
+        }
+        if is_range(lower, upper) {
+            ranges.push((name, upper - lower));
+        }
+    }
+    if !ranges.is_empty() {
+        out.push_str("RANGES\n");
+        for (name, range) in ranges {
+            let _ = writeln!(out, "    RNG {name} {}", number(range));
+        }
+    }
+
+    out.push_str("BOUNDS\n");
+    for (j, (name, &(lower, upper))) in columns.iter().zip(&lp.columns).enumerate() {
+        let mut bound = |kind: &str, value: Option<f64>| {
+            let value = value.map(|v| format!(" {}", number(v))).unwrap_or_default();
+            let _ = writeln!(out, " {kind} BND {name}{value}");
+        };
+        if lp.semicontinuous[j] {
+            bound("SC", Some(upper));
+        } else if lower == f64::NEG_INFINITY && upper == f64::INFINITY {
+            bound("FR", None);
+            continue;
+        } else if is_fixed(lower, upper) {
+            bound("FX", Some(lower));
+            continue;
+        } else if upper != f64::INFINITY {
+            bound("UP", Some(upper));
+        }
+        // after the upper bound, as a negative one drops the default lower one
+        match lower {
+            f64::NEG_INFINITY => bound("MI", None),
+            0.0 if upper >= 0.0 => {}
+            _ => bound("LO", Some(lower)),
+        }
+    }
+    out.push_str("ENDATA\n");
+    out
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)] // numbers have to read back exactly
+mod tests {
+    use super::*;
+    use crate::{CoeffVar, MessageProblem, parse_lp, parse_mps};
+    use std::collections::HashMap;
+
+    fn lp() -> Lp {
+        let inf = f64::INFINITY;
+        Lp {
+            maximise: true,
+            costs: vec![3.0, -2.0, 0.5, 0.0, 0.0],
+            columns: vec![
+                (0.0, inf),
+                (-inf, inf),
+                (0.0, 8.0),
+                (-1.0, -0.5),
+                (2.0, 2.0),
+            ],
+            integer: vec![true, false, false, true, false],
+            semicontinuous: vec![false, false, true, false, false],
+            rows: vec![(-inf, 4.0), (1.0, 3.0), (1.5, 1.5), (-2.0, inf)],
+            entries: vec![
+                vec![(0, 1.0), (1, 1.0)],
+                vec![(0, 1.0), (2, -1.0)],
+                vec![(3, 2.0)],
+                vec![(1, 1e-7), (2, 1e20)],
+            ],
+        }
+    }
+
+    fn dump(format: Format) -> ModelDump {
+        let columns = ["x", "my var", "z"].map(String::from);
+        let rows = [Some("cap"), Some("min"), None].map(|r| r.map(String::from));
+        write(&lp(), format, &columns, &rows, 2.5)
+    }
+
+    fn terms(terms: &[CoeffVar]) -> Vec<(String, f64)> {
+        let terms = terms.iter().map(|term| match term {
+            CoeffVar::Named { name, factor } => (name.to_string(), *factor),
+            _ => unreachable!("the readers name their terms"),
+        });
+        terms.collect()
+    }
+
+    /// Checks that `problem` is the model of [`lp`] under the given names.
+    fn assert_reads_back(problem: &MessageProblem, columns: &[&str], rows: &[&str]) {
+        let lp = lp();
+        assert!(problem.direction == Some(crate::Direction::Max));
+        assert_eq!(problem.objective_offset, 2.5);
+        let variables: HashMap<_, _> = problem
+            .variables
+            .iter()
+            .map(|v| (v.name.as_ref(), v))
+            .collect();
+        for (j, name) in columns.iter().enumerate() {
+            let v = variables[name];
+            let bounds = (
+                v.min.unwrap_or(f64::NEG_INFINITY),
+                v.max.unwrap_or(f64::INFINITY),
+            );
+            assert_eq!(bounds, lp.columns[j], "{name}");
+            assert_eq!(v.integer, Some(lp.integer[j]), "{name}");
+            assert_eq!(v.semicontinuous, lp.semicontinuous[j], "{name}");
+        }
+        let mut objective = terms(&problem.objective);
+        objective.retain(|(_, cost)| *cost != 0.0);
+        let costs = columns.iter().zip(&lp.costs).filter(|(_, c)| **c != 0.0);
+        let costs: Vec<_> = costs.map(|(name, &c)| ((*name).to_owned(), c)).collect();
+        assert_eq!(objective, costs);
+
+        assert_eq!(problem.ranged_constraints.len(), rows.len());
+        for (i, row) in problem.ranged_constraints.iter().enumerate() {
+            assert_eq!(row.name.as_deref(), Some(rows[i]));
+            assert_eq!((row.lower, row.upper), lp.rows[i], "{}", rows[i]);
+            let entries = lp.entries[i].iter();
+            let entries: Vec<_> = entries.map(|&(j, f)| (columns[j].to_owned(), f)).collect();
+            assert_eq!(terms(&row.terms), entries, "{}", rows[i]);
+        }
+    }
+
+    #[test]
+    fn lp_files_read_back_as_written() {
+        let dump = dump(Format::Lp);
+        let problem = parse_lp(&dump.text).unwrap();
+        let columns = ["x", "my_var", "z", "_c3", "_c4"];
+        assert_reads_back(&problem, &columns, &["cap", "_min", "_r2", "_r3"]);
+        let changed = |names: &[(&str, &str)]| {
+            let names = names.iter();
+            names.map(|&(a, b)| (a.to_owned(), b.to_owned())).collect()
+        };
+        assert_eq!(dump.column_names, changed(&[("my_var", "my var")]));
+        assert_eq!(dump.row_names, changed(&[("_min", "min")]));
+    }
+
+    #[test]
+    fn mps_files_read_back_as_written() {
+        let dump = dump(Format::Mps);
+        let problem = parse_mps(&dump.text).unwrap();
+        let columns = ["x", "my_var", "z", "_c3", "_c4"];
+        assert_reads_back(&problem, &columns, &["cap", "min", "_r2", "_r3"]);
+        assert_eq!(dump.row_names, BTreeMap::new());
+    }
+
+    #[test]
+    fn names_are_made_unique_and_valid() {
+        let mut names = Names::new(Format::Lp, &[OBJECTIVE]);
+        let mut claim = |name: &str| names.claim(Some(&name.to_owned()), String::new);
+        assert_eq!(claim("obj"), "obj_1");
+        assert_eq!(claim("a b"), "a_b");
+        assert_eq!(claim("a-b"), "a_b_1");
+        assert_eq!(claim("2x"), "_2x");
+        assert_eq!(claim("Free"), "_Free");
+        assert_eq!(claim("größe"), "gr__e");
+        assert_eq!(claim(""), "_");
+        assert_eq!(names.changed.len(), 7);
+        assert_eq!(sanitize("'MARKER'", Format::Mps), "_'MARKER'");
+        assert_eq!(sanitize("a-b[1]", Format::Mps), "a-b[1]");
+    }
+
+    #[test]
+    fn numbers_read_back_exactly() {
+        let cases = [
+            (0.1, "0.1"),
+            (-3.0, "-3"),
+            (1e20, "1e20"),
+            (1.5e-7, "1.5e-7"),
+            (f64::INFINITY, "inf"),
+            (f64::NEG_INFINITY, "-inf"),
+        ];
+        for (value, text) in cases {
+            assert_eq!(number(value), text);
+            assert_eq!(text.parse::<f64>().unwrap(), value);
+        }
+    }
+}
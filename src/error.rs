@@ -0,0 +1,89 @@
+//! Errors that are reported back to the caller as JSON documents.
+
+use serde::Serialize;
+use std::fmt;
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    Parse,      // the input could not be decoded
+    Validation, // the input was decoded but does not describe a valid problem
+    Solver,     // HiGHS failed to solve the problem
+    Internal,   // the solution could not be encoded
+    // the problem would take more than the configured resources
+    ResourceLimit,
+}
+
+/// Error document that is returned in place of a solution, serialised as
+/// `{"status":"error","kind":"...","message":"..."}`.
+#[derive(Serialize, Debug)]
+#[serde(tag = "status", rename = "error")]
+pub struct MessageError {
+    pub kind: ErrorKind,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<serde_json::Value>, // copied from the problem if available
+    // position in an expression string, in characters, and the token there
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    // line and column in a problem given as text, counted from 1
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<u32>,
+}
+impl MessageError {
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        MessageError {
+            kind,
+            message: message.into(),
+            id: None,
+            offset: None,
+            token: None,
+            line: None,
+            column: None,
+        }
+    }
+    /// Points the error at a token of an expression string.
+    #[must_use]
+    pub fn at(mut self, offset: usize, token: impl Into<String>) -> Self {
+        self.offset = Some(offset);
+        self.token = Some(token.into());
+        self
+    }
+    /// Points the error at a line of a problem given as text.
+    #[must_use]
+    pub fn on_line(mut self, line: usize) -> Self {
+        self.line = Some(u32::try_from(line).unwrap_or(u32::MAX));
+        self
+    }
+    /// Points the error at a column of its line.
+    #[must_use]
+    pub fn in_column(mut self, column: usize) -> Self {
+        self.column = Some(u32::try_from(column).unwrap_or(u32::MAX));
+        self
+    }
+    pub fn parse(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Parse, message)
+    }
+    pub fn validation(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Validation, message)
+    }
+    pub fn solver(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Solver, message)
+    }
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Internal, message)
+    }
+    pub fn resource_limit(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::ResourceLimit, message)
+    }
+}
+impl fmt::Display for MessageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} error: {}", self.kind, self.message)
+    }
+}
+impl std::error::Error for MessageError {}
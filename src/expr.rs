@@ -0,0 +1,309 @@
+//! Parser for linear constraints and objectives written as strings, such as
+//! `2*x + 3*y - z <= 7`. Offsets count characters from the start.
+
+use crate::Op;
+
+/// Linear expression `sum(factor * name) + constant`.
+#[derive(Default)]
+pub struct Linear {
+    pub terms: Vec<Term>,
+    pub constant: f64,
+}
+pub struct Term {
+    pub name: String,
+    pub factor: f64,
+    pub offset: usize, // of the name
+}
+
+/// Token at which the text stopped making sense.
+#[derive(Debug)]
+pub struct SyntaxError {
+    pub offset: usize,
+    pub token: String, // empty at the end of the text
+    pub message: String,
+}
+
+/// Parses `expr op expr` into `terms op -constant`.
+pub fn constraint(text: &str) -> Result<(Linear, Op), SyntaxError> {
+    let mut parser = Parser::new(text)?;
+    let mut linear = Linear::default();
+    parser.side(&mut linear, 1.0)?;
+    let lexeme = parser.next();
+    let Kind::Compare(op) = lexeme.kind else {
+        return Err(lexeme.error("expected <=, >= or =="));
+    };
+    parser.side(&mut linear, -1.0)?;
+    parser.end()?;
+    Ok((linear, op))
+}
+
+/// Parses a single expression.
+pub fn objective(text: &str) -> Result<Linear, SyntaxError> {
+    let mut parser = Parser::new(text)?;
+    let mut linear = Linear::default();
+    parser.side(&mut linear, 1.0)?;
+    parser.end()?;
+    Ok(linear)
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Number(f64),
+    Name,
+    Star,
+    Plus,
+    Minus,
+    Compare(Op),
+    End,
+}
+struct Lexeme {
+    kind: Kind,
+    offset: usize,
+    text: String,
+}
+impl Lexeme {
+    fn error(&self, message: &str) -> SyntaxError {
+        let found = if self.text.is_empty() {
+            "the end".to_owned()
+        } else {
+            format!("'{}'", self.text)
+        };
+        SyntaxError {
+            offset: self.offset,
+            token: self.text.clone(),
+            message: format!("{message}, found {found}"),
+        }
+    }
+}
+
+struct Parser {
+    lexemes: Vec<Lexeme>, // ends with `Kind::End`
+    pos: usize,
+}
+impl Parser {
+    fn new(text: &str) -> Result<Self, SyntaxError> {
+        Ok(Parser {
+            lexemes: tokenize(text)?,
+            pos: 0,
+        })
+    }
+    fn peek(&self) -> &Lexeme {
+        &self.lexemes[self.pos]
+    }
+    fn next(&mut self) -> &Lexeme {
+        let pos = self.pos;
+        self.pos = (pos + 1).min(self.lexemes.len() - 1);
+        &self.lexemes[pos]
+    }
+    fn end(&mut self) -> Result<(), SyntaxError> {
+        let lexeme = self.next();
+        match lexeme.kind {
+            Kind::End => Ok(()),
+            _ => Err(lexeme.error("expected + or -")),
+        }
+    }
+
+    /// Adds the terms `term { (+|-) term }` times `sign`.
+    fn side(&mut self, into: &mut Linear, sign: f64) -> Result<(), SyntaxError> {
+        let mut factor = sign;
+        loop {
+            self.term(into, factor)?;
+            factor = match self.peek().kind {
+                Kind::Plus => sign,
+                Kind::Minus => -sign,
+                _ => return Ok(()),
+            };
+            self.next();
+        }
+    }
+
+    /// Adds a term `{ + | - } atom { * atom }` with at most one variable
+    /// among its atoms.
+    fn term(&mut self, into: &mut Linear, mut factor: f64) -> Result<(), SyntaxError> {
+        loop {
+            match self.peek().kind {
+                Kind::Plus => {}
+                Kind::Minus => factor = -factor,
+                _ => break,
+            }
+            self.next();
+        }
+        let mut name: Option<(String, usize)> = None;
+        loop {
+            let lexeme = self.next();
+            match lexeme.kind {
+                Kind::Number(value) => factor *= value,
+                Kind::Name if name.is_some() => {
+                    return Err(lexeme.error("expected a linear term"));
+                }
+                Kind::Name => name = Some((lexeme.text.clone(), lexeme.offset)),
+                _ => return Err(lexeme.error("expected a number or a variable")),
+            }
+            if !matches!(self.peek().kind, Kind::Star) {
+                break;
+            }
+            self.next();
+        }
+        match name {
+            Some((name, offset)) => into.terms.push(Term {
+                name,
+                factor,
+                offset,
+            }),
+            None => into.constant += factor,
+        }
+        Ok(())
+    }
+}
+
+/// Splits `text` into lexemes. Names start with a letter or an underscore
+/// and may contain brackets, so that they cover elements of arrays.
+fn tokenize(text: &str) -> Result<Vec<Lexeme>, SyntaxError> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut lexemes = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        i += 1;
+        let kind = match c {
+            _ if c.is_whitespace() => continue,
+            '*' => Kind::Star,
+            '+' => Kind::Plus,
+            '-' => Kind::Minus,
+            '<' | '>' if chars.get(i) == Some(&'=') => {
+                i += 1;
+                Kind::Compare(if c == '<' { Op::Leq } else { Op::Geq })
+            }
+            '=' => {
+                // both = and ==
+                if chars.get(i) == Some(&'=') {
+                    i += 1;
+                }
+                Kind::Compare(Op::Eq)
+            }
+            '0'..='9' | '.' => {
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                // an exponent needs a digit, possibly after a sign
+                let digit_at = |j: usize| chars.get(j).is_some_and(char::is_ascii_digit);
+                if matches!(chars.get(i), Some('e' | 'E')) {
+                    let sign = usize::from(matches!(chars.get(i + 1), Some('+' | '-')));
+                    if digit_at(i + 1 + sign) {
+                        i += 1 + sign;
+                        while digit_at(i) {
+                            i += 1;
+                        }
+                    }
+                }
+                let number: String = chars[start..i].iter().collect();
+                let Ok(value) = number.parse() else {
+                    return Err(SyntaxError {
+                        offset: start,
+                        message: format!("'{number}' is not a number"),
+                        token: number,
+                    });
+                };
+                Kind::Number(value)
+            }
+            _ if c.is_alphabetic() || c == '_' => {
+                while i < chars.len()
+                    && (chars[i].is_alphanumeric() || matches!(chars[i], '_' | '[' | ']'))
+                {
+                    i += 1;
+                }
+                Kind::Name
+            }
+            _ => {
+                return Err(SyntaxError {
+                    offset: start,
+                    token: c.to_string(),
+                    message: format!("unexpected character '{c}'"),
+                });
+            }
+        };
+        lexemes.push(Lexeme {
+            kind,
+            offset: start,
+            text: chars[start..i].iter().collect(),
+        });
+    }
+    lexemes.push(Lexeme {
+        kind: Kind::End,
+        offset: chars.len(),
+        text: String::new(),
+    });
+    Ok(lexemes)
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)] // the numbers are read, not computed
+mod tests {
+    use super::*;
+
+    fn terms(linear: &Linear) -> Vec<(&str, f64)> {
+        let terms = linear.terms.iter();
+        terms.map(|t| (t.name.as_str(), t.factor)).collect()
+    }
+
+    fn error(text: &str) -> (usize, String, String) {
+        let Err(e) = constraint(text) else {
+            panic!("{text} parsed");
+        };
+        (e.offset, e.token, e.message)
+    }
+
+    #[test]
+    fn moves_everything_to_the_left() {
+        let (linear, op) = constraint("2*x + 3 * y[1] - z <= 7 - x + 1").unwrap();
+        assert!(matches!(op, Op::Leq));
+        assert_eq!(
+            terms(&linear),
+            [("x", 2.0), ("y[1]", 3.0), ("z", -1.0), ("x", 1.0)]
+        );
+        assert_eq!(linear.constant, -8.0);
+        assert_eq!(linear.terms[1].offset, 10);
+    }
+
+    #[test]
+    fn reads_signs_products_and_exponents() {
+        let linear = objective("-x * 2 * 1.5 + - -y - 2e-1").unwrap();
+        assert_eq!(terms(&linear), [("x", -3.0), ("y", 1.0)]);
+        assert_eq!(linear.constant, -0.2);
+        // without a digit, the e is a name of its own
+        let e = objective("3e").err().unwrap();
+        assert_eq!(e.message, "expected + or -, found 'e'");
+        assert!(matches!(constraint("x >= 1").unwrap().1, Op::Geq));
+        assert!(matches!(constraint("x = 1").unwrap().1, Op::Eq));
+        assert!(matches!(constraint("x == 1").unwrap().1, Op::Eq));
+    }
+
+    #[test]
+    fn errors_point_at_the_token() {
+        assert_eq!(
+            error("x * y <= 1"),
+            (4, "y".into(), "expected a linear term, found 'y'".into())
+        );
+        assert_eq!(
+            error("x + 1"),
+            (
+                5,
+                String::new(),
+                "expected <=, >= or ==, found the end".into()
+            )
+        );
+        assert_eq!(
+            error("x <= 1.2.3"),
+            (5, "1.2.3".into(), "'1.2.3' is not a number".into())
+        );
+        assert_eq!(
+            error("ä + $ <= 1"),
+            (4, "$".into(), "unexpected character '$'".into())
+        );
+        assert_eq!(
+            error("x <= 1 <= 2"),
+            (7, "<=".into(), "expected + or -, found '<='".into())
+        );
+    }
+}
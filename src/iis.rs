@@ -0,0 +1,112 @@
+//! Deletion filter that shrinks an infeasible problem to an irreducible
+//! infeasible subsystem (IIS) by re-solving it with parts of it removed.
+
+#[cfg(feature = "highs")]
+use crate::BuiltRow;
+#[cfg(feature = "highs")]
+use good_lp::{IntoAffineExpression, Variable};
+#[cfg(feature = "highs")]
+use highs::{HighsModelStatus, RowProblem, Sense};
+use serde::Serialize;
+#[cfg(feature = "highs")]
+use std::collections::HashMap;
+
+/// Bounds and integrality of a variable as seen by the filter.
+pub struct Column {
+    pub min: f64,
+    pub max: f64,
+    pub integer: bool,
+}
+
+/// Conflicting subset of a problem, referenced by input indices.
+#[derive(Serialize)]
+pub struct Iis {
+    pub rows: Vec<usize>, // in the order of `activities`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub row_names: Vec<String>, // of the conflicting rows that have a name
+    pub bounds: Vec<usize>, // variables whose bounds take part in the conflict
+    pub minimal: bool,    // false if the solve limit was hit before the end
+}
+
+/// Runs the deletion filter. Every bound pair and every row is dropped in
+/// turn, and it stays dropped if the rest of the problem remains infeasible.
+/// At most `max_solves` feasibility problems are solved.
+#[cfg(feature = "highs")]
+pub fn explain(vars: &[Variable], columns: &[Column], rows: &[BuiltRow], max_solves: usize) -> Iis {
+    let index: HashMap<_, _> = vars.iter().copied().zip(0..).collect();
+    // the first elements are variable bounds, the rest are rows
+    let mut active = vec![true; columns.len() + rows.len()];
+    let mut minimal = true;
+    for element in 0..active.len() {
+        if element == max_solves {
+            minimal = false;
+            break;
+        }
+        active[element] = false;
+        let (bounds, rows_active) = active.split_at(columns.len());
+        if !is_infeasible(&index, columns, bounds, rows, rows_active) {
+            active[element] = true;
+        }
+    }
+
+    let (bounds, rows_active) = active.split_at(columns.len());
+    let picked = |flags: &[bool]| -> Vec<usize> {
+        flags
+            .iter()
+            .enumerate()
+            .filter_map(|(i, a)| a.then_some(i))
+            .collect()
+    };
+    let picked_rows = picked(rows_active);
+    let row_names = picked_rows
+        .iter()
+        .filter_map(|&i| rows[i].name.clone())
+        .collect();
+    Iis {
+        rows: picked_rows,
+        row_names,
+        bounds: picked(bounds),
+        minimal,
+    }
+}
+
+#[cfg(feature = "highs")]
+fn is_infeasible(
+    index: &HashMap<Variable, usize>,
+    columns: &[Column],
+    bounds: &[bool],
+    rows: &[BuiltRow],
+    rows_active: &[bool],
+) -> bool {
+    let mut problem = RowProblem::default();
+    let cols: Vec<_> = columns
+        .iter()
+        .zip(bounds)
+        .map(|(c, &bounded)| {
+            let (min, max) = if bounded {
+                (c.min, c.max)
+            } else {
+                (f64::NEG_INFINITY, f64::INFINITY)
+            };
+            problem.add_column_with_integrality(0.0, min..=max, c.integer)
+        })
+        .collect();
+    for (row, _) in rows.iter().zip(rows_active).filter(|(_, a)| **a) {
+        // the constant moves to the bounds
+        let constant = row.expr.constant();
+        let (lower, upper) = row.bounds();
+        let factors = (&row.expr)
+            .linear_coefficients()
+            .map(|(v, f)| (cols[index[&v]], f));
+        problem.add_row(lower - constant..=upper - constant, factors);
+    }
+    let Ok(model) = problem.try_optimise(Sense::Minimise) else {
+        return false;
+    };
+    model.try_solve().is_ok_and(|solved| {
+        matches!(
+            solved.status(),
+            HighsModelStatus::Infeasible | HighsModelStatus::UnboundedOrInfeasible
+        )
+    })
+}
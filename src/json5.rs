@@ -0,0 +1,143 @@
+//! Reader for problems in JSON5, which extends JSON with comments, trailing
+//! commas, unquoted keys, single-quoted strings and more forms of numbers.
+//! The text is read by the `json5` crate into the same JSON values as JSON.
+
+use crate::MessageError;
+use crate::values::Seed;
+use serde::de::DeserializeSeed;
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+
+/// Reads a JSON5 value. `Infinity` becomes the string `"inf"` like in the
+/// other formats, and `NaN` and duplicate keys are errors.
+pub fn parse(text: &str) -> Result<Value, MessageError> {
+    let Document(value) = ::json5::from_str(text).map_err(|e| {
+        let error = MessageError::parse(format!("invalid JSON5: {e}"));
+        match e.position() {
+            Some(at) => error.on_line(at.line + 1).in_column(at.column + 1),
+            None => error,
+        }
+    })?;
+    Ok(value)
+}
+
+/// The value of a whole text.
+struct Document(Value);
+
+impl<'de> Deserialize<'de> for Document {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Seed::default().deserialize(deserializer).map(Document)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::values::MAX_DEPTH;
+    use serde_json::json;
+
+    fn error_message(text: &str) -> String {
+        parse(text).unwrap_err().message
+    }
+
+    #[test]
+    fn skips_comments() {
+        let text = "// the problem\n{ /* inline */ a: 1, // after\n b: /* before */ 2 }\n// end";
+        assert_eq!(parse(text).unwrap(), json!({"a": 1, "b": 2}));
+        assert_eq!(parse("/**/1/***/").unwrap(), json!(1));
+        assert!(parse("{a: 1 /* open").is_err());
+    }
+
+    #[test]
+    fn allows_trailing_commas() {
+        assert_eq!(parse("[1, 2, ]").unwrap(), json!([1, 2]));
+        assert_eq!(
+            parse("{a: [1,], b: {c: 1,},}").unwrap(),
+            json!({"a": [1], "b": {"c": 1}})
+        );
+        for text in ["[1,,]", "[,]", "{,}"] {
+            assert!(parse(text).is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn reads_keys_and_strings() {
+        let text = r#"{plain: 1, $dollar_1: 2, 'single': 3, "double": 4, ab: 5, ünï: 6}"#;
+        let expected =
+            json!({"plain": 1, "$dollar_1": 2, "single": 3, "double": 4, "ab": 5, "ünï": 6});
+        assert_eq!(parse(text).unwrap(), expected);
+        let text = r#"['it\'s', "say \"hi\"", 'a\x41é😀', 'tab\tline\nnul\0', 'one \
+two', '\q', '😀']"#;
+        let expected = json!([
+            "it's",
+            "say \"hi\"",
+            "aAé😀",
+            "tab\tline\nnul\0",
+            "one two",
+            "q",
+            "😀"
+        ]);
+        assert_eq!(parse(text).unwrap(), expected);
+        assert!(parse("'a\nb'").is_err());
+        assert!(error_message("{a: 1, a: 2}").starts_with("invalid JSON5: duplicate key 'a'"));
+    }
+
+    #[test]
+    fn reads_numbers() {
+        let text = "[0x1F, 0XfF, -0x10, +1, .5, 5., 1e3, -2.5E-1, 0, 18446744073709551615]";
+        let expected = json!([
+            31,
+            255,
+            -16,
+            1,
+            0.5,
+            5.0,
+            1000.0,
+            -0.25,
+            0,
+            18_446_744_073_709_551_615_u64
+        ]);
+        assert_eq!(parse(text).unwrap(), expected);
+        assert!(parse("012").is_err());
+    }
+
+    #[test]
+    fn reads_infinity_and_rejects_nan() {
+        let text = "[Infinity, +Infinity, -Infinity]";
+        assert_eq!(parse(text).unwrap(), json!(["inf", "inf", "-inf"]));
+        assert!(error_message("{x: NaN}").starts_with("invalid JSON5: NaN is not a number"));
+        assert!(error_message("-NaN").starts_with("invalid JSON5: NaN is not a number"));
+        assert!(parse("[inf]").is_err());
+    }
+
+    #[test]
+    fn locates_errors() {
+        let error = parse("{\n  a: 1,\r\n  b 2\n}").unwrap_err();
+        assert_eq!(error.line, Some(3));
+        assert!(
+            error.message.starts_with("invalid JSON5: "),
+            "{}",
+            error.message
+        );
+        assert!(parse("{} x").is_err());
+        assert!(parse("").is_err());
+    }
+
+    #[test]
+    fn limits_nesting() {
+        let deep = format!("{}{}", "[".repeat(MAX_DEPTH), "]".repeat(MAX_DEPTH));
+        assert!(parse(&deep).is_ok());
+        let deeper = format!("[{deep}]");
+        assert!(
+            error_message(&deeper)
+                .starts_with(&format!("invalid JSON5: nesting deeper than {MAX_DEPTH}"))
+        );
+    }
+
+    #[test]
+    fn reads_json() {
+        let text = r#"{"variables": [{"name": "x", "max": 1.5e2, "integer": false}], "objective": [], "id": null}"#;
+        let expected: Value = serde_json::from_str(text).unwrap();
+        assert_eq!(parse(text).unwrap(), expected);
+    }
+}
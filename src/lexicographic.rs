@@ -0,0 +1,49 @@
+//! Lexicographic optimisation over several objectives. Each objective is
+//! solved among the solutions that keep the previous ones at their optimum.
+
+#[cfg(feature = "highs")]
+use crate::raw;
+#[cfg(feature = "highs")]
+use highs::{Model, SolvedModel};
+#[cfg(feature = "highs")]
+use highs_sys::HighsInt;
+
+/// Objective as set in HiGHS, without its offset.
+pub struct Stage {
+    pub costs: Vec<(usize, f64)>, // column indices and factors
+    pub maximise: bool,
+    pub offset: f64,
+    pub tolerance: f64, // absolute, for keeping it at its optimum
+}
+
+/// Keeps the objective of `solved`, which was `current`, within its
+/// tolerance of the optimum, and replaces it by `next`. Returns the model to
+/// solve next, or nothing if HiGHS rejects the changes.
+#[cfg(feature = "highs")]
+pub fn switch(solved: SolvedModel, current: &Stage, next: &Stage) -> Option<Model> {
+    let optimum = solved.objective_value();
+    let mut model = Model::from(solved);
+    // the costs of auxiliary columns belong to the objective, too
+    let costs = raw::costs(&model)?;
+    let (index, factors): (Vec<_>, Vec<_>) = costs
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| **c != 0.0)
+        .map(|(col, &c)| Some((HighsInt::try_from(col).ok()?, c)))
+        .collect::<Option<Vec<_>>>()?
+        .into_iter()
+        .unzip();
+    let (lower, upper) = if current.maximise {
+        (optimum - current.tolerance, f64::INFINITY)
+    } else {
+        (f64::NEG_INFINITY, optimum + current.tolerance)
+    };
+    if !raw::add_row(&mut model, lower, upper, &index, &factors) {
+        return None;
+    }
+    let mut costs = vec![0.0; costs.len()];
+    for &(col, factor) in &next.costs {
+        costs[col] += factor;
+    }
+    raw::set_objective(&mut model, &costs, next.maximise).then_some(model)
+}
@@ -1,23 +1,282 @@
+// without HiGHS, the parts of a built problem that only it reads go unused
+#![cfg_attr(not(feature = "highs"), allow(dead_code))]
+
 use good_lp::{
-    Expression, IntoAffineExpression, ProblemVariables, Solution, SolverModel, Variable,
-    VariableDefinition, constraint, highs,
+    Expression, IntoAffineExpression, ProblemVariables, Variable, VariableDefinition, constraint,
 };
+#[cfg(feature = "highs")]
+use good_lp::{SolverModel, highs};
+#[cfg(feature = "highs")]
+use highs::{HighsModelStatus, HighsSolutionStatus};
 
-use serde::{Deserialize, Serialize};
-use std::ffi::CString;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ffi::{CStr, CString};
+use std::fmt::Write;
+use std::io::Read;
 use std::os::raw::c_char;
-use std::{slice, vec};
+use std::path::Path;
+#[cfg(feature = "json5")]
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(not(all(feature = "wasm", target_arch = "wasm32")))]
+use std::time::Instant;
+use std::{iter, slice, vec};
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+use wasm::Instant;
+
+mod cbor;
+mod defaults;
+mod dump;
+mod error;
+mod expr;
+mod iis;
+#[cfg(feature = "json5")]
+mod json5;
+mod lexicographic;
+mod lp;
+mod memory;
+#[cfg(feature = "microlp")]
+mod microlp;
+mod mmap;
+mod mps;
+mod msgpack;
+mod names;
+mod number;
+mod options;
+#[cfg(feature = "highs")]
+mod piecewise;
+mod pool;
+mod proto;
+#[cfg(feature = "highs")]
+mod raw;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "highs")]
+mod solfile;
+#[cfg(feature = "highs")]
+mod sos;
+mod strict;
+mod template;
+mod upload;
+mod values;
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+mod wasm;
+#[cfg(feature = "yaml")]
+mod yaml;
+
+#[cfg(not(any(feature = "highs", feature = "microlp")))]
+compile_error!("a backend is needed, the highs or the microlp feature");
+
+pub use error::{ErrorKind, MessageError};
+pub use lp::parse_lp;
+pub use mps::parse_mps;
+pub use names::NameMatching;
 
 #[derive(Deserialize)]
-pub struct VariableDef {
-    pub name: String,
+pub struct VariableDef<'a> {
+    #[serde(borrow)]
+    pub name: Cow<'a, str>,
+    #[serde(default, deserialize_with = "number::min_bound")]
     pub min: Option<f64>,
+    #[serde(default, deserialize_with = "number::max_bound")]
     pub max: Option<f64>,
     pub initial: Option<f64>,
-    pub integer: bool,
+    // false if absent and not set by `variable_defaults`
+    #[serde(default)]
+    pub integer: Option<bool>,
+    // integer with bounds within [0, 1], which default to 0 and 1
+    #[serde(default)]
+    pub binary: bool,
+    // zero or within [min, max], semi-integer if also integer
+    #[serde(default)]
+    pub semicontinuous: bool,
+    // sets both bounds, which must admit the value if given
+    #[serde(default)]
+    pub fixed: Option<f64>,
+    // objective factor, added to any factor in `objective`
+    #[serde(default)]
+    pub cost: Option<f64>,
+    // higher is branched on first, only for integer variables and only as a
+    // hint, because HiGHS has no branching priorities
+    #[serde(default)]
+    pub branch_priority: Option<i32>,
+}
+impl VariableDef<'_> {
+    fn is_integer(&self) -> bool {
+        self.integer == Some(true)
+    }
+    /// Fills in what the variable leaves open. Binary variables keep their
+    /// own default bounds.
+    fn apply_defaults(&mut self, defaults: &VariableDefaults) {
+        if !self.binary {
+            self.min = self.min.or(defaults.min);
+            self.max = self.max.or(defaults.max);
+        }
+        self.integer = self.integer.or(defaults.integer);
+    }
+    /// Drops infinite bounds, which are the same as absent ones.
+    fn drop_infinite_bounds(&mut self) {
+        self.min = self.min.filter(|&min| min != f64::NEG_INFINITY);
+        self.max = self.max.filter(|&max| max != f64::INFINITY);
+    }
+    /// Turns a binary variable into an integer variable with bounds.
+    fn expand_binary(&mut self) -> Result<(), MessageError> {
+        if !self.binary {
+            return Ok(());
+        }
+        let (min, max) = (self.min.unwrap_or(0.0), self.max.unwrap_or(1.0));
+        if !(0.0..=1.0).contains(&min) || !(0.0..=1.0).contains(&max) {
+            return Err(MessageError::validation(format!(
+                "binary variable '{}' has bounds [{min}, {max}] outside of [0, 1]",
+                self.name
+            )));
+        }
+        self.integer = Some(true);
+        self.min = Some(min);
+        self.max = Some(max);
+        Ok(())
+    }
+    /// Pins a variable with a fixed value to it.
+    fn expand_fixed(&mut self) -> Result<(), MessageError> {
+        let Some(value) = self.fixed else {
+            return Ok(());
+        };
+        let min = self.min.unwrap_or(f64::NEG_INFINITY);
+        let max = self.max.unwrap_or(f64::INFINITY);
+        if !value.is_finite() || !(min..=max).contains(&value) {
+            return Err(MessageError::validation(format!(
+                "variable '{}' is fixed to {value} outside of its bounds [{min}, {max}]",
+                self.name
+            )));
+        }
+        if self.is_integer() && value.fract() != 0.0 {
+            return Err(MessageError::validation(format!(
+                "integer variable '{}' is fixed to the fractional value {value}",
+                self.name
+            )));
+        }
+        self.min = Some(value);
+        self.max = Some(value);
+        Ok(())
+    }
+    /// Rejects semicontinuous variables without finite bounds.
+    fn check_semicontinuous(&self) -> Result<(), MessageError> {
+        let finite = |bound: Option<f64>| bound.is_some_and(f64::is_finite);
+        if self.semicontinuous && !(finite(self.min) && finite(self.max)) {
+            return Err(MessageError::validation(format!(
+                "semicontinuous variable '{}' needs a finite min and max",
+                self.name
+            )));
+        }
+        Ok(())
+    }
+}
+/// Further objective for lexicographic optimisation.
+#[derive(Deserialize)]
+pub struct ObjectiveStage<'a> {
+    pub direction: Direction,
+    #[serde(borrow, deserialize_with = "objective_terms")]
+    pub terms: Vec<CoeffVar<'a>>,
+    #[serde(default)]
+    pub offset: f64,
+    // absolute and in the direction of the objective, defaults to 1e-6
+    #[serde(default)]
+    pub tolerance: Option<f64>,
+    // only with `blend`, relative to `objective`, defaults to 1
+    #[serde(default)]
+    pub weight: Option<f64>,
+}
+/// Initial value of a single variable.
+#[derive(Deserialize)]
+pub struct StartValue<'a> {
+    #[serde(borrow)]
+    pub name: Cow<'a, str>,
+    pub value: f64,
+}
+/// Variables with shared settings named like `base[i][j]` for a shape of two
+/// dimensions.
+#[derive(Deserialize)]
+pub struct VariableArray<'a> {
+    #[serde(borrow)]
+    pub name: Cow<'a, str>,
+    pub shape: Vec<usize>,
+    #[serde(default, deserialize_with = "number::min_bound")]
+    pub min: Option<f64>,
+    #[serde(default, deserialize_with = "number::max_bound")]
+    pub max: Option<f64>,
+    #[serde(default)]
+    pub integer: Option<bool>,
+    #[serde(default)]
+    pub binary: bool,
+    #[serde(default)]
+    pub semicontinuous: bool,
+    #[serde(default)]
+    pub cost: Option<f64>,
+}
+/// Settings of all variables that do not set them themselves.
+#[derive(Deserialize, Default)]
+pub struct VariableDefaults {
+    #[serde(default, deserialize_with = "number::min_bound")]
+    pub min: Option<f64>,
+    #[serde(default, deserialize_with = "number::max_bound")]
+    pub max: Option<f64>,
+    #[serde(default)]
+    pub integer: Option<bool>,
+}
+impl<'a> VariableArray<'a> {
+    /// The variables of the array in row-major order.
+    fn expand(&self) -> impl Iterator<Item = VariableDef<'a>> + '_ {
+        let count = self.shape.iter().product::<usize>();
+        (0..count).map(move |mut flat| {
+            let mut indices = vec![0; self.shape.len()];
+            for (index, &len) in indices.iter_mut().zip(&self.shape).rev() {
+                *index = flat % len;
+                flat /= len;
+            }
+            VariableDef {
+                name: element_name(&self.name, &indices).into(),
+                min: self.min,
+                max: self.max,
+                initial: None,
+                integer: self.integer,
+                binary: self.binary,
+                semicontinuous: self.semicontinuous,
+                fixed: None,
+                cost: self.cost,
+                branch_priority: None,
+            }
+        })
+    }
+}
+fn element_name(array: &str, indices: &[usize]) -> String {
+    let mut name = array.to_owned();
+    for i in indices {
+        let _ = write!(name, "[{i}]");
+    }
+    name
 }
-impl From<&VariableDef> for VariableDefinition {
-    fn from(value: &VariableDef) -> Self {
+impl From<&VariableDef<'_>> for iis::Column {
+    fn from(value: &VariableDef<'_>) -> Self {
+        let min = value.min.unwrap_or(f64::NEG_INFINITY);
+        let max = value.max.unwrap_or(f64::INFINITY);
+        // the filter cannot express the gap between zero and the bounds
+        let (min, max) = if value.semicontinuous {
+            (min.min(0.0), max.max(0.0))
+        } else {
+            (min, max)
+        };
+        iis::Column {
+            min,
+            max,
+            integer: value.is_integer(),
+        }
+    }
+}
+impl From<&VariableDef<'_>> for VariableDefinition {
+    fn from(value: &VariableDef<'_>) -> Self {
         let mut res = Self::new().name(value.name.clone());
         if let Some(min) = value.min {
             res = res.min(min);
@@ -28,78 +287,1095 @@ impl From<&VariableDef> for VariableDefinition {
         if let Some(initial) = value.initial {
             res = res.initial(initial);
         }
-        if value.integer {
+        if value.is_integer() {
             res = res.integer();
         }
         res
     }
 }
 
-#[derive(Deserialize, PartialEq, Eq)]
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum Direction {
     Min,
     Max,
 }
+#[derive(Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Output {
+    /// only `values`, ordered like the input variables
+    #[default]
+    Positional,
+    /// `values` plus `named_values`, keyed by variable name
+    Named,
+}
+#[derive(Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NonFinite {
+    /// fail with a solver error naming the variable
+    #[default]
+    Error,
+    /// report the value as the string `"inf"`, `"-inf"`, or `"nan"`
+    String,
+}
+/// What happens to a variable that occurs more than once in a term list.
+#[derive(Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicatePolicy {
+    /// add up the factors
+    #[default]
+    Sum,
+    /// fail with a validation error naming the variable
+    Error,
+    /// keep only the factor of the last occurrence
+    Last,
+}
+impl DuplicatePolicy {
+    /// Applies the policy to terms of variable index and factor. For
+    /// [`DuplicatePolicy::Last`], the factors of all earlier occurrences
+    /// become zero. Fails with the index of the first repeated variable.
+    fn apply(self, terms: &mut [(usize, f64)]) -> Result<(), usize> {
+        match self {
+            DuplicatePolicy::Sum => {}
+            DuplicatePolicy::Error => {
+                let mut seen = HashSet::new();
+                if let Some(&(i, _)) = terms.iter().find(|(i, _)| !seen.insert(*i)) {
+                    return Err(i);
+                }
+            }
+            DuplicatePolicy::Last => {
+                let last: HashMap<_, _> = terms.iter().enumerate().map(|(k, t)| (t.0, k)).collect();
+                for (k, (i, factor)) in terms.iter_mut().enumerate() {
+                    if last[i] != k {
+                        *factor = 0.0;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+/// Settings for solving and encoding the response.
+#[derive(Deserialize, Default, Clone)]
+#[allow(clippy::struct_excessive_bools)] // independent flags of the protocol
+pub struct Options {
+    #[serde(default)]
+    pub pretty_output: bool, // indented instead of compact JSON
+    // round all reported numbers except for stats, keeps full precision if absent
+    #[serde(default)]
+    pub decimal_places: Option<u8>,
+    // return the HiGHS log in `log` instead of printing it
+    #[serde(default)]
+    pub capture_log: bool,
+    // gzip the response, which needs an entry point that returns its length
+    #[serde(default)]
+    pub compress_output: bool,
+    // stop the solver after this much wall-clock time
+    #[serde(default)]
+    pub time_limit_seconds: Option<f64>,
+    // stop a MIP once the relative gap is below this, reported in `mip_gap`
+    #[serde(default)]
+    pub mip_gap: Option<f64>,
+    // at most as many as there are cores, the HiGHS default if absent or 0
+    #[serde(default)]
+    pub threads: Option<u32>,
+    // print the detailed HiGHS log, or capture it with `capture_log`
+    #[serde(default)]
+    pub verbose: Option<bool>,
+    // for the randomised parts of HiGHS, at most 2147483647, reported in `stats`
+    #[serde(default)]
+    pub seed: Option<u32>,
+    #[serde(default)]
+    pub presolve: Option<Toggle>,
+    // for LPs, and only the simplex variants also for the relaxations of MIPs
+    #[serde(default)]
+    pub solver_algorithm: Option<Algorithm>,
+    // whether interior point solutions are made basic, only for ipm
+    #[serde(default)]
+    pub crossover: Option<bool>,
+    // absolute, above 0 and at most 0.1, the ones used are reported in `stats`
+    #[serde(default)]
+    pub primal_feasibility_tolerance: Option<f64>,
+    #[serde(default)]
+    pub dual_feasibility_tolerance: Option<f64>,
+    // for HiGHS and for checking and rounding integer variables, between 0 and
+    // 0.5, defaults to the HiGHS tolerance of 1e-6
+    #[serde(default)]
+    pub integrality_tolerance: Option<f64>,
+    // branch-and-bound nodes of a MIP before stopping, 0 for the root only
+    #[serde(default)]
+    pub node_limit: Option<u64>,
+    // stop a MIP with `feasible` once this many better incumbents were found
+    #[serde(default)]
+    pub improving_solution_limit: Option<u32>,
+    // reject problems whose estimated memory use is larger, see `stats.memory`
+    #[serde(default)]
+    pub memory_limit_mb: Option<u64>,
+    // how the simplex solver scales the matrix, compare `stats.coefficients`
+    #[serde(default)]
+    pub scaling: Option<Scaling>,
+    // trade-off of a MIP between finding solutions and proving the bound,
+    // balanced by default, see `stats.emphasis` for the options it sets
+    #[serde(default)]
+    pub emphasis: Option<Emphasis>,
+    // further HiGHS options by name, set after all others so that they take
+    // precedence, with warnings about the ones that HiGHS rejects
+    #[serde(default)]
+    pub raw_options: BTreeMap<String, serde_json::Value>,
+    // one of `SOLVERS`, highs by default if it is compiled in, see `microlp`
+    // for what that one lacks
+    #[serde(default)]
+    pub solver: Option<String>,
+    // respond in YAML, only read by `solve_yaml`
+    #[cfg(feature = "yaml")]
+    #[serde(default)]
+    pub yaml_output: bool,
+    // keys that come from `set_default_options` rather than the problem
+    #[serde(skip)]
+    inherited: Vec<String>,
+}
+impl Options {
+    /// Combines a setting with the one of the problem outside of `options`,
+    /// which takes precedence over the defaults but not over `options`.
+    fn or_problem<T>(&self, key: &str, option: Option<T>, problem: Option<T>) -> Option<T> {
+        if self.inherited.iter().any(|k| k == key) {
+            problem.or(option)
+        } else {
+            option.or(problem)
+        }
+    }
+    /// Rejects settings that HiGHS would not accept, and returns warnings
+    /// about the ones that are ignored.
+    fn check(&self) -> Result<Vec<String>, MessageError> {
+        if let Some(seconds) = self.time_limit_seconds
+            && (seconds.is_nan() || seconds <= 0.0)
+        {
+            return Err(MessageError::validation(format!(
+                "time_limit_seconds must be positive, not {seconds}"
+            )));
+        }
+        if let Some(gap) = self.mip_gap
+            && !(0.0..1.0).contains(&gap)
+        {
+            return Err(MessageError::validation(format!(
+                "mip_gap must be at least 0 and less than 1, not {gap}"
+            )));
+        }
+        if let Some(seed) = self.seed
+            && i32::try_from(seed).is_err()
+        {
+            return Err(MessageError::validation(format!(
+                "seed must be at most {}, not {seed}",
+                i32::MAX
+            )));
+        }
+        let tolerances = [
+            ("primal", self.primal_feasibility_tolerance),
+            ("dual", self.dual_feasibility_tolerance),
+        ];
+        for (kind, tolerance) in tolerances {
+            if let Some(tolerance) = tolerance
+                && !(tolerance > 0.0 && tolerance <= 0.1)
+            {
+                return Err(MessageError::validation(format!(
+                    "{kind}_feasibility_tolerance must be above 0 and at most 0.1, not {tolerance}"
+                )));
+            }
+        }
+        if let Some(tolerance) = self.integrality_tolerance
+            && !(tolerance > 0.0 && tolerance < 0.5)
+        {
+            return Err(MessageError::validation(format!(
+                "integrality_tolerance must be between 0 and 0.5, not {tolerance}"
+            )));
+        }
+        if self.improving_solution_limit == Some(0) {
+            return Err(MessageError::validation(
+                "improving_solution_limit must be at least 1",
+            ));
+        }
+        if let Some(solver) = &self.solver
+            && !SOLVERS.contains(&solver.as_str())
+        {
+            return Err(MessageError::validation(format!(
+                "solver '{solver}' is not available, the available solvers are {}",
+                SOLVERS.join(", ")
+            )));
+        }
+        let mut warnings = vec![];
+        if self.crossover.is_some() && self.solver_algorithm != Some(Algorithm::Ipm) {
+            warnings.push("crossover is ignored unless solver_algorithm is ipm".to_owned());
+        }
+        Ok(warnings)
+    }
+    /// Passes the solver settings to HiGHS, and returns a warning about the
+    /// raw options that it rejects.
+    #[cfg(feature = "highs")]
+    fn apply(&self, model: &mut highs::Model, is_lp: bool) -> Option<String> {
+        if self.verbose == Some(true) {
+            model.set_option("output_flag", true);
+            model.set_option("log_to_console", true);
+            model.set_option("log_dev_level", 2);
+        }
+        if let Some(seconds) = self.time_limit_seconds {
+            model.set_option("time_limit", seconds);
+        }
+        if let Some(gap) = self.mip_gap {
+            model.set_option("mip_rel_gap", gap);
+        }
+        if let Some(threads) = self.threads() {
+            model.set_option("threads", i32::try_from(threads).unwrap_or(i32::MAX));
+            model.set_option("parallel", if threads == 1 { "off" } else { "on" });
+        }
+        if let Some(seed) = self.seed.and_then(|s| i32::try_from(s).ok()) {
+            model.set_option("random_seed", seed);
+        }
+        if let Some(tolerance) = self.primal_feasibility_tolerance {
+            model.set_option("primal_feasibility_tolerance", tolerance);
+        }
+        if let Some(tolerance) = self.dual_feasibility_tolerance {
+            model.set_option("dual_feasibility_tolerance", tolerance);
+        }
+        if let Some(tolerance) = self.integrality_tolerance {
+            model.set_option("mip_feasibility_tolerance", tolerance);
+        }
+        if let Some(nodes) = self.node_limit {
+            // larger limits become the HiGHS default, which is no limit in practice
+            let nodes = i32::try_from(nodes).unwrap_or(i32::MAX);
+            model.set_option("mip_max_nodes", nodes);
+        }
+        if let Some(solutions) = self.improving_solution_limit {
+            let solutions = i32::try_from(solutions).unwrap_or(i32::MAX);
+            model.set_option("mip_max_improving_sols", solutions);
+        }
+        if let Some(presolve) = self.presolve {
+            model.set_option("presolve", presolve.as_str());
+        }
+        if let Some(scaling) = self.scaling {
+            model.set_option("simplex_scale_strategy", scaling as i32);
+        }
+        if let Some(emphasis) = self.emphasis {
+            for &(name, setting) in options::emphasis(emphasis) {
+                setting.apply(model, name);
+            }
+        }
+        match self.solver_algorithm {
+            // HiGHS would solve only the relaxation of a MIP with another solver
+            Some(Algorithm::Ipm) if is_lp => {
+                model.set_option("solver", "ipm");
+                if let Some(crossover) = self.crossover {
+                    model.set_option("run_crossover", if crossover { "on" } else { "off" });
+                }
+            }
+            Some(Algorithm::Ipm) | None => {}
+            Some(algorithm) => {
+                if is_lp {
+                    model.set_option("solver", "simplex");
+                }
+                let primal = algorithm == Algorithm::Simplex;
+                model.set_option("simplex_strategy", if primal { 4 } else { 1 });
+            }
+        }
+        let rejected: Vec<_> = self
+            .raw_options
+            .iter()
+            .filter(|(name, value)| !raw::set_option(model, name, value))
+            .map(|(name, _)| format!("'{name}'"))
+            .collect();
+        (!rejected.is_empty()).then(|| {
+            format!(
+                "HiGHS rejected the raw options {}, which are unknown or have invalid values",
+                rejected.join(", ")
+            )
+        })
+    }
+    /// The backend that `solver` names, which `check` made sure is
+    /// available.
+    fn backend(&self) -> Backend {
+        match self.solver.as_deref().unwrap_or(SOLVERS[0]) {
+            #[cfg(feature = "highs")]
+            "highs" => Backend::Highs,
+            #[cfg(feature = "microlp")]
+            "microlp" => Backend::Microlp,
+            other => unreachable!("checked solver '{other}'"),
+        }
+    }
+    /// The number of threads that HiGHS is told to use, if any.
+    fn threads(&self) -> Option<u32> {
+        let cores = std::thread::available_parallelism().map_or(1, usize::from);
+        let cores = u32::try_from(cores).unwrap_or(u32::MAX);
+        self.threads.filter(|&n| n > 0).map(|n| n.min(cores))
+    }
+}
+/// Solver that a problem is built for.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Backend {
+    #[cfg(feature = "highs")]
+    Highs,
+    #[cfg(feature = "microlp")]
+    Microlp,
+}
+/// Setting of HiGHS that can be turned on or off, or left to HiGHS.
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Toggle {
+    On,
+    Off,
+    Choose,
+}
+impl Toggle {
+    fn as_str(self) -> &'static str {
+        match self {
+            Toggle::On => "on",
+            Toggle::Off => "off",
+            Toggle::Choose => "choose",
+        }
+    }
+}
+/// Method of HiGHS for solving LPs.
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Algorithm {
+    Simplex, // primal
+    DualSimplex,
+    Ipm, // interior point
+}
+/// Scaling strategy of the simplex solver, in the order of the values of
+/// `simplex_scale_strategy`.
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Scaling {
+    Off,
+    Choose,
+    Equilibration, // the HiGHS default
+    ForcedEquilibration,
+    MaxValue,
+}
+/// What the MIP solver spends its time on.
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Emphasis {
+    Balanced,
+    Feasibility, // finding good solutions early
+    Optimality,  // proving the bound
+}
+/// Request for the built model in `model_dump` of the solution.
 #[derive(Deserialize)]
-pub struct CoeffVar {
+pub struct DumpModel {
+    pub format: dump::Format,
+}
+/// Factor of a variable that is referenced either by name or by its
+/// position in `variables`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum CoeffVar<'a> {
+    Named {
+        #[serde(borrow)]
+        name: Cow<'a, str>,
+        #[serde(deserialize_with = "number::factor")]
+        factor: f64,
+    },
+    Indexed {
+        index: usize,
+        #[serde(deserialize_with = "number::factor")]
+        factor: f64,
+    },
+    Element {
+        #[serde(borrow)]
+        array: Cow<'a, str>,
+        indices: Vec<usize>,
+        #[serde(deserialize_with = "number::factor")]
+        factor: f64,
+    },
+}
+impl CoeffVar<'_> {
+    /// Finds the position of the variable in `names`.
+    fn position<'a>(
+        &self,
+        mut names: impl Iterator<Item = &'a str>,
+    ) -> Result<usize, MessageError> {
+        match self {
+            CoeffVar::Named { name, .. } => names
+                .position(|n| n == name)
+                .ok_or_else(|| MessageError::validation(format!("unknown variable '{name}'"))),
+            CoeffVar::Element { array, indices, .. } => {
+                let name = element_name(array, indices);
+                names
+                    .position(|n| n == name)
+                    .ok_or_else(|| MessageError::validation(format!("unknown variable '{name}'")))
+            }
+            CoeffVar::Indexed { index, .. } => {
+                let count = names.count();
+                (*index < count).then_some(*index).ok_or_else(|| {
+                    MessageError::validation(format!(
+                        "variable index {index} is out of range for {count} variables"
+                    ))
+                })
+            }
+        }
+    }
+    fn factor(&self) -> f64 {
+        match self {
+            CoeffVar::Named { factor, .. }
+            | CoeffVar::Indexed { factor, .. }
+            | CoeffVar::Element { factor, .. } => *factor,
+        }
+    }
+}
+#[derive(Deserialize, Clone, Copy)]
+pub enum Op {
+    #[serde(rename = "<=")]
+    Leq,
+    #[serde(rename = ">=")]
+    Geq,
+    #[serde(rename = "==")]
+    Eq,
+}
+/// Constraint `terms op rhs`.
+#[derive(Deserialize)]
+pub struct Row<'a> {
+    #[serde(borrow)]
+    pub terms: Vec<CoeffVar<'a>>,
+    pub op: Op,
+    #[serde(default, deserialize_with = "number::rhs")]
+    pub rhs: f64,
+    #[serde(borrow, default, deserialize_with = "names::optional")]
+    pub name: Option<Cow<'a, str>>,
+}
+#[derive(Deserialize)]
+pub struct RangedConstraint<'a> {
+    #[serde(borrow)]
+    pub terms: Vec<CoeffVar<'a>>,
+    #[serde(deserialize_with = "number::lower_bound")]
+    pub lower: f64,
+    #[serde(deserialize_with = "number::upper_bound")]
+    pub upper: f64,
+    #[serde(borrow, default, deserialize_with = "names::optional")]
+    pub name: Option<Cow<'a, str>>,
+}
+/// Rows `terms[i] senses[i] rhs[i]` with the senses `<=`, `>=` and `==`.
+#[derive(Deserialize)]
+pub struct SensedRows<'a> {
+    #[serde(borrow)]
+    pub terms: Vec<Vec<CoeffVar<'a>>>,
+    pub senses: Vec<String>,
+    #[serde(deserialize_with = "number::sensed_rhs")]
+    pub rhs: Vec<f64>,
+}
+/// Row `terms op rhs` for every value of an index from `range`, whose term
+/// names contain placeholders like `{t}`, `{t+1}` or `{t-1}` for index `t`.
+#[derive(Deserialize)]
+pub struct ConstraintTemplate {
+    pub name: String,
+    pub index: String,
+    pub range: (i64, i64), // start inclusive, end exclusive
+    pub terms: Vec<TemplateTerm>,
+    pub op: Op,
+    #[serde(default, deserialize_with = "number::rhs")]
+    pub rhs: f64,
+}
+#[derive(Deserialize)]
+pub struct TemplateTerm {
     pub name: String,
+    #[serde(deserialize_with = "number::factor")]
     pub factor: f64,
 }
+/// Constraint matrix in compressed sparse row form over variable indices,
+/// with the rows `terms op rhs`.
 #[derive(Deserialize)]
-pub struct MessageProblem {
-    pub direction: Direction,
-    pub variables: Vec<VariableDef>,
-    pub objective: Vec<CoeffVar>,
+pub struct SparseMatrix {
+    pub row_starts: Vec<usize>, // one per row plus the end of the last row
+    pub col_indices: Vec<usize>,
+    pub values: Vec<f64>,
+    pub ops: Vec<Op>,
+    pub rhs: Vec<f64>,
+}
+/// Special ordered set over variables, ordered by their weights.
+#[derive(Deserialize)]
+pub struct Sos<'a> {
+    #[serde(rename = "type")]
+    pub kind: u8, // 1: at most one nonzero, 2: at most two adjacent nonzeros
+    #[serde(borrow, deserialize_with = "names::list")]
+    pub variables: Vec<Cow<'a, str>>,
+    #[serde(default)]
+    pub weights: Option<Vec<f64>>, // one per variable, defaults to the listed order
+}
+/// Term `factor * a * b` of the objective.
+#[derive(Deserialize)]
+pub struct QuadraticTerm<'a> {
+    #[serde(borrow)]
+    pub name_a: Cow<'a, str>,
+    #[serde(borrow)]
+    pub name_b: Cow<'a, str>,
+    pub factor: f64,
+}
+/// Piecewise-linear objective term of a variable, which is zero at the first
+/// breakpoint.
+#[derive(Deserialize)]
+pub struct Piecewise<'a> {
+    #[serde(borrow)]
+    pub variable: Cow<'a, str>,
+    pub breakpoints: Vec<f64>, // increasing, only the last one may be infinite
+    pub slopes: Vec<f64>,      // one per segment between two breakpoints
+}
+impl Piecewise<'_> {
+    /// Whether the objective fills the segments in their order by itself,
+    /// so that no binaries are needed.
+    fn in_order(&self, direction: Direction) -> bool {
+        self.slopes.windows(2).all(|w| match direction {
+            Direction::Min => w[0] <= w[1],
+            Direction::Max => w[0] >= w[1],
+        })
+    }
+}
+/// Affine expression `terms + offset`.
+#[derive(Deserialize)]
+pub struct Affine<'a> {
+    #[serde(borrow)]
+    pub terms: Vec<CoeffVar<'a>>,
+    #[serde(default)]
+    pub offset: f64,
+}
+/// Helper variable that is at least (`max_of`) or at most (`min_of`) each of
+/// the expressions. It can be used by name like any other variable, but it
+/// is not reported.
+#[derive(Deserialize)]
+pub struct Extremum<'a> {
+    #[serde(borrow)]
+    pub name: Cow<'a, str>,
+    #[serde(borrow)]
+    pub expressions: Vec<Affine<'a>>,
+}
+/// Objective term `factor * |terms + offset|`.
+#[derive(Deserialize)]
+pub struct AbsTerm<'a> {
+    #[serde(borrow)]
+    pub terms: Vec<CoeffVar<'a>>,
+    #[serde(default)]
+    pub offset: f64,
+    pub factor: f64, // must not reward large values, so >= 0 when minimising
+}
+/// Row `terms op rhs` that only has to hold while a binary variable has the
+/// active value.
+#[derive(Deserialize)]
+pub struct Indicator<'a> {
+    #[serde(borrow)]
+    pub variable: Cow<'a, str>,
+    #[serde(default)]
+    pub active_value: Option<u8>, // 0 or 1, defaults to 1
+    #[serde(borrow)]
+    pub terms: Vec<CoeffVar<'a>>,
+    pub op: Op,
+    #[serde(default)]
+    pub rhs: f64,
+    // derived from the variable bounds if absent
+    #[serde(default)]
+    pub big_m: Option<f64>,
+}
+#[derive(Deserialize, Default)]
+#[allow(clippy::struct_excessive_bools)] // independent flags of the protocol
+pub struct MessageProblem<'a> {
+    // opaque value that is copied into the response
+    #[serde(default)]
+    pub id: Option<serde_json::Value>,
+    // reject keys that are not part of the protocol instead of ignoring them
+    #[serde(default)]
+    pub strict: bool,
+    // only optional without an objective, then it is min
+    #[serde(default)]
+    pub direction: Option<Direction>,
+    #[serde(borrow)]
+    pub variables: Vec<VariableDef<'a>>,
+    #[serde(borrow, default)]
+    pub variable_arrays: Vec<VariableArray<'a>>, // after `variables`, in their order
+    #[serde(default)]
+    pub variable_defaults: VariableDefaults,
+    // one initial value per variable, overrides `initial`
+    #[serde(default)]
+    pub warm_start: Option<Vec<f64>>,
+    // initial values for some variables, overrides `warm_start`
+    #[serde(borrow, default)]
+    pub partial_start: Vec<StartValue<'a>>,
+    #[serde(borrow, deserialize_with = "objective_terms")]
+    pub objective: Vec<CoeffVar<'a>>, // or a map from names to factors
+    #[serde(default, deserialize_with = "number::objective_offset")]
     pub objective_offset: f64,
+    // solved in order after `objective`, keeping the previous ones optimal
+    #[serde(borrow, default)]
+    pub objectives: Vec<ObjectiveStage<'a>>,
+    #[serde(default)]
+    pub objective_tolerance: Option<f64>, // for keeping `objective` optimal
+    // optimise `objective` plus the weighted `objectives` instead
+    #[serde(default)]
+    pub blend: bool,
+    #[serde(borrow, default)]
+    pub objective_quadratic: Vec<QuadraticTerm<'a>>, // convex, without integer variables
+    #[serde(borrow, default)]
+    pub piecewise: Vec<Piecewise<'a>>, // further objective terms
+    #[serde(borrow, default)]
+    pub abs_terms: Vec<AbsTerm<'a>>,
+    // only exact if the objective pushes them towards the expressions
+    #[serde(borrow, default)]
+    pub max_of: Vec<Extremum<'a>>,
+    #[serde(borrow, default)]
+    pub min_of: Vec<Extremum<'a>>,
+
+    #[serde(borrow, default)]
+    pub constraints: Vec<Vec<CoeffVar<'a>>>, // <= 0 constraints
+    #[serde(default, deserialize_with = "number::constraint_offsets")]
+    pub constraint_offsets: Vec<f64>, // all zero if omitted
+    #[serde(borrow, default, deserialize_with = "names::list")]
+    pub constraint_names: Vec<Cow<'a, str>>, // optional, one per constraint
+
+    #[serde(borrow, default)]
+    pub equalities: Vec<Vec<CoeffVar<'a>>>, // == 0 constraints
+    #[serde(default, deserialize_with = "number::equalities_offsets")]
+    pub equalities_offsets: Vec<f64>, // all zero if omitted
+    #[serde(borrow, default, deserialize_with = "names::list")]
+    pub equality_names: Vec<Cow<'a, str>>,
+
+    #[serde(borrow, default)]
+    pub geq_constraints: Vec<Vec<CoeffVar<'a>>>, // >= 0 constraints
+    #[serde(default, deserialize_with = "number::geq_offsets")]
+    pub geq_offsets: Vec<f64>, // all zero if omitted
+    #[serde(borrow, default, deserialize_with = "names::list")]
+    pub geq_names: Vec<Cow<'a, str>>,
 
-    pub constraints: Vec<Vec<CoeffVar>>, // <= 0 constraints
-    pub constraint_offsets: Vec<f64>,
+    #[serde(borrow, default)]
+    pub rows: Vec<Row<'a>>, // alternative to the offset arrays
+    #[serde(borrow, default)]
+    pub sensed_rows: Option<SensedRows<'a>>, // appended to `rows`
+    // rows for every value of an index, appended to `rows`
+    #[serde(default)]
+    pub constraint_templates: Vec<ConstraintTemplate>,
+    // constraints like "2*x + y <= 4", appended to `rows` after the templates
+    #[serde(default)]
+    pub expressions: Vec<String>,
+    // added to `objective`
+    #[serde(default)]
+    pub objective_expr: Option<String>,
+    #[serde(default)]
+    pub matrix: Option<SparseMatrix>, // bulk alternative to `rows`
+    // rows `dense_rows[i] . variables dense_ops[i] dense_rhs[i]`, with one
+    // coefficient per variable in order and no names to resolve
+    #[serde(default)]
+    pub dense_rows: Vec<Vec<f64>>,
+    #[serde(default)]
+    pub dense_ops: Vec<Op>,
+    #[serde(default)]
+    pub dense_rhs: Vec<f64>,
 
-    pub equalities: Vec<Vec<CoeffVar>>, // == 0 constraints
-    pub equalities_offsets: Vec<f64>,
+    #[serde(borrow, default)]
+    pub ranged_constraints: Vec<RangedConstraint<'a>>, // lower <= expr <= upper
+    // the members need finite bounds
+    #[serde(borrow, default)]
+    pub sos: Vec<Sos<'a>>,
+    #[serde(borrow, default)]
+    pub indicators: Vec<Indicator<'a>>,
+    // applies to the objective and to the terms of every row
+    #[serde(default)]
+    pub duplicate_policy: DuplicatePolicy,
+    // absolute, terms with smaller factors are left out of expressions
+    #[serde(default)]
+    pub drop_coefficients_below: f64,
+    // how referenced names are compared to the names of the variables
+    #[serde(default)]
+    pub name_matching: NameMatching,
+    // the same as `options.verbose`, which takes precedence
+    #[serde(default)]
+    pub verbose: Option<bool>,
+    #[serde(default)]
+    pub output: Output,
+    // report only values above zero_tolerance in `nonzeros`
+    #[serde(default)]
+    pub sparse_output: bool,
+    #[serde(default)]
+    pub zero_tolerance: Option<f64>, // absolute, defaults to 1e-9
+    // how infinite or NaN values are reported
+    #[serde(default)]
+    pub non_finite: NonFinite,
+    // the same as `options.integrality_tolerance`, which takes precedence
+    #[serde(default)]
+    pub integrality_tolerance: Option<f64>,
+    // merged over the ones of `set_default_options`, and over these the
+    // settings like `verbose` that are also outside of `options`
+    #[serde(default = "defaults::options", deserialize_with = "defaults::under")]
+    pub options: Options,
+
+    // search for a small set of conflicting constraints if infeasible
+    #[serde(default)]
+    pub explain_infeasibility: bool,
+    #[serde(default)]
+    pub explain_infeasibility_max_solves: Option<usize>, // defaults to 1000
+    // ranging of objective coefficients and right-hand sides for pure LPs
+    #[serde(default)]
+    pub sensitivity: bool,
+    // solve with integer and binary variables treated as continuous ones
+    #[serde(default)]
+    pub relax_integrality: bool,
+    // collect up to this many solutions, requires bounded integer variables
+    #[serde(default)]
+    pub solution_limit: Option<usize>,
+    // contribution of each objective term to the objective value
+    #[serde(default)]
+    pub objective_breakdown: bool,
+    // the model as handed to HiGHS, written in LP or MPS format
+    #[serde(default)]
+    pub dump_model: Option<DumpModel>,
+    // path to write the solution to in the format of HiGHS solution files
+    #[serde(default)]
+    pub write_solution_file: Option<String>,
+}
+
+impl MessageProblem<'_> {
+    fn direction(&self) -> Direction {
+        self.direction.unwrap_or(Direction::Min)
+    }
+    /// Whether anything is optimised. Meant for after the objective string
+    /// was parsed.
+    fn has_objective(&self) -> bool {
+        !self.objective.is_empty()
+            || !self.objectives.is_empty()
+            || !self.objective_quadratic.is_empty()
+            || !self.piecewise.is_empty()
+            || !self.abs_terms.is_empty()
+            || self.variables.iter().any(|v| v.cost.is_some())
+    }
+}
 
-    pub verbose: bool,
+/// Accepts the objective as a list of terms or as a map from variable names
+/// to factors. Terms of a map are ordered by name.
+fn objective_terms<'de: 'a, 'a, D: Deserializer<'de>>(d: D) -> Result<Vec<CoeffVar<'a>>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Objective<'a> {
+        Terms(#[serde(borrow)] Vec<CoeffVar<'a>>),
+        Factors(BTreeMap<String, number::Number>),
+    }
+    Ok(match Objective::deserialize(d)? {
+        Objective::Terms(terms) => terms,
+        Objective::Factors(factors) => factors
+            .into_iter()
+            .map(|(name, factor)| {
+                let factor = factor.finite(&format!("factor of '{name}'"))?;
+                Ok(CoeffVar::Named {
+                    name: name.into(),
+                    factor,
+                })
+            })
+            .collect::<Result<_, _>>()?,
+    })
 }
 
-#[derive(Serialize, PartialEq, Eq)]
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum Status {
     Optimal,
+    Feasible, // stopped early with an incumbent
     Unbounded,
     Infeasible,
+    // stopped early, `values` carries the incumbent if one was found
+    TimeLimit,
+    NodeLimit,
+    Interrupted,
+    // one of `objectives` cannot be solved once the previous ones are optimal
+    StageUnbounded,
+    StageInfeasible,
+    // stopped early without an incumbent, which is `feasible` with one
+    IterationLimit,
+    SolutionLimit,
+}
+impl Status {
+    fn has_values(self) -> bool {
+        !matches!(
+            self,
+            Status::Unbounded
+                | Status::Infeasible
+                | Status::StageUnbounded
+                | Status::StageInfeasible
+        )
+    }
+}
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BasisStatus {
+    Lower,
+    Basic,
+    Upper,
+    Free, // nonbasic at zero
+    Nonbasic,
+}
+#[cfg(feature = "highs")]
+impl TryFrom<highs_sys::HighsInt> for BasisStatus {
+    type Error = MessageError;
+    fn try_from(value: highs_sys::HighsInt) -> Result<Self, Self::Error> {
+        match value {
+            highs_sys::kHighsBasisStatusLower => Ok(BasisStatus::Lower),
+            highs_sys::kHighsBasisStatusBasic => Ok(BasisStatus::Basic),
+            highs_sys::kHighsBasisStatusUpper => Ok(BasisStatus::Upper),
+            highs_sys::kHighsBasisStatusZero => Ok(BasisStatus::Free),
+            highs_sys::kHighsBasisStatusNonbasic => Ok(BasisStatus::Nonbasic),
+            other => Err(MessageError::solver(format!(
+                "unknown basis status {other}"
+            ))),
+        }
+    }
+}
+#[derive(Serialize)]
+pub struct Basis {
+    pub columns: Vec<BasisStatus>, // one per variable
+    pub rows: Vec<BasisStatus>,    // one per row
+}
+/// Interval `[down, up]` over which a value can vary.
+pub type Range = [f64; 2];
+/// Value of a variable, emitted as a JSON integer for integer variables.
+#[derive(Serialize, Clone, Copy)]
+#[serde(untagged)]
+pub enum Value {
+    Integer(i64),
+    Continuous(f64),
+    NonFinite(&'static str), // see [`NonFinite::String`]
+}
+#[derive(Serialize)]
+pub struct SparseValue {
+    pub index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>, // only for named output
+    pub value: Value,
 }
+/// Solution of a [`MessageProblem`]. Everything reported per row lists the
+/// constraints, then the equalities, then the geq constraints, then the
+/// rows, then the matrix rows, then the dense rows, then the ranged
+/// constraints.
 #[derive(Serialize)]
 pub struct MessageSolution {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<serde_json::Value>, // copied from the problem
     pub status: Status,
-    pub values: Vec<f64>,
+    pub values: Vec<Value>,
+    pub objective: Option<f64>, // includes objective_offset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub named_values: Option<BTreeMap<String, Value>>,
+    // whether all integer variables are integral, only for MIPs with values
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub integral: Option<bool>,
+    // only if the integrality of the variables was relaxed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relaxed: Option<bool>,
+    // only if crossover was off, so that the duals are not those of a basis
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interior: Option<bool>,
+    // only if there is no objective, so that `optimal` means feasible
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feasibility: Option<bool>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub nonintegral_variables: Vec<String>,
+    // replaces `values` and `named_values` for sparse output
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonzeros: Option<Vec<SparseValue>>,
+    // one per row, only for pure LPs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duals: Option<Vec<f64>>,
+    // keyed by row name, only rows with a name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub named_duals: Option<BTreeMap<String, f64>>,
+    // one per variable, only for pure LPs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reduced_costs: Option<Vec<f64>>,
+    // value of each row expression
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub activities: Option<Vec<f64>>,
+    // distance of each activity to its nearest bound, negative if violated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slacks: Option<Vec<f64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub named_slacks: Option<BTreeMap<String, f64>>,
+    // only for pure LPs solved to optimality
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub basis: Option<Basis>,
+    // [low, high] per variable, only if requested for pure LPs at optimality
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost_ranges: Option<Vec<Range>>,
+    // [low, high] per row
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rhs_ranges: Option<Vec<Range>>,
+    // only if requested and a solution was found
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub objective_breakdown: Option<ObjectiveBreakdown>,
+    // optimum of `objective` and then of each of `objectives` that was
+    // solved, or their values in the solution if they are blended
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stage_objectives: Option<Vec<f64>>,
+    // only if requested, starts with the incumbent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub solutions: Option<Vec<pool::PoolSolution>>,
+    // only if requested and infeasible
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub infeasibility: Option<iis::Iis>,
+    // unbounded direction, one entry per variable
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ray: Option<Vec<f64>>,
+    // only for MIPs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub best_bound: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mip_gap: Option<f64>, // relative
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<Stats>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log: Option<String>, // only if captured
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_dump: Option<dump::ModelDump>, // only if requested
 }
-impl MessageSolution {
-    fn unbounded() -> Self {
-        MessageSolution {
-            status: Status::Unbounded,
-            values: vec![],
+#[derive(Serialize)]
+pub struct ObjectiveBreakdown {
+    pub terms: Vec<f64>, // factor * value, aligned with the input objective
+    // factor * a * b, aligned with the input quadratic terms
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub quadratic: Vec<f64>,
+    // aligned with the input piecewise terms
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub piecewise: Vec<f64>,
+    // aligned with the input abs terms
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub abs: Vec<f64>,
+    // cost * value for each variable with a cost, in their order
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub costs: Vec<f64>,
+    // signed weight * value, aligned with the blended objectives
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub blended: Vec<f64>,
+    pub offset: f64, // all terms and offset sum up to the objective value
+}
+#[derive(Serialize)]
+pub struct Stats {
+    pub parse_ms: f64, // decoding the JSON input
+    pub build_ms: f64, // constructing the model
+    pub solve_ms: f64, // wall-clock time spent in the solver
+    pub simplex_iterations: Option<i32>,
+    pub nodes: Option<i64>, // branch-and-bound nodes, only for MIPs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presolve: Option<Presolve>, // only if a solution was found
+    pub dropped_coefficients: usize, // below drop_coefficients_below
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threads: Option<u32>, // only if set, after limiting it to the cores
+    pub seed: Option<i32>,  // the random seed of HiGHS, set or not
+    pub primal_feasibility_tolerance: Option<f64>, // used by HiGHS, set or not
+    pub dual_feasibility_tolerance: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory: Option<Memory>, // only with memory_limit_mb
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coefficients: Option<Coefficients>, // only if a row has a nonzero
+    // the HiGHS options that `emphasis` set, with their values
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emphasis: Option<BTreeMap<&'static str, String>>,
+    // false if a variable has a branch_priority that HiGHS cannot take
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch_priorities_native: Option<bool>,
+}
+#[derive(Serialize)]
+pub struct Presolve {
+    pub rows_removed: i32,
+    pub columns_removed: i32,
+    pub nonzeros_removed: i32,
+}
+impl From<[i32; 3]> for Presolve {
+    fn from([rows_removed, columns_removed, nonzeros_removed]: [i32; 3]) -> Self {
+        Presolve {
+            rows_removed,
+            columns_removed,
+            nonzeros_removed,
         }
     }
-    fn infeasible() -> Self {
+}
+/// Smallest and largest absolute nonzero coefficient of the input rows,
+/// whose ratio shows whether the matrix is badly scaled.
+#[derive(Serialize)]
+pub struct Coefficients {
+    pub min_abs: f64,
+    pub max_abs: f64,
+}
+impl Coefficients {
+    fn of(rows: &[BuiltRow]) -> Option<Self> {
+        let factors = rows.iter().flat_map(|r| (&r.expr).linear_coefficients());
+        let magnitudes = factors.map(|(_, f)| f.abs()).filter(|&f| f > 0.0);
+        magnitudes.fold(None, |range, f| {
+            Some(match range {
+                None => Coefficients {
+                    min_abs: f,
+                    max_abs: f,
+                },
+                Some(Coefficients { min_abs, max_abs }) => Coefficients {
+                    min_abs: min_abs.min(f),
+                    max_abs: max_abs.max(f),
+                },
+            })
+        })
+    }
+}
+/// Memory estimate that was checked against `memory_limit_mb`.
+#[derive(Serialize)]
+pub struct Memory {
+    pub limit_mb: u64,
+    pub columns: u64,
+    pub rows: u64,
+    pub nonzeros: u64,
+    pub estimated_bytes: u64,
+    pub formula: &'static str, // how the estimate is computed from the counts
+    pub peak_bytes: Option<u64>, // of the whole process after solving, only on Linux
+}
+impl MessageSolution {
+    fn new(status: Status, values: Vec<f64>) -> Self {
         MessageSolution {
-            status: Status::Infeasible,
-            values: vec![],
+            id: None,
+            status,
+            values: values.into_iter().map(Value::Continuous).collect(),
+            objective: None,
+            named_values: None,
+            integral: None,
+            relaxed: None,
+            interior: None,
+            feasibility: None,
+            nonintegral_variables: vec![],
+            nonzeros: None,
+            duals: None,
+            named_duals: None,
+            reduced_costs: None,
+            activities: None,
+            slacks: None,
+            named_slacks: None,
+            basis: None,
+            cost_ranges: None,
+            rhs_ranges: None,
+            objective_breakdown: None,
+            stage_objectives: None,
+            solutions: None,
+            infeasibility: None,
+            ray: None,
+            best_bound: None,
+            mip_gap: None,
+            stats: None,
+            warnings: vec![],
+            log: None,
+            model_dump: None,
         }
     }
-    fn optimal(values: Vec<f64>) -> Self {
+    fn incumbent(status: Status, values: Vec<f64>, objective: f64) -> Self {
         MessageSolution {
-            status: Status::Optimal,
-            values,
+            objective: Some(objective),
+            ..Self::new(status, values)
         }
     }
 }
 
 /// Receives a byte buffer of a JSON-encoded MILP problem instance, computes a
-/// solution, encodes it as JSON, and returns it as a C string.
+/// solution, encodes it as JSON, and returns it as a C string. The buffer may
+/// be compressed with gzip or zstd, see [`set_decompression_limit`].
 ///
-/// A null pointer is returned if there is an error. The error message is
-/// written to stderr.
+/// If the problem cannot be solved, a [`MessageError`] is encoded instead. A
+/// null pointer is only returned if the buffer itself is null.
 ///
 /// # Safety
 /// This function must be called with a valid length and byte buffer. See
@@ -110,97 +1386,2897 @@ pub unsafe extern "C" fn solve(buffer: *const u8, len: usize) -> *const c_char {
         return std::ptr::null();
     }
     let input_bytes = unsafe { slice::from_raw_parts(buffer, len) };
-    let input: MessageProblem = match serde_json::from_slice(input_bytes) {
-        Ok(s) => s,
-        Err(e) => {
-            eprintln!("Error parsing JSON: {e}");
-            return std::ptr::null();
-        }
-    };
+    let (res, envelope) = answer(input_bytes, false);
+    let json = response(res, envelope.id, envelope.options.pretty_output);
+    // JSON escapes all control characters, so there cannot be any null bytes
+    CString::new(json).map_or(std::ptr::null(), |s| s.into_raw().cast_const())
+}
 
-    let mut problem = ProblemVariables::new();
-    let vars: Vec<_> = problem.add_all(input.variables.iter().map_into());
-    let mapping = input
-        .variables
-        .iter()
-        .map(|v| v.name.as_ref())
-        .zip(vars.iter().copied())
-        .collect::<Vec<_>>();
-    let objective = to_expr(&mapping, input.objective_offset, input.objective);
-    let mut problem = match input.direction {
-        Direction::Min => problem.minimise(objective),
-        Direction::Max => problem.maximise(objective),
-    }
-    .using(highs);
-    problem.set_verbose(input.verbose);
-    let solution =
-        problem
-            .with_all(
-                input.constraints.into_iter().enumerate().map(|(i, c)| {
-                    constraint!(to_expr(&mapping, input.constraint_offsets[i], c) <= 0)
-                }),
-            )
-            .with_all(
-                input.equalities.into_iter().enumerate().map(|(i, c)| {
-                    constraint!(to_expr(&mapping, input.equalities_offsets[i], c) == 0)
-                }),
-            )
-            .solve();
+/// Like [`solve`], but returns the response as a byte buffer whose length is
+/// written to `out_len`, so that it needs no `strlen` and may contain null
+/// bytes, as it does when it is compressed with the option
+/// `compress_output`. Free it with [`free_buffer`]. A null pointer is only
+/// returned if a pointer argument is null.
+///
+/// # Safety
+/// This function must be called with a valid length and byte buffer. See
+/// [`slice::from_raw_parts`] for details. `out_len` must be valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn solve2(buffer: *const u8, len: usize, out_len: *mut usize) -> *mut u8 {
+    if buffer.is_null() || out_len.is_null() {
+        return std::ptr::null_mut();
+    }
+    let input_bytes = unsafe { slice::from_raw_parts(buffer, len) };
+    unsafe { into_buffer(answer_bytes(input_bytes), out_len) }
+}
 
-    let res = match solution {
-        Ok(sol) => MessageSolution::optimal(vars.into_iter().map(|v| sol.value(v)).collect()),
-        Err(err) => match err {
-            good_lp::ResolutionError::Unbounded => MessageSolution::unbounded(),
-            good_lp::ResolutionError::Infeasible => MessageSolution::infeasible(),
-            good_lp::ResolutionError::Other(e) => {
-                eprintln!("{e}");
-                return std::ptr::null();
-            }
-            good_lp::ResolutionError::Str(e) => {
-                eprintln!("{e}");
-                return std::ptr::null();
-            }
-        },
-    };
+/// Returned by [`solve_into`] if the input buffer is null, or if the output
+/// buffer is null but has a capacity.
+pub const SOLVE_INTO_NULL_POINTER: isize = isize::MIN;
 
-    let Ok(json) = serde_json::to_string(&res) else {
-        eprintln!("could not serialise solution");
-        return std::ptr::null();
-    };
-    let Ok(c_string) = CString::new(json) else {
-        eprintln!("Error: CString conversion failed (internal null bytes detected).");
+/// Like [`solve2`], but writes the response into `out_buffer`, which holds
+/// `out_capacity` bytes, so that nothing has to be freed. Returns the
+/// number of bytes written. If the response does not fit, nothing is
+/// written and the negated size of the response is returned, so that the
+/// call can be repeated with a large enough buffer. The response is not
+/// terminated by a null byte.
+///
+/// Invalid arguments give [`SOLVE_INTO_NULL_POINTER`], which is never the
+/// negated size of a response. A null `out_buffer` with a capacity of 0
+/// only asks for the size.
+///
+/// # Safety
+/// See [`solve`]. `out_buffer` must be valid for writes of `out_capacity`
+/// bytes unless it is null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn solve_into(
+    buffer: *const u8,
+    len: usize,
+    out_buffer: *mut u8,
+    out_capacity: usize,
+) -> isize {
+    if buffer.is_null() || (out_buffer.is_null() && out_capacity > 0) {
+        return SOLVE_INTO_NULL_POINTER;
+    }
+    let bytes = answer_bytes(unsafe { slice::from_raw_parts(buffer, len) });
+    // allocations never exceed isize::MAX bytes
+    let size = isize::try_from(bytes.len()).unwrap_or(isize::MAX);
+    if bytes.len() > out_capacity {
+        return -size;
+    }
+    unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), out_buffer, bytes.len()) };
+    size
+}
+
+/// Like [`solve`], but for many problems in newline-delimited JSON, one per
+/// line, which are solved in order. The responses are returned in the same
+/// order, one per line, with an error for each line that fails. Blank lines
+/// are skipped, and `pretty_output` is ignored so that every response stays
+/// on its line. The buffer may be compressed as a whole.
+///
+/// # Safety
+/// See [`solve`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn solve_batch(buffer: *const u8, len: usize) -> *const c_char {
+    if buffer.is_null() {
         return std::ptr::null();
+    }
+    let input_bytes = unsafe { slice::from_raw_parts(buffer, len) };
+    let json = match decompressed(input_bytes) {
+        Ok(bytes) => {
+            let mut json = String::new();
+            for line in bytes.split(|&b| b == b'\n') {
+                if line.iter().all(u8::is_ascii_whitespace) {
+                    continue;
+                }
+                let (res, envelope) = answer(line, false);
+                json.push_str(&response(res, envelope.id, false));
+                json.push('\n');
+            }
+            json
+        }
+        Err(e) => response(Err(e), None, false) + "\n",
     };
-    c_string.into_raw()
+    // JSON escapes all control characters, so there cannot be any null bytes
+    CString::new(json).map_or(std::ptr::null(), |s| s.into_raw().cast_const())
+}
+
+/// Handle of a problem that is uploaded in chunks, see [`solve_begin`]. It
+/// is never dereferenced, so stale handles are detected rather than used.
+#[repr(C)]
+pub struct Upload {
+    _private: [u8; 0],
+}
+
+/// Starts a problem that is passed to [`solve_append`] in chunks, for hosts
+/// that cap the size of a single buffer, and solved by [`solve_finish`].
+/// See [`set_upload_limit`] for the total size.
+#[unsafe(no_mangle)]
+pub extern "C" fn solve_begin() -> *mut Upload {
+    std::ptr::without_provenance_mut(upload::begin())
 }
 
-/// Frees a string allocated by [`solve`].
+/// Adds the next chunk of the JSON-encoded problem to an upload of
+/// [`solve_begin`]. Returns whether the chunk was added, which it is not if
+/// the upload is unknown or finished, or if it grew beyond its limit, which
+/// [`solve_finish`] then reports.
 ///
 /// # Safety
-/// This may only be called for pointers returned from [`solve`], and it may
-/// only be done once per pointer.
+/// See [`solve`].
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn free(s: *mut c_char) {
-    if s.is_null() {
-        return;
+pub unsafe extern "C" fn solve_append(session: *mut Upload, buffer: *const u8, len: usize) -> bool {
+    if buffer.is_null() {
+        return false;
     }
-    unsafe {
-        let _ = CString::from_raw(s);
+    let chunk = unsafe { slice::from_raw_parts(buffer, len) };
+    upload::append(session.addr(), chunk)
+}
+
+/// Like [`solve`] for the chunks of an upload, which is closed. The
+/// response is an error if the upload is unknown or already finished.
+#[unsafe(no_mangle)]
+pub extern "C" fn solve_finish(session: *mut Upload) -> *const c_char {
+    let (res, envelope) = match upload::finish(session.addr()) {
+        Ok(bytes) => answer(&bytes, false),
+        Err(e) => (Err(e), Envelope::default()),
+    };
+    let json = response(res, envelope.id, envelope.options.pretty_output);
+    // JSON escapes all control characters, so there cannot be any null bytes
+    CString::new(json).map_or(std::ptr::null(), |s| s.into_raw().cast_const())
+}
+
+/// Closes an upload of [`solve_begin`] without solving it. Returns whether
+/// it was still open.
+#[unsafe(no_mangle)]
+pub extern "C" fn solve_abort(session: *mut Upload) -> bool {
+    upload::abort(session.addr())
+}
+
+/// Sets the number of bytes that an upload of [`solve_begin`] may reach in
+/// total, 1 GiB by default.
+#[unsafe(no_mangle)]
+pub extern "C" fn set_upload_limit(bytes: usize) {
+    upload::LIMIT.store(bytes, Ordering::Relaxed);
+}
+
+/// Solves a JSON-encoded problem for [`solve2`] and [`solve_into`], which
+/// return the response as bytes that may contain null bytes, so that it can
+/// be compressed.
+fn answer_bytes(input_bytes: &[u8]) -> Vec<u8> {
+    let (res, envelope) = answer(input_bytes, true);
+    let json = response(res, envelope.id, envelope.options.pretty_output).into_bytes();
+    if envelope.options.compress_output {
+        gzip(&json)
+    } else {
+        json
     }
 }
 
-fn to_expr(vars: &Vec<(&str, Variable)>, off: f64, coeff: Vec<CoeffVar>) -> Expression {
-    off.into_expression()
-        + coeff
-            .into_iter()
-            .map(|c| {
-                c.factor
-                    * vars
-                        .iter()
-                        .find_map(|(name, v)| (*name == c.name).then_some(*v))
-                        .expect("bad coeff")
-            })
-            .sum::<Expression>()
+/// Solves a JSON-encoded problem for [`solve`] and [`solve2`], of which only
+/// the latter can return `compressed` output.
+fn answer(
+    input_bytes: &[u8],
+    compressed: bool,
+) -> (Result<MessageSolution, MessageError>, Envelope) {
+    let start = Instant::now();
+    let (ready, envelope) = read_json(input_bytes, compressed, start);
+    (ready.and_then(solve_ready), envelope)
+}
+
+/// Reads and builds a JSON-encoded problem for [`answer`], so that the
+/// decompressed input is dropped before solving.
+fn read_json(
+    input_bytes: &[u8],
+    compressed: bool,
+    start: Instant,
+) -> (Result<Ready, MessageError>, Envelope) {
+    let input_bytes = match decompressed(input_bytes) {
+        Ok(bytes) => bytes,
+        Err(e) => return (Err(e), Envelope::default()),
+    };
+    #[cfg(feature = "json5")]
+    if RELAXED_JSON.load(Ordering::Relaxed) {
+        let parsed = std::str::from_utf8(&input_bytes)
+            .map_err(|e| MessageError::parse(format!("invalid UTF-8: {e}")))
+            .and_then(json5::parse);
+        return match parsed {
+            Ok(value) => {
+                let (input, envelope) = read_value(&value, compressed);
+                (input.and_then(|input| Ready::new(input, start)), envelope)
+            }
+            Err(e) => (Err(e), Envelope::default()),
+        };
+    }
+    let (input, envelope) = parse_json(&input_bytes, compressed);
+    (input.and_then(|input| Ready::new(input, start)), envelope)
+}
+
+/// Reads a problem from JSON, which its names borrow from.
+fn parse_json(
+    input_bytes: &[u8],
+    compressed: bool,
+) -> (Result<MessageProblem<'_>, MessageError>, Envelope) {
+    match serde_json::from_slice::<MessageProblem>(input_bytes) {
+        Ok(input) => {
+            let envelope = Envelope {
+                id: input.id.clone(),
+                options: input.options.clone(),
+            };
+            if input.strict
+                && let Some(key) = serde_json::from_slice(input_bytes)
+                    .ok()
+                    .and_then(|v| strict::unknown_key(&v))
+            {
+                let err = MessageError::parse(format!("unknown field '{key}'"));
+                return (Err(err), envelope);
+            }
+            if input.options.compress_output && !compressed {
+                return (Err(uncompressible()), envelope);
+            }
+            (Ok(input), envelope)
+        }
+        Err(e) => {
+            // still correlate and format the error if the envelope can be read
+            let envelope = serde_json::from_slice::<Envelope>(input_bytes).unwrap_or_default();
+            let err = MessageError::parse(format!("invalid JSON: {e}"));
+            (Err(err), envelope)
+        }
+    }
+}
+
+fn uncompressible() -> MessageError {
+    MessageError::validation("compress_output needs solve2, as the response is no C string then")
+}
+
+/// Whether [`solve`] and [`solve2`] read JSON5, off by default.
+#[cfg(feature = "json5")]
+static RELAXED_JSON: AtomicBool = AtomicBool::new(false);
+
+/// Makes [`solve`] and [`solve2`] accept JSON5, see [`json5::parse`], for
+/// hand-written problems with comments and trailing commas. Strict JSON is
+/// read again once this is turned off.
+#[cfg(feature = "json5")]
+#[unsafe(no_mangle)]
+pub extern "C" fn set_relaxed_json(enabled: bool) {
+    RELAXED_JSON.store(enabled, Ordering::Relaxed);
+}
+
+/// Upper bound on the size of decompressed input, 1 GiB by default.
+static DECOMPRESSION_LIMIT: AtomicUsize = AtomicUsize::new(1 << 30);
+
+/// Sets the number of bytes that compressed input may expand to, after which
+/// it is rejected rather than decompressed further.
+#[unsafe(no_mangle)]
+pub extern "C" fn set_decompression_limit(bytes: usize) {
+    DECOMPRESSION_LIMIT.store(bytes, Ordering::Relaxed);
+}
+
+/// Sets options for every later problem from a JSON object like `options`,
+/// which replaces the previous one. The options of a problem take
+/// precedence key by key. Returns whether the options are valid, and keeps
+/// the previous ones otherwise.
+///
+/// # Safety
+/// See [`solve`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn set_default_options(buffer: *const u8, len: usize) -> bool {
+    if buffer.is_null() {
+        return false;
+    }
+    let bytes = unsafe { slice::from_raw_parts(buffer, len) };
+    defaults::set(bytes).is_ok()
+}
+
+/// Returns the object of [`set_default_options`], or `{}`, as a JSON string
+/// that has to be freed with [`free_string`].
+#[unsafe(no_mangle)]
+pub extern "C" fn get_default_options() -> *const c_char {
+    // JSON escapes all control characters, so there cannot be any null bytes
+    CString::new(defaults::get()).map_or(std::ptr::null(), |s| s.into_raw().cast_const())
+}
+
+/// The backends that `options.solver` can name in this build, the first of
+/// which is the default.
+pub const SOLVERS: &[&str] = &[
+    #[cfg(feature = "highs")]
+    "highs",
+    #[cfg(feature = "microlp")]
+    "microlp",
+];
+
+/// Returns what this build supports as a JSON string that has to be freed
+/// with [`free_string`]: the `solvers` that `options.solver` accepts and the
+/// optional `features` that were compiled in.
+#[unsafe(no_mangle)]
+pub extern "C" fn get_capabilities() -> *const c_char {
+    let features = [
+        ("json5", cfg!(feature = "json5")),
+        ("microlp", cfg!(feature = "microlp")),
+        ("server", cfg!(feature = "server")),
+        ("yaml", cfg!(feature = "yaml")),
+    ];
+    let features: Vec<_> = features
+        .iter()
+        .filter(|(_, on)| *on)
+        .map(|(f, _)| f)
+        .collect();
+    let capabilities = serde_json::json!({"solvers": SOLVERS, "features": features});
+    // JSON escapes all control characters, so there cannot be any null bytes
+    CString::new(capabilities.to_string()).map_or(std::ptr::null(), |s| s.into_raw().cast_const())
+}
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Decompresses the input if it starts with the magic bytes of gzip or zstd.
+fn decompressed(bytes: &[u8]) -> Result<Cow<'_, [u8]>, MessageError> {
+    let (format, reader): (_, Box<dyn Read>) = if bytes.starts_with(&GZIP_MAGIC) {
+        ("gzip", Box::new(flate2::read::MultiGzDecoder::new(bytes)))
+    } else if bytes.starts_with(&ZSTD_MAGIC) {
+        zstd_decoder(bytes)?
+    } else {
+        return Ok(Cow::Borrowed(bytes));
+    };
+    let limit = DECOMPRESSION_LIMIT.load(Ordering::Relaxed);
+    // one byte more than the limit tells whether it is exceeded
+    let max = u64::try_from(limit).map_or(u64::MAX, |limit| limit.saturating_add(1));
+    let mut out = vec![];
+    if let Err(e) = reader.take(max).read_to_end(&mut out) {
+        return Err(MessageError::parse(format!("invalid {format} data: {e}")));
+    }
+    if out.len() > limit {
+        return Err(MessageError::parse(format!(
+            "decompressed input exceeds the limit of {limit} bytes"
+        )));
+    }
+    Ok(Cow::Owned(out))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn zstd_decoder(bytes: &[u8]) -> Result<(&'static str, Box<dyn Read + '_>), MessageError> {
+    let invalid = |e| MessageError::parse(format!("invalid zstd data: {e}"));
+    let decoder = zstd::stream::read::Decoder::with_buffer(bytes).map_err(invalid)?;
+    Ok(("zstd", Box::new(decoder)))
+}
+// the zstd crate builds its C library, which needs a C compiler for wasm32
+#[cfg(target_arch = "wasm32")]
+fn zstd_decoder(_: &[u8]) -> Result<(&'static str, Box<dyn Read + '_>), MessageError> {
+    Err(MessageError::parse(
+        "zstd-compressed input is not supported on wasm32",
+    ))
+}
+
+/// Compresses a response with gzip for `compress_output`.
+fn gzip(bytes: &[u8]) -> Vec<u8> {
+    let mut out = vec![];
+    flate2::read::GzEncoder::new(bytes, flate2::Compression::default())
+        .read_to_end(&mut out)
+        .expect("reading from a slice cannot fail");
+    out
+}
+
+/// Hands out `bytes` and writes their length to `out_len`.
+///
+/// # Safety
+/// `out_len` must be valid for writes.
+unsafe fn into_buffer(bytes: Vec<u8>, out_len: *mut usize) -> *mut u8 {
+    let bytes = bytes.into_boxed_slice();
+    unsafe { *out_len = bytes.len() };
+    Box::into_raw(bytes).cast()
+}
+
+/// Like [`solve`], but for a problem in free MPS format, see [`parse_mps`].
+/// The response is encoded as JSON with the default settings.
+///
+/// # Safety
+/// See [`solve`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn solve_mps(buffer: *const u8, len: usize) -> *const c_char {
+    unsafe { solve_text(buffer, len, parse_mps) }
+}
+
+/// Like [`solve_mps`], but for a problem in LP format, see [`parse_lp`].
+///
+/// # Safety
+/// See [`solve`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn solve_lp_format(buffer: *const u8, len: usize) -> *const c_char {
+    unsafe { solve_text(buffer, len, parse_lp) }
+}
+
+/// Solves a problem in a text format that `parse` reads.
+///
+/// # Safety
+/// See [`solve`].
+unsafe fn solve_text(buffer: *const u8, len: usize, parse: TextParser) -> *const c_char {
+    if buffer.is_null() {
+        return std::ptr::null();
+    }
+    let input_bytes = unsafe { slice::from_raw_parts(buffer, len) };
+    let json = answer_text(input_bytes, parse);
+    // JSON escapes all control characters, so there cannot be any null bytes
+    CString::new(json).map_or(std::ptr::null(), |s| s.into_raw().cast_const())
+}
+
+/// Reads of problems in text formats other than JSON.
+type TextParser = fn(&str) -> Result<MessageProblem<'static>, MessageError>;
+
+/// Solves a problem in a text format and returns the JSON response.
+fn answer_text(input_bytes: &[u8], parse: TextParser) -> String {
+    let start = Instant::now();
+    let res = read_text(input_bytes, parse).and_then(|input| solve_problem(input, start));
+    response(res, None, false)
+}
+fn read_text(
+    input_bytes: &[u8],
+    parse: TextParser,
+) -> Result<MessageProblem<'static>, MessageError> {
+    let bytes = decompressed(input_bytes)?;
+    let text = std::str::from_utf8(&bytes)
+        .map_err(|e| MessageError::parse(format!("invalid UTF-8: {e}")))?;
+    let mut input = parse(text)?;
+    // the formats have no options of their own
+    input.options = defaults::options();
+    Ok(input)
+}
+
+/// Like [`solve`], but reads the problem from the file at `path`, which
+/// saves copying it into a buffer first. Files ending in `.mps` or `.lp`
+/// are read like in [`solve_mps`] and [`solve_lp_format`], and files ending
+/// in `.json` as JSON, also with another `.gz`. Other files are told apart
+/// by how their text starts. Files that cannot be read are reported as
+/// errors.
+///
+/// # Safety
+/// `path` must be a null-terminated string, see [`CStr::from_ptr`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn solve_file(path: *const c_char) -> *const c_char {
+    unsafe { solve_path(path, false) }
+}
+
+/// Like [`solve_file`], but maps the file into memory on 64-bit Unix instead
+/// of reading it, and parses the problem from the mapping. Names without
+/// escapes are borrowed from it instead of copied. The mapping is removed
+/// once the model is built, before solving starts. Elsewhere, the file is
+/// read like in [`solve_file`].
+///
+/// # Safety
+/// See [`solve_file`]. The file must not be changed while it is parsed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn solve_file_mmap(path: *const c_char) -> *const c_char {
+    unsafe { solve_path(path, true) }
+}
+
+/// # Safety
+/// See [`solve_file`].
+unsafe fn solve_path(path: *const c_char, mapped: bool) -> *const c_char {
+    if path.is_null() {
+        return std::ptr::null();
+    }
+    let path = unsafe { CStr::from_ptr(path) };
+    let json = match path.to_str() {
+        Ok(path) => answer_file(Path::new(path), mapped),
+        Err(_) => response(
+            Err(MessageError::parse("path is not valid UTF-8")),
+            None,
+            false,
+        ),
+    };
+    // JSON escapes all control characters, so there cannot be any null bytes
+    CString::new(json).map_or(std::ptr::null(), |s| s.into_raw().cast_const())
+}
+
+/// Solves the problem in a file, which is read or `mapped`, and returns the
+/// JSON response.
+fn answer_file(path: &Path, mapped: bool) -> String {
+    let start = Instant::now();
+    // the contents, which the names of the problem borrow from, are dropped
+    // once the model is built
+    let read = if mapped {
+        mmap::Mapping::open(path).map(|mapping| read_file(path, &mapping, start))
+    } else {
+        std::fs::read(path).map(|bytes| read_file(path, &bytes, start))
+    };
+    let (ready, envelope) = read.unwrap_or_else(|e| {
+        let err = MessageError::parse(format!("could not read {}: {e}", path.display()));
+        (Err(err), Envelope::default())
+    });
+    response(
+        ready.and_then(solve_ready),
+        envelope.id,
+        envelope.options.pretty_output,
+    )
+}
+
+/// Reads and builds a problem from the contents of a file.
+fn read_file(path: &Path, bytes: &[u8], start: Instant) -> (Result<Ready, MessageError>, Envelope) {
+    let bytes = match decompressed(bytes) {
+        Ok(bytes) => bytes,
+        Err(e) => return (Err(e), Envelope::default()),
+    };
+    match text_format(path, &bytes) {
+        Some(parse) => {
+            let input = read_text(&bytes, parse);
+            let ready = input.and_then(|input| Ready::new(input, start));
+            (ready, Envelope::default())
+        }
+        None => read_json(&bytes, false, start),
+    }
+}
+
+/// Picks the reader of a file by its extension, or by the start of its
+/// decompressed text otherwise, and none for JSON.
+fn text_format(path: &Path, bytes: &[u8]) -> Option<TextParser> {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_ascii_lowercase());
+    let name = name.as_deref().unwrap_or_default();
+    let name = name.strip_suffix(".gz").unwrap_or(name);
+    if let Some((_, extension)) = name.rsplit_once('.') {
+        match extension {
+            "mps" => return Some(parse_mps),
+            "lp" => return Some(parse_lp),
+            "json" => return None,
+            _ => {}
+        }
+    }
+    let text = bytes.trim_ascii_start();
+    let starts = |prefix: &[u8]| {
+        text.get(..prefix.len())
+            .is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+    };
+    if starts(b"{") {
+        None
+    } else if starts(b"NAME") || starts(b"ROWS") || starts(b"*") {
+        Some(parse_mps)
+    } else if starts(b"\\") || starts(b"min") || starts(b"max") {
+        Some(parse_lp)
+    } else {
+        None
+    }
+}
+
+/// Like [`solve`], but for a problem in YAML, see [`yaml::parse`]. The
+/// response is JSON, or YAML if the option `yaml_output` is set, and has to
+/// be freed with [`free_string`].
+///
+/// # Safety
+/// See [`solve`].
+#[cfg(feature = "yaml")]
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn solve_yaml(buffer: *const u8, len: usize) -> *const c_char {
+    if buffer.is_null() {
+        return std::ptr::null();
+    }
+    let start = Instant::now();
+    let input_bytes = unsafe { slice::from_raw_parts(buffer, len) };
+    let parsed = decompressed(input_bytes).and_then(|bytes| {
+        let text = std::str::from_utf8(&bytes)
+            .map_err(|e| MessageError::parse(format!("invalid UTF-8: {e}")))?;
+        yaml::parse(text)
+    });
+    let (res, Envelope { id, options }) = match parsed {
+        Ok(value) => answer_value(&value, start, false),
+        Err(e) => (Err(e), Envelope::default()),
+    };
+    let text = if options.yaml_output {
+        yaml::emit(&Reply::new(res, id))
+    } else {
+        response(res, id, options.pretty_output)
+    };
+    // both escape all control characters, so there cannot be any null bytes
+    CString::new(text).map_or(std::ptr::null(), |s| s.into_raw().cast_const())
+}
+
+/// Like [`solve`], but for a `MessagePack`-encoded problem. The solution, or a
+/// [`MessageError`], is encoded as `MessagePack`, and its length is written to
+/// `out_len`. A null pointer is only returned if a pointer argument is null.
+///
+/// # Safety
+/// This function must be called with a valid length and byte buffer. See
+/// [`slice::from_raw_parts`] for details. `out_len` must be valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn solve_msgpack(
+    buffer: *const u8,
+    len: usize,
+    out_len: *mut usize,
+) -> *mut u8 {
+    unsafe { solve_binary(buffer, len, out_len, &MSGPACK) }
+}
+
+/// Like [`solve_msgpack`], but for CBOR.
+///
+/// # Safety
+/// See [`solve_msgpack`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn solve_cbor(buffer: *const u8, len: usize, out_len: *mut usize) -> *mut u8 {
+    unsafe { solve_binary(buffer, len, out_len, &CBOR) }
+}
+
+/// Like [`solve_msgpack`], but for a `Problem` message that is encoded with
+/// Protocol Buffers, see `proto/knorpelsolve.proto`. The response is a
+/// `Response` message.
+///
+/// # Safety
+/// See [`solve_msgpack`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn solve_proto(
+    buffer: *const u8,
+    len: usize,
+    out_len: *mut usize,
+) -> *mut u8 {
+    unsafe { solve_binary(buffer, len, out_len, &PROTOBUF) }
+}
+
+/// Encoding of problems through JSON values and of replies.
+struct Format {
+    name: &'static str,
+    decode: fn(&[u8]) -> Result<serde_json::Value, String>,
+    encode: fn(&Reply) -> Vec<u8>,
+}
+const JSON: Format = Format {
+    name: "JSON",
+    decode: |bytes| serde_json::from_slice(bytes).map_err(|e| e.to_string()),
+    encode: |reply| serde_json::to_vec(reply).unwrap_or_else(|_| INTERNAL_ERROR.into()),
+};
+const MSGPACK: Format = Format {
+    name: "MessagePack",
+    decode: msgpack::decode,
+    encode: |reply| msgpack::encode(reply),
+};
+const CBOR: Format = Format {
+    name: "CBOR",
+    decode: cbor::decode,
+    encode: |reply| cbor::encode(reply),
+};
+const PROTOBUF: Format = Format {
+    name: "protobuf",
+    decode: proto::decode,
+    encode: |reply| proto::encode(&reply.value()),
+};
+
+/// A solution or an error, tagged with the id of its problem. It is
+/// serialised with the fields in the order they are declared, like
+/// [`response`] writes them.
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Reply {
+    Solution(Box<MessageSolution>),
+    Error(MessageError),
+}
+impl Reply {
+    fn new(res: Result<MessageSolution, MessageError>, id: Option<serde_json::Value>) -> Self {
+        match res {
+            Ok(mut sol) => {
+                sol.id = id;
+                Reply::Solution(Box::new(sol))
+            }
+            Err(mut e) => {
+                e.id = id;
+                Reply::Error(e)
+            }
+        }
+    }
+
+    /// The reply as a JSON value, for the formats that are written from one.
+    fn value(&self) -> serde_json::Value {
+        serde_json::to_value(self)
+            .or_else(|_| serde_json::from_str(INTERNAL_ERROR))
+            .unwrap_or_default()
+    }
+}
+
+/// Solves a problem in `format` and returns the encoded response.
+///
+/// # Safety
+/// See [`solve_msgpack`].
+unsafe fn solve_binary(
+    buffer: *const u8,
+    len: usize,
+    out_len: *mut usize,
+    format: &Format,
+) -> *mut u8 {
+    if buffer.is_null() || out_len.is_null() {
+        return std::ptr::null_mut();
+    }
+    let input_bytes = unsafe { slice::from_raw_parts(buffer, len) };
+    let (reply, options) = answer_decoded(input_bytes, format, &serde_json::Map::new());
+    let bytes = (format.encode)(&reply);
+    unsafe { into_buffer(compressed(bytes, &options), out_len) }
+}
+
+/// Decodes and solves a problem in `format`, with `overrides` of its
+/// options, and returns the reply with the options that apply to it.
+fn answer_decoded(
+    input_bytes: &[u8],
+    format: &Format,
+    overrides: &serde_json::Map<String, serde_json::Value>,
+) -> (Reply, Options) {
+    let start = Instant::now();
+    let decoded = decompressed(input_bytes).and_then(|bytes| {
+        (format.decode)(&bytes)
+            .map_err(|e| MessageError::parse(format!("invalid {}: {e}", format.name)))
+    });
+    let (res, Envelope { id, options }) = match decoded {
+        Ok(mut value) => {
+            if !overrides.is_empty()
+                && let Some(problem) = value.as_object_mut()
+            {
+                let options = problem
+                    .entry("options")
+                    .or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()));
+                if let Some(options) = options.as_object_mut() {
+                    options.extend(overrides.clone());
+                }
+            }
+            answer_value(&value, start, true)
+        }
+        Err(e) => (Err(e), Envelope::default()),
+    };
+    (Reply::new(res, id), options)
+}
+
+fn compressed(bytes: Vec<u8>, options: &Options) -> Vec<u8> {
+    if options.compress_output {
+        gzip(&bytes)
+    } else {
+        bytes
+    }
+}
+
+/// Solves a JSON-encoded problem like [`solve`], but as a safe function for
+/// Rust callers and for bindings that cannot pass raw pointers, such as
+/// JavaScript with the `wasm` feature.
+#[must_use]
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
+pub fn solve_json(input: &str) -> String {
+    let (res, envelope) = answer(input.as_bytes(), false);
+    response(res, envelope.id, envelope.options.pretty_output)
+}
+
+/// Encoding of problems and responses for [`solve_bytes`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Encoding {
+    Json,
+    MessagePack,
+}
+
+/// Solves a problem like [`solve2`] or [`solve_msgpack`], but as a safe
+/// function for Rust callers. `overrides` replace keys of `options` in the
+/// problem, such as `pretty_output`.
+///
+/// # Errors
+/// Fails with the encoded [`MessageError`] if the problem cannot be solved.
+pub fn solve_bytes(
+    input: &[u8],
+    encoding: Encoding,
+    overrides: &serde_json::Map<String, serde_json::Value>,
+) -> Result<Vec<u8>, Vec<u8>> {
+    let format = match encoding {
+        Encoding::Json => &JSON,
+        Encoding::MessagePack => &MSGPACK,
+    };
+    let (reply, options) = answer_decoded(input, format, overrides);
+    let bytes = if encoding == Encoding::Json && options.pretty_output {
+        serde_json::to_vec_pretty(&reply).unwrap_or_else(|_| INTERNAL_ERROR.into())
+    } else {
+        (format.encode)(&reply)
+    };
+    let bytes = compressed(bytes, &options);
+    match reply {
+        Reply::Solution(_) => Ok(bytes),
+        Reply::Error(_) => Err(bytes),
+    }
+}
+
+/// Solves a problem that was decoded into a JSON value, like [`answer`].
+fn answer_value(
+    value: &serde_json::Value,
+    start: Instant,
+    compressed: bool,
+) -> (Result<MessageSolution, MessageError>, Envelope) {
+    let (input, envelope) = read_value(value, compressed);
+    let res = input.and_then(|input| solve_problem(input, start));
+    (res, envelope)
+}
+
+/// Reads a problem from a JSON value for [`answer_value`].
+fn read_value(
+    value: &serde_json::Value,
+    compressed: bool,
+) -> (Result<MessageProblem<'_>, MessageError>, Envelope) {
+    match MessageProblem::deserialize(value) {
+        Ok(input) => {
+            let envelope = Envelope {
+                id: input.id.clone(),
+                options: input.options.clone(),
+            };
+            let input = match strict::unknown_key(value).filter(|_| input.strict) {
+                Some(key) => Err(MessageError::parse(format!("unknown field '{key}'"))),
+                None if input.options.compress_output && !compressed => Err(uncompressible()),
+                None => Ok(input),
+            };
+            (input, envelope)
+        }
+        Err(e) => {
+            let envelope = Envelope::deserialize(value).unwrap_or_default();
+            let err = MessageError::parse(format!("invalid problem: {e}"));
+            (Err(err), envelope)
+        }
+    }
+}
+
+/// Like [`response`], but as a JSON value for other encodings.
+#[derive(Deserialize, Default)]
+struct Envelope {
+    #[serde(default)]
+    id: Option<serde_json::Value>,
+    #[serde(default = "defaults::options", deserialize_with = "defaults::under")]
+    options: Options,
+}
+
+/// Encodes a solution or an error as JSON, tagged with the given id.
+fn response(
+    res: Result<MessageSolution, MessageError>,
+    id: Option<serde_json::Value>,
+    pretty: bool,
+) -> String {
+    res.and_then(|mut sol| {
+        sol.id.clone_from(&id);
+        encode(&sol, pretty)
+            .map_err(|e| MessageError::internal(format!("could not serialise solution: {e}")))
+    })
+    .unwrap_or_else(|mut e| {
+        e.id = id;
+        encode(&e, pretty).unwrap_or_else(|_| INTERNAL_ERROR.to_owned())
+    })
+}
+fn encode(value: &impl Serialize, pretty: bool) -> serde_json::Result<String> {
+    if pretty {
+        serde_json::to_string_pretty(value)
+    } else {
+        serde_json::to_string(value)
+    }
+}
+const INTERNAL_ERROR: &str =
+    r#"{"status":"error","kind":"internal","message":"could not serialise error"}"#;
+
+#[derive(Clone, Copy)]
+enum RowSense {
+    Leq,
+    Geq,
+    Eq,
+    Range(f64, f64),
+}
+/// Row of the built model, comparing an expression including its offset
+/// with zero.
+struct BuiltRow {
+    expr: Expression,
+    sense: RowSense,
+    name: Option<String>,
+}
+impl From<Op> for RowSense {
+    fn from(op: Op) -> Self {
+        match op {
+            Op::Leq => RowSense::Leq,
+            Op::Geq => RowSense::Geq,
+            Op::Eq => RowSense::Eq,
+        }
+    }
+}
+impl BuiltRow {
+    /// Lower and upper bound of the expression.
+    fn bounds(&self) -> (f64, f64) {
+        match self.sense {
+            RowSense::Leq => (f64::NEG_INFINITY, 0.0),
+            RowSense::Geq => (0.0, f64::INFINITY),
+            RowSense::Eq => (0.0, 0.0),
+            RowSense::Range(lower, upper) => (lower, upper),
+        }
+    }
+    /// Distance of `activity` to the nearest bound, negative if violated.
+    fn slack(&self, activity: f64) -> f64 {
+        let (lower, upper) = self.bounds();
+        (upper - activity).min(activity - lower)
+    }
+    /// Constraint for `good_lp`, which does not know ranged rows.
+    fn constraint(&self) -> Option<good_lp::Constraint> {
+        let expr = self.expr.clone();
+        Some(match self.sense {
+            RowSense::Leq => constraint!(expr <= 0),
+            RowSense::Geq => constraint!(expr >= 0),
+            RowSense::Eq => constraint!(expr == 0),
+            RowSense::Range(..) => return None,
+        })
+    }
+}
+
+/// Model as handed to the backend of [`Options::solver`].
+enum Model {
+    #[cfg(feature = "highs")]
+    Highs(highs::Model),
+    #[cfg(feature = "microlp")]
+    Microlp(good_lp::solvers::microlp::MicroLpProblem),
+}
+
+/// Everything needed to interpret the solution of the model handed to the
+/// backend in terms of the input problem.
+struct Built {
+    vars: Vec<Variable>, // input variables, then helpers of max_of and min_of
+    num_inputs: usize,
+    objective: Expression,
+    rows: Vec<BuiltRow>, // in the order documented on [`MessageSolution`]
+    quadratic: Vec<(usize, usize, f64)>, // variable indices and factor
+    piecewise: Vec<Vec<(usize, f64)>>, // auxiliary columns and costs per term
+    abs: Vec<(usize, f64)>, // auxiliary column and factor per term
+    is_lp: bool,
+    objective_offset: f64, // HiGHS never sees it
+    // empty, or `objective` with no costs followed by `objectives`
+    stages: Vec<lexicographic::Stage>,
+    // `objectives` with their weight in `objective` if they are blended
+    blended: Vec<(f64, Expression)>,
+    dropped_coefficients: usize,
+    option_warning: Option<String>, // about rejected raw options
+}
+
+/// Leaves out terms with tiny factors and counts them.
+struct Cleanup {
+    below: f64,
+    dropped: Cell<usize>,
+}
+impl Cleanup {
+    fn keep(&self, factor: f64) -> bool {
+        let keep = factor.abs() >= self.below;
+        if !keep {
+            self.dropped.set(self.dropped.get() + 1);
+        }
+        keep
+    }
+}
+
+fn build(mut input: MessageProblem) -> Result<(Model, Built), MessageError> {
+    check_rows(&input)?;
+    let backend = input.options.backend();
+    let variables = std::mem::take(&mut input.variables);
+    let is_lp = !variables.iter().any(|v| v.is_integer() || v.semicontinuous)
+        && input.sos.is_empty()
+        && input
+            .piecewise
+            .iter()
+            .all(|p| p.in_order(input.direction()));
+    let mut problem = ProblemVariables::new();
+    let mut vars: Vec<_> = problem.add_all(variables.iter().map_into());
+    let (max_of, min_of) = (
+        std::mem::take(&mut input.max_of),
+        std::mem::take(&mut input.min_of),
+    );
+    let extrema = || max_of.iter().chain(&min_of);
+    vars.extend(extrema().map(|e| problem.add(VariableDefinition::new().name(e.name.as_ref()))));
+    let mapping = variables
+        .iter()
+        .map(|v| v.name.as_ref())
+        .chain(extrema().map(|e| e.name.as_ref()))
+        .zip(vars.iter().copied())
+        .collect::<Vec<_>>();
+    check_extrema(&input, &max_of, &min_of, &mapping)?;
+    let cleanup = Cleanup {
+        below: input.drop_coefficients_below,
+        dropped: Cell::new(0),
+    };
+    let (objective, blended) = linear_objective(&input, &variables, &mapping, &cleanup)?;
+    let problem = match input.direction() {
+        Direction::Min => problem.minimise(objective.clone()),
+        Direction::Max => problem.maximise(objective.clone()),
+    };
+    let quadratic = quadratic_terms(&input.objective_quadratic, &variables, is_lp)?;
+    let stages = objective_stages(&input, &mapping)?;
+    let rows = build_rows(&mut input, &mapping, &cleanup)?;
+    let (model, piecewise, abs, option_warning) = match backend {
+        #[cfg(feature = "microlp")]
+        Backend::Microlp => {
+            let warning = microlp::ignored(&input.options, &variables);
+            (
+                Model::Microlp(microlp::model(problem, &rows)),
+                vec![],
+                vec![],
+                warning,
+            )
+        }
+        #[cfg(feature = "highs")]
+        Backend::Highs => {
+            let mut model = problem
+                .using(highs)
+                .with_all(rows.iter().filter_map(BuiltRow::constraint))
+                .into_inner();
+            add_ranged_rows(&mut model, &vars, &rows)?;
+            // after all input rows, so that only columns and rows are appended
+            add_sos(&mut model, &input.sos, &variables)?;
+            add_indicators(&mut model, &input, &variables)?;
+            let piecewise = add_piecewise(&mut model, &input, &variables)?;
+            let abs = add_abs_terms(&mut model, &input, &variables)?;
+            let extrema = max_of.iter().map(|e| (e, true));
+            let extrema = extrema.chain(min_of.iter().map(|e| (e, false)));
+            add_extrema(
+                &mut model,
+                extrema,
+                &mapping,
+                variables.len(),
+                input.duplicate_policy,
+            )?;
+            let option_warning = input.options.apply(&mut model, is_lp);
+            set_columns(&mut model, &variables)?;
+            // after all other columns, because it covers every column
+            if !quadratic.is_empty() && !raw::set_hessian(&mut model, &quadratic) {
+                return Err(MessageError::solver(
+                    "could not set the quadratic objective terms",
+                ));
+            }
+            (Model::Highs(model), piecewise, abs, option_warning)
+        }
+    };
+    Ok((
+        model,
+        Built {
+            vars,
+            num_inputs: variables.len(),
+            rows,
+            quadratic,
+            piecewise,
+            abs,
+            is_lp,
+            objective_offset: objective.constant(),
+            objective,
+            stages,
+            blended,
+            dropped_coefficients: cleanup.dropped.get(),
+            option_warning,
+        },
+    ))
+}
+
+/// The linear objective with the costs of the variables and the blended
+/// objectives, which are also returned on their own.
+fn linear_objective(
+    input: &MessageProblem,
+    variables: &[VariableDef],
+    mapping: &Vec<(&str, Variable)>,
+    cleanup: &Cleanup,
+) -> Result<(Expression, Vec<(f64, Expression)>), MessageError> {
+    let mut objective = to_expr(
+        mapping,
+        input.objective_offset,
+        &input.objective,
+        input.duplicate_policy,
+        cleanup,
+    )?;
+    // the input variables come first
+    for (v, &(_, var)) in variables.iter().zip(mapping) {
+        if let Some(cost) = v.cost {
+            objective.add_mul(cost, var);
+        }
+    }
+    let blended = blend_objectives(input, mapping, &mut objective, cleanup)?;
+    Ok((objective, blended))
+}
+
+/// Resolves the objectives of a lexicographic optimisation. The quadratic
+/// objective terms cannot be kept at their optimum by a linear row, so they
+/// are rejected.
+fn objective_stages(
+    input: &MessageProblem,
+    mapping: &[(&str, Variable)],
+) -> Result<Vec<lexicographic::Stage>, MessageError> {
+    if input.objectives.is_empty() || input.blend {
+        return Ok(vec![]);
+    }
+    if input.objectives.iter().any(|o| o.weight.is_some()) {
+        return Err(MessageError::validation(
+            "weights of objectives require blend",
+        ));
+    }
+    if !input.objective_quadratic.is_empty() {
+        return Err(MessageError::validation(
+            "objectives cannot be combined with objective_quadratic",
+        ));
+    }
+    let first = lexicographic::Stage {
+        costs: vec![],
+        maximise: input.direction() == Direction::Max,
+        offset: input.objective_offset,
+        tolerance: input.objective_tolerance.unwrap_or(1e-6),
+    };
+    let names = || mapping.iter().map(|(name, _)| *name);
+    let rest = input.objectives.iter().map(|o| {
+        Ok(lexicographic::Stage {
+            costs: resolve(names, &o.terms, input.duplicate_policy)?,
+            maximise: o.direction == Direction::Max,
+            offset: o.offset,
+            tolerance: o.tolerance.unwrap_or(1e-6),
+        })
+    });
+    iter::once(Ok(first)).chain(rest).collect()
+}
+
+/// Adds the blended objectives to `objective`, turned towards its direction,
+/// and returns them with their signed weights.
+fn blend_objectives(
+    input: &MessageProblem,
+    mapping: &Vec<(&str, Variable)>,
+    objective: &mut Expression,
+    cleanup: &Cleanup,
+) -> Result<Vec<(f64, Expression)>, MessageError> {
+    if !input.blend {
+        return Ok(vec![]);
+    }
+    if input.objective_tolerance.is_some() || input.objectives.iter().any(|o| o.tolerance.is_some())
+    {
+        return Err(MessageError::validation(
+            "tolerances of objectives cannot be combined with blend",
+        ));
+    }
+    let mut blended = vec![];
+    for (k, o) in input.objectives.iter().enumerate() {
+        let weight = o.weight.unwrap_or(1.0);
+        if weight.is_nan() || weight <= 0.0 {
+            return Err(MessageError::validation(format!(
+                "objective {k} has weight {weight}, which is not positive"
+            )));
+        }
+        let sign = if o.direction == input.direction() {
+            1.0
+        } else {
+            -1.0
+        };
+        let expr = to_expr(mapping, o.offset, &o.terms, input.duplicate_policy, cleanup)?;
+        *objective += sign * weight * expr.clone();
+        blended.push((sign * weight, expr));
+    }
+    Ok(blended)
+}
+
+/// Solves the objectives after the first one as long as they reach their
+/// optimum. Returns the last solved model, the optimum of every solved
+/// objective, and a status if one of them is unbounded or infeasible.
+#[cfg(feature = "highs")]
+fn solve_stages(
+    mut solved: highs::SolvedModel,
+    stages: &[lexicographic::Stage],
+) -> Result<(highs::SolvedModel, Vec<f64>, Option<Status>), MessageError> {
+    let mut optima = vec![];
+    for (k, stage) in stages.iter().enumerate() {
+        if k > 0 {
+            match solved.status() {
+                HighsModelStatus::Infeasible | HighsModelStatus::UnboundedOrInfeasible => {
+                    return Ok((solved, optima, Some(Status::StageInfeasible)));
+                }
+                HighsModelStatus::Unbounded => {
+                    return Ok((solved, optima, Some(Status::StageUnbounded)));
+                }
+                _ => {}
+            }
+        }
+        if solved.status() != HighsModelStatus::Optimal {
+            break;
+        }
+        // HiGHS never sees the objective offset
+        optima.push(solved.objective_value() + stage.offset);
+        let Some(next) = stages.get(k + 1) else {
+            break;
+        };
+        let model = lexicographic::switch(solved, stage, next).ok_or_else(|| {
+            MessageError::solver(format!("could not switch to objective {}", k + 1))
+        })?;
+        solved = model
+            .try_solve()
+            .map_err(|e| MessageError::solver(format!("HiGHS failed to run: {e:?}")))?;
+    }
+    Ok((solved, optima, None))
+}
+
+/// Resolves the quadratic objective terms, which HiGHS only accepts for
+/// continuous problems.
+fn quadratic_terms(
+    terms: &[QuadraticTerm],
+    variables: &[VariableDef],
+    is_lp: bool,
+) -> Result<Vec<(usize, usize, f64)>, MessageError> {
+    if !terms.is_empty() && !is_lp {
+        return Err(MessageError::validation(
+            "quadratic objective terms require a problem without integer variables",
+        ));
+    }
+    let index = |name: &str| {
+        variables
+            .iter()
+            .position(|v| v.name == name)
+            .ok_or_else(|| {
+                MessageError::validation(format!(
+                    "unknown variable '{name}' in quadratic objective"
+                ))
+            })
+    };
+    terms
+        .iter()
+        .map(|t| Ok((index(&t.name_a)?, index(&t.name_b)?, t.factor)))
+        .collect()
+}
+
+/// Takes the rows out of `input`, in the order documented on
+/// [`MessageSolution`].
+fn build_rows(
+    input: &mut MessageProblem,
+    mapping: &Vec<(&str, Variable)>,
+    cleanup: &Cleanup,
+) -> Result<Vec<BuiltRow>, MessageError> {
+    let policy = input.duplicate_policy;
+    let sections = [
+        (
+            std::mem::take(&mut input.constraints),
+            std::mem::take(&mut input.constraint_offsets),
+            std::mem::take(&mut input.constraint_names),
+            RowSense::Leq,
+        ),
+        (
+            std::mem::take(&mut input.equalities),
+            std::mem::take(&mut input.equalities_offsets),
+            std::mem::take(&mut input.equality_names),
+            RowSense::Eq,
+        ),
+        (
+            std::mem::take(&mut input.geq_constraints),
+            std::mem::take(&mut input.geq_offsets),
+            std::mem::take(&mut input.geq_names),
+            RowSense::Geq,
+        ),
+    ];
+    let legacy = sections
+        .into_iter()
+        .flat_map(|(coeffs, offsets, names, sense)| {
+            let names = names.into_iter().map(Some).chain(iter::repeat(None));
+            let offsets = offsets.into_iter().chain(iter::repeat(0.0));
+            coeffs
+                .into_iter()
+                .zip(offsets)
+                .zip(names)
+                .map(move |((c, off), name)| (c, off, sense, name))
+        });
+    let objects = std::mem::take(&mut input.rows)
+        .into_iter()
+        .map(|r| (r.terms, -r.rhs, r.op.into(), r.name));
+    let to_row = |(i, (c, off, sense, name)): (usize, (Vec<CoeffVar>, _, _, Option<Cow<str>>))| {
+        let expr = to_expr(mapping, off, &c, policy, cleanup).map_err(|e| {
+            let row = name
+                .as_ref()
+                .map_or_else(|| i.to_string(), |n| format!("'{n}'"));
+            MessageError::validation(format!("{} in row {row}", e.message))
+        })?;
+        let name = name.map(Cow::into_owned);
+        Ok(BuiltRow { expr, sense, name })
+    };
+    let mut rows = legacy
+        .chain(objects)
+        .enumerate()
+        .map(to_row)
+        .collect::<Result<Vec<_>, MessageError>>()?;
+    if let Some(matrix) = &input.matrix {
+        for row in matrix_rows(matrix, mapping, rows.len(), policy, cleanup) {
+            rows.push(row?);
+        }
+    }
+    rows.extend(dense_rows(input, mapping, cleanup));
+    // ranged rows are added to HiGHS directly, so they have to come last
+    let ranged = std::mem::take(&mut input.ranged_constraints)
+        .into_iter()
+        .map(|r| (r.terms, 0.0, RowSense::Range(r.lower, r.upper), r.name));
+    let first = rows.len();
+    for row in ranged.enumerate().map(|(i, r)| to_row((first + i, r))) {
+        rows.push(row?);
+    }
+    Ok(rows)
+}
+
+/// Compiles the special ordered sets into binaries and rows.
+#[cfg(feature = "highs")]
+fn add_sos(
+    model: &mut highs::Model,
+    sets: &[Sos],
+    variables: &[VariableDef],
+) -> Result<(), MessageError> {
+    for (i, set) in sets.iter().enumerate() {
+        if set.kind != 1 && set.kind != 2 {
+            return Err(MessageError::validation(format!(
+                "sos {i} has type {} instead of 1 or 2",
+                set.kind
+            )));
+        }
+        if set.variables.len() < 2 {
+            return Err(MessageError::validation(format!(
+                "sos {i} needs at least two variables"
+            )));
+        }
+        let mut members = set
+            .variables
+            .iter()
+            .map(|name| {
+                let (col, v) = (0..)
+                    .zip(variables)
+                    .find(|(_, v)| v.name == *name)
+                    .ok_or_else(|| {
+                        MessageError::validation(format!("unknown variable '{name}' in sos {i}"))
+                    })?;
+                match (v.min, v.max) {
+                    (Some(min), Some(max)) if min.is_finite() && max.is_finite() => {
+                        Ok(sos::Member { col, min, max })
+                    }
+                    _ => Err(MessageError::validation(format!(
+                        "sos {i} needs finite bounds on '{name}'"
+                    ))),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some(weights) = &set.weights {
+            if weights.len() != members.len() {
+                return Err(MessageError::validation(format!(
+                    "sos {i} has {} variables but {} weights",
+                    members.len(),
+                    weights.len()
+                )));
+            }
+            let mut weighted: Vec<_> = weights.iter().zip(members).collect();
+            weighted.sort_by(|a, b| a.0.total_cmp(b.0));
+            members = weighted.into_iter().map(|(_, m)| m).collect();
+        }
+        if !sos::add(model, &members, set.kind == 2) {
+            return Err(MessageError::solver(format!("could not add sos {i}")));
+        }
+    }
+    Ok(())
+}
+
+/// Compiles the indicator rows into big-M rows.
+#[cfg(feature = "highs")]
+fn add_indicators(
+    model: &mut highs::Model,
+    input: &MessageProblem,
+    variables: &[VariableDef],
+) -> Result<(), MessageError> {
+    let names = || variables.iter().map(|v| v.name.as_ref());
+    for (i, ind) in input.indicators.iter().enumerate() {
+        let invalid =
+            |message: String| MessageError::validation(format!("{message} in indicator {i}"));
+        let z = names()
+            .position(|n| n == ind.variable)
+            .ok_or_else(|| invalid(format!("unknown variable '{}'", ind.variable)))?;
+        let v = &variables[z];
+        if !(v.is_integer() && v.min.is_some_and(|m| m >= 0.0) && v.max.is_some_and(|m| m <= 1.0)) {
+            return Err(invalid(format!("variable '{}' is not binary", v.name)));
+        }
+        let active = match ind.active_value.unwrap_or(1) {
+            0 => false,
+            1 => true,
+            other => return Err(invalid(format!("active value {other} is not 0 or 1"))),
+        };
+        let terms =
+            resolve(names, &ind.terms, input.duplicate_policy).map_err(|e| invalid(e.message))?;
+        // bounds of terms - rhs over the variable bounds
+        let (mut lowest, mut highest) = (-ind.rhs, -ind.rhs);
+        for &(col, factor) in terms.iter().filter(|t| t.1 != 0.0) {
+            // helpers of max_of and min_of are unbounded
+            let v = variables.get(col);
+            let min = factor * v.and_then(|v| v.min).unwrap_or(f64::NEG_INFINITY);
+            let max = factor * v.and_then(|v| v.max).unwrap_or(f64::INFINITY);
+            lowest += min.min(max);
+            highest += min.max(max);
+        }
+        let sides = match ind.op {
+            Op::Leq => &[1.0][..],
+            Op::Geq => &[-1.0],
+            Op::Eq => &[1.0, -1.0],
+        };
+        for &sign in sides {
+            // sign * (terms - rhs) <= big_m while the indicator is off
+            let big_m = ind
+                .big_m
+                .unwrap_or(if sign > 0.0 { highest } else { -lowest });
+            if !big_m.is_finite() {
+                return Err(invalid(
+                    "cannot derive a finite big_m from the variable bounds".into(),
+                ));
+            }
+            let (switch, upper) = if active {
+                (big_m, big_m + sign * ind.rhs)
+            } else {
+                (-big_m, sign * ind.rhs)
+            };
+            let row = terms.iter().map(|&(col, f)| (col, sign * f));
+            let (index, factors) = sparse_row(row.chain([(z, switch)]));
+            if !raw::add_row(model, f64::NEG_INFINITY, upper, &index, &factors) {
+                return Err(MessageError::solver(format!("could not add indicator {i}")));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Adds a column `t >= |terms + offset|` for each absolute value term, and
+/// returns them with their factors.
+#[cfg(feature = "highs")]
+fn add_abs_terms(
+    model: &mut highs::Model,
+    input: &MessageProblem,
+    variables: &[VariableDef],
+) -> Result<Vec<(usize, f64)>, MessageError> {
+    let names = || variables.iter().map(|v| v.name.as_ref());
+    let mut columns = vec![];
+    for (i, term) in input.abs_terms.iter().enumerate() {
+        let invalid =
+            |message: String| MessageError::validation(format!("{message} in abs term {i}"));
+        // t is only pushed down onto |terms + offset| if that pays off
+        let convex = match input.direction() {
+            Direction::Min => term.factor >= 0.0,
+            Direction::Max => term.factor <= 0.0,
+        };
+        if !convex {
+            return Err(invalid(format!(
+                "factor {} would reward a large absolute value, which the objective cannot express",
+                term.factor
+            )));
+        }
+        let terms =
+            resolve(names, &term.terms, input.duplicate_policy).map_err(|e| invalid(e.message))?;
+        let t = raw::add_column(model, term.factor, 0.0, f64::INFINITY)
+            .ok_or_else(|| MessageError::solver(format!("could not add abs term {i}")))?;
+        // t - (terms + offset) >= 0 and t + (terms + offset) >= 0
+        for (sign, lower) in [(-1.0, term.offset), (1.0, -term.offset)] {
+            let (mut index, mut factors) =
+                sparse_row(terms.iter().map(|&(col, f)| (col, sign * f)));
+            index.push(t);
+            factors.push(1.0);
+            if !raw::add_row(model, lower, f64::INFINITY, &index, &factors) {
+                return Err(MessageError::solver(format!("could not add abs term {i}")));
+            }
+        }
+        if let Ok(t) = usize::try_from(t) {
+            columns.push((t, term.factor));
+        }
+    }
+    Ok(columns)
+}
+
+/// Rejects helpers of `max_of` and `min_of` whose names are taken, or that the
+/// objective pushes away from their expressions, which nothing would stop.
+fn check_extrema(
+    input: &MessageProblem,
+    max_of: &[Extremum],
+    min_of: &[Extremum],
+    mapping: &[(&str, Variable)],
+) -> Result<(), MessageError> {
+    let first = mapping.len() - max_of.len() - min_of.len();
+    let mut seen: HashSet<_> = mapping[..first].iter().map(|(name, _)| *name).collect();
+    if let Some((name, _)) = mapping[first..]
+        .iter()
+        .find(|(name, _)| !seen.insert(*name))
+    {
+        return Err(MessageError::validation(format!(
+            "max_of or min_of variable '{name}' has the name of another variable"
+        )));
+    }
+    let names = || mapping.iter().map(|(name, _)| *name);
+    // unknown variables are rejected when building the objective
+    let Ok(objective) = resolve(names, &input.objective, DuplicatePolicy::Sum) else {
+        return Ok(());
+    };
+    for (col, factor) in objective.into_iter().filter(|t| t.0 >= first) {
+        let is_max = col - first < max_of.len();
+        let pushed_down = (input.direction() == Direction::Min) == (factor > 0.0);
+        if factor != 0.0 && is_max != pushed_down {
+            let (kind, side) = if is_max {
+                ("max_of", "below")
+            } else {
+                ("min_of", "above")
+            };
+            return Err(MessageError::validation(format!(
+                "{kind} variable '{}' is only bounded from {side}, so the objective must not push it away from its expressions",
+                mapping[col].0
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Bounds the helpers of `max_of` and `min_of`, flagged by whether they are of
+/// `max_of`, by their expressions. They are the columns from `first` on.
+#[cfg(feature = "highs")]
+fn add_extrema<'a>(
+    model: &mut highs::Model,
+    extrema: impl Iterator<Item = (&'a Extremum<'a>, bool)>,
+    mapping: &[(&str, Variable)],
+    first: usize,
+    policy: DuplicatePolicy,
+) -> Result<(), MessageError> {
+    let names = || mapping.iter().map(|(name, _)| *name);
+    for (y, (extremum, is_max)) in (first..).zip(extrema) {
+        let name = &extremum.name;
+        for expr in &extremum.expressions {
+            let terms = resolve(names, &expr.terms, policy).map_err(|e| {
+                MessageError::validation(format!("{} in the expressions of '{name}'", e.message))
+            })?;
+            // y - terms >= offset for max_of, y - terms <= offset for min_of
+            let row = terms.into_iter().map(|(col, f)| (col, -f));
+            let (index, factors) = sparse_row(row.chain([(y, 1.0)]));
+            let (lower, upper) = if is_max {
+                (expr.offset, f64::INFINITY)
+            } else {
+                (f64::NEG_INFINITY, expr.offset)
+            };
+            if !raw::add_row(model, lower, upper, &index, &factors) {
+                return Err(MessageError::solver(format!("could not bound '{name}'")));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Merges terms over columns into the indices and factors of a HiGHS row,
+/// which needs every column at most once.
+#[cfg(feature = "highs")]
+fn sparse_row(
+    terms: impl IntoIterator<Item = (usize, f64)>,
+) -> (Vec<highs_sys::HighsInt>, Vec<f64>) {
+    let mut row = BTreeMap::new();
+    for (col, factor) in terms {
+        *row.entry(col).or_insert(0.0) += factor;
+    }
+    row.into_iter()
+        .filter_map(|(col, factor)| Some((highs_sys::HighsInt::try_from(col).ok()?, factor)))
+        .unzip()
+}
+
+/// Expands the piecewise-linear terms into segment columns.
+#[cfg(feature = "highs")]
+fn add_piecewise(
+    model: &mut highs::Model,
+    input: &MessageProblem,
+    variables: &[VariableDef],
+) -> Result<Vec<Vec<(usize, f64)>>, MessageError> {
+    let column: Vec<highs_sys::HighsInt> = (0..).take(variables.len()).collect();
+    let mut terms = vec![];
+    for (i, term) in input.piecewise.iter().enumerate() {
+        let invalid =
+            |message: String| MessageError::validation(format!("{message} in piecewise term {i}"));
+        let x = variables
+            .iter()
+            .position(|v| v.name == term.variable)
+            .ok_or_else(|| invalid(format!("unknown variable '{}'", term.variable)))?;
+        let bp = &term.breakpoints;
+        if term.slopes.is_empty() || bp.len() != term.slopes.len() + 1 {
+            return Err(invalid(format!(
+                "got {} breakpoints for {} slopes",
+                bp.len(),
+                term.slopes.len()
+            )));
+        }
+        if bp.windows(2).any(|w| w[0] >= w[1]) {
+            return Err(invalid("breakpoints do not increase".into()));
+        }
+        let in_order = term.in_order(input.direction());
+        // an unbounded last segment is only possible without binaries
+        let finite = if in_order { &bp[..bp.len() - 1] } else { bp };
+        if !finite.iter().all(|b| b.is_finite()) {
+            return Err(invalid("breakpoints are not finite".into()));
+        }
+        let segments = piecewise::add(model, column[x], bp, &term.slopes, in_order)
+            .ok_or_else(|| MessageError::solver(format!("could not add piecewise term {i}")))?;
+        let segments = segments
+            .into_iter()
+            .filter_map(|(col, slope)| Some((usize::try_from(col).ok()?, slope)));
+        terms.push(segments.collect());
+    }
+    Ok(terms)
+}
+
+/// Sets the column types that `good_lp` does not know, and hands the initial
+/// values to HiGHS if any variable has one. Only the columns of the variables
+/// are set, since the model may have more by now.
+#[cfg(feature = "highs")]
+fn set_columns(model: &mut highs::Model, variables: &[VariableDef]) -> Result<(), MessageError> {
+    for (col, v) in (0..).zip(variables) {
+        if v.semicontinuous && !raw::set_semicontinuous(model, col, v.is_integer()) {
+            return Err(MessageError::solver(format!(
+                "could not make '{}' semicontinuous",
+                v.name
+            )));
+        }
+    }
+    if variables.iter().any(|v| v.initial.is_some()) {
+        let initial: Vec<_> = variables
+            .iter()
+            .map(|v| v.initial.unwrap_or(f64::INFINITY))
+            .collect();
+        if !raw::set_start(model, &initial) {
+            return Err(MessageError::solver("could not set initial values"));
+        }
+    }
+    Ok(())
+}
+
+/// Appends the sensed rows to `rows`.
+fn add_sensed_rows(input: &mut MessageProblem) -> Result<(), MessageError> {
+    let Some(rows) = input.sensed_rows.take() else {
+        return Ok(());
+    };
+    let (terms, senses, rhs) = (rows.terms.len(), rows.senses.len(), rows.rhs.len());
+    if senses != terms || rhs != terms {
+        return Err(MessageError::validation(format!(
+            "sensed_rows has {terms} term lists, {senses} senses and {rhs} right-hand sides, \
+             so row {} is incomplete",
+            terms.min(senses).min(rhs)
+        )));
+    }
+    let rows = rows.terms.into_iter().zip(rows.senses).zip(rows.rhs);
+    for (i, ((terms, sense), rhs)) in rows.enumerate() {
+        let op = match sense.as_str() {
+            "<=" => Op::Leq,
+            ">=" => Op::Geq,
+            "==" => Op::Eq,
+            _ => {
+                return Err(MessageError::validation(format!(
+                    "sense {i} is '{sense}' instead of <=, >= or =="
+                )));
+            }
+        };
+        input.rows.push(Row {
+            terms,
+            op,
+            rhs,
+            name: None,
+        });
+    }
+    Ok(())
+}
+
+/// Names that rows can reference, normalized for `name_matching`.
+fn known_names<'a>(input: &'a MessageProblem) -> HashSet<Cow<'a, str>> {
+    let mode = input.name_matching;
+    let extrema = input.max_of.iter().chain(&input.min_of);
+    input
+        .variables
+        .iter()
+        .map(|v| v.name.as_ref())
+        .chain(extrema.map(|e| e.name.as_ref()))
+        .map(|name| mode.normalize(name))
+        .collect()
+}
+
+/// Expands the constraint templates into rows named `name[t]` for every
+/// value `t` of their index.
+fn expand_templates(input: &mut MessageProblem) -> Result<(), MessageError> {
+    let templates = std::mem::take(&mut input.constraint_templates);
+    let mode = input.name_matching;
+    let names = known_names(input);
+    let mut rows = vec![];
+    for template in &templates {
+        let (start, end) = template.range;
+        for t in start..end {
+            let terms = template.terms.iter().map(|term| {
+                let name = template::substitute(&term.name, &template.index, t).map_err(|e| {
+                    MessageError::validation(format!("template '{}': {e}", template.name))
+                })?;
+                if !names.contains(&mode.normalize(&name)) {
+                    return Err(MessageError::validation(format!(
+                        "template '{}' references unknown variable '{name}' for {} = {t}",
+                        template.name, template.index
+                    )));
+                }
+                Ok(CoeffVar::Named {
+                    name: name.into(),
+                    factor: term.factor,
+                })
+            });
+            rows.push(Row {
+                terms: terms.collect::<Result<_, _>>()?,
+                op: template.op,
+                rhs: template.rhs,
+                name: Some(format!("{}[{t}]", template.name).into()),
+            });
+        }
+    }
+    input.rows.extend(rows);
+    Ok(())
+}
+
+/// Turns the constraint strings into rows and adds the objective string to
+/// the objective. Names have to be those of variables or of helpers of
+/// `max_of` and `min_of`.
+fn parse_expressions(input: &mut MessageProblem) -> Result<(), MessageError> {
+    let mode = input.name_matching;
+    let names = known_names(input);
+    let syntax = |what: &str, e: expr::SyntaxError| {
+        MessageError::parse(format!("{what} at offset {}: {}", e.offset, e.message))
+            .at(e.offset, e.token)
+    };
+    let terms = |what: &str, linear: expr::Linear| {
+        linear
+            .terms
+            .into_iter()
+            .map(|t| {
+                if names.contains(&mode.normalize(&t.name)) {
+                    Ok(CoeffVar::Named {
+                        name: t.name.into(),
+                        factor: t.factor,
+                    })
+                } else {
+                    Err(MessageError::validation(format!(
+                        "{what} at offset {}: unknown variable '{}'",
+                        t.offset, t.name
+                    ))
+                    .at(t.offset, t.name))
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let mut rows = vec![];
+    for (i, text) in input.expressions.iter().enumerate() {
+        let what = format!("expression {i}");
+        let (linear, op) = expr::constraint(text).map_err(|e| syntax(&what, e))?;
+        let rhs = -linear.constant;
+        rows.push(Row {
+            terms: terms(&what, linear)?,
+            op,
+            rhs,
+            name: None,
+        });
+    }
+    let mut objective = vec![];
+    let mut offset = 0.0;
+    if let Some(text) = &input.objective_expr {
+        let linear = expr::objective(text).map_err(|e| syntax("objective_expr", e))?;
+        offset = linear.constant;
+        objective = terms("objective_expr", linear)?;
+    }
+    input.rows.extend(rows);
+    input.objective.extend(objective);
+    input.objective_offset += offset;
+    Ok(())
+}
+
+/// Turns the warm start and then the partial start into initial values.
+/// Values outside of the bounds or fractional values of integer variables
+/// are still used, since they are only hints, and are described in the
+/// returned warnings.
+fn apply_starts(input: &mut MessageProblem) -> Result<Vec<String>, MessageError> {
+    let mut starts = vec![];
+    if let Some(start) = input.warm_start.take() {
+        if start.len() != input.variables.len() {
+            return Err(MessageError::validation(format!(
+                "warm_start has {} values for {} variables",
+                start.len(),
+                input.variables.len()
+            )));
+        }
+        starts.extend(start.into_iter().enumerate());
+    }
+    for StartValue { name, value } in std::mem::take(&mut input.partial_start) {
+        let i = input
+            .variables
+            .iter()
+            .position(|v| v.name == name)
+            .ok_or_else(|| {
+                MessageError::validation(format!("unknown variable '{name}' in partial_start"))
+            })?;
+        starts.push((i, value));
+    }
+    let mut warnings = vec![];
+    for (i, value) in starts {
+        let v = &mut input.variables[i];
+        let min = v.min.unwrap_or(f64::NEG_INFINITY);
+        let max = v.max.unwrap_or(f64::INFINITY);
+        let admitted = (min..=max).contains(&value) || v.semicontinuous && value == 0.0;
+        if !admitted {
+            warnings.push(format!(
+                "warm start value {value} of '{}' is outside of its bounds [{min}, {max}]",
+                v.name
+            ));
+        } else if v.is_integer() && value.fract() != 0.0 {
+            warnings.push(format!(
+                "warm start value {value} of integer variable '{}' is fractional",
+                v.name
+            ));
+        }
+        v.initial = Some(value);
+    }
+    Ok(warnings)
+}
+
+/// Rejects offsets that do not line up with their rows and empty ranges.
+/// Omitted offsets are all zero.
+fn check_rows(input: &MessageProblem) -> Result<(), MessageError> {
+    if !input.constraint_offsets.is_empty()
+        && input.constraint_offsets.len() != input.constraints.len()
+    {
+        return Err(MessageError::validation(format!(
+            "got {} constraints but {} constraint offsets",
+            input.constraints.len(),
+            input.constraint_offsets.len()
+        )));
+    }
+    if !input.equalities_offsets.is_empty()
+        && input.equalities_offsets.len() != input.equalities.len()
+    {
+        return Err(MessageError::validation(format!(
+            "got {} equalities but {} equality offsets",
+            input.equalities.len(),
+            input.equalities_offsets.len()
+        )));
+    }
+    if !input.geq_offsets.is_empty() && input.geq_offsets.len() != input.geq_constraints.len() {
+        return Err(MessageError::validation(format!(
+            "got {} geq constraints but {} geq offsets",
+            input.geq_constraints.len(),
+            input.geq_offsets.len()
+        )));
+    }
+    let names = [
+        (
+            "constraint",
+            input.constraints.len(),
+            &input.constraint_names,
+        ),
+        ("equality", input.equalities.len(), &input.equality_names),
+        ("geq", input.geq_constraints.len(), &input.geq_names),
+    ];
+    for (kind, rows, names) in names {
+        if !names.is_empty() && names.len() != rows {
+            return Err(MessageError::validation(format!(
+                "got {rows} {kind} rows but {} {kind} names",
+                names.len()
+            )));
+        }
+    }
+    for (i, r) in input.ranged_constraints.iter().enumerate() {
+        if r.lower > r.upper {
+            let row = r
+                .name
+                .as_ref()
+                .map_or_else(|| i.to_string(), |n| format!("'{n}'"));
+            return Err(MessageError::validation(format!(
+                "ranged constraint {row} has lower bound {} above upper bound {}",
+                r.lower, r.upper
+            )));
+        }
+    }
+
+    let objects = input.rows.iter().filter_map(|r| r.name.as_ref());
+    let ranged = input
+        .ranged_constraints
+        .iter()
+        .filter_map(|r| r.name.as_ref());
+    let names = input
+        .constraint_names
+        .iter()
+        .chain(&input.equality_names)
+        .chain(&input.geq_names)
+        .chain(objects)
+        .chain(ranged);
+    let mut seen = HashSet::new();
+    for name in names {
+        if !seen.insert(name) {
+            return Err(MessageError::validation(format!(
+                "duplicate row name '{name}'"
+            )));
+        }
+    }
+    if let Some(matrix) = &input.matrix {
+        check_matrix(matrix, input.variables.len())?;
+    }
+    check_dense_rows(input)
+}
+
+/// Builds the dense rows that passed [`check_dense_rows`].
+fn dense_rows<'a>(
+    input: &'a MessageProblem,
+    vars: &'a [(&str, Variable)],
+    cleanup: &'a Cleanup,
+) -> impl Iterator<Item = BuiltRow> + 'a {
+    let ops = input.dense_ops.iter().zip(&input.dense_rhs);
+    input
+        .dense_rows
+        .iter()
+        .zip(ops)
+        .map(move |(coefficients, (&op, &rhs))| {
+            let mut expr = Expression::with_capacity(coefficients.len());
+            for (&factor, (_, var)) in coefficients.iter().zip(vars) {
+                if factor != 0.0 && cleanup.keep(factor) {
+                    expr.add_mul(factor, *var);
+                }
+            }
+            BuiltRow {
+                expr: expr - rhs,
+                sense: op.into(),
+                name: None,
+            }
+        })
+}
+
+/// Rejects dense rows that do not have one coefficient per variable.
+fn check_dense_rows(input: &MessageProblem) -> Result<(), MessageError> {
+    let rows = input.dense_rows.len();
+    if input.dense_ops.len() != rows || input.dense_rhs.len() != rows {
+        return Err(MessageError::validation(format!(
+            "got {rows} dense rows but {} ops and {} right-hand sides",
+            input.dense_ops.len(),
+            input.dense_rhs.len()
+        )));
+    }
+    let num_vars = input.variables.len();
+    if let Some(i) = input.dense_rows.iter().position(|r| r.len() != num_vars) {
+        return Err(MessageError::validation(format!(
+            "dense row {i} has {} coefficients but there are {num_vars} variables",
+            input.dense_rows[i].len()
+        )));
+    }
+    Ok(())
+}
+
+/// Builds the rows of a [`SparseMatrix`] that passed [`check_matrix`],
+/// numbering them from `first` in errors.
+fn matrix_rows<'a>(
+    matrix: &'a SparseMatrix,
+    vars: &'a [(&str, Variable)],
+    first: usize,
+    policy: DuplicatePolicy,
+    cleanup: &'a Cleanup,
+) -> impl Iterator<Item = Result<BuiltRow, MessageError>> + 'a {
+    let ops = matrix.ops.iter().zip(&matrix.rhs);
+    matrix
+        .row_starts
+        .windows(2)
+        .zip(ops)
+        .enumerate()
+        .map(move |(i, (range, (&op, &rhs)))| {
+            let (start, end) = (range[0], range[1]);
+            let cols = matrix.col_indices[start..end].iter().copied();
+            let mut terms: Vec<_> = cols
+                .zip(matrix.values[start..end].iter().copied())
+                .collect();
+            policy.apply(&mut terms).map_err(|col| {
+                MessageError::validation(format!(
+                    "duplicate variable '{}' in row {}",
+                    vars[col].0,
+                    first + i
+                ))
+            })?;
+            let mut expr = Expression::with_capacity(terms.len());
+            for (col, factor) in terms.into_iter().filter(|&(_, f)| cleanup.keep(f)) {
+                expr.add_mul(factor, vars[col].1);
+            }
+            Ok(BuiltRow {
+                expr: expr - rhs,
+                sense: op.into(),
+                name: None,
+            })
+        })
+}
+
+/// Rejects a [`SparseMatrix`] whose arrays do not fit together.
+fn check_matrix(matrix: &SparseMatrix, num_vars: usize) -> Result<(), MessageError> {
+    let nonzeros = matrix.col_indices.len();
+    if matrix.values.len() != nonzeros {
+        return Err(MessageError::validation(format!(
+            "got {nonzeros} matrix column indices but {} values",
+            matrix.values.len()
+        )));
+    }
+    let num_rows = matrix.row_starts.len().saturating_sub(1);
+    if matrix.ops.len() != num_rows || matrix.rhs.len() != num_rows {
+        return Err(MessageError::validation(format!(
+            "got {num_rows} matrix rows but {} ops and {} right-hand sides",
+            matrix.ops.len(),
+            matrix.rhs.len()
+        )));
+    }
+    if matrix.row_starts.first().is_some_and(|&s| s != 0) {
+        return Err(MessageError::validation(
+            "matrix row_starts must begin at 0",
+        ));
+    }
+    if let Some(i) = matrix.row_starts.windows(2).position(|w| w[0] > w[1]) {
+        return Err(MessageError::validation(format!(
+            "matrix row_starts decrease after row {i}"
+        )));
+    }
+    if matrix.row_starts.last().is_some_and(|&s| s != nonzeros) {
+        return Err(MessageError::validation(format!(
+            "matrix row_starts must end at {nonzeros}, the number of values"
+        )));
+    }
+    if let Some(k) = matrix.col_indices.iter().position(|&c| c >= num_vars) {
+        return Err(MessageError::validation(format!(
+            "matrix column index {} at position {k} is out of range for {num_vars} variables",
+            matrix.col_indices[k]
+        )));
+    }
+    Ok(())
+}
+
+/// Adds the ranged rows to HiGHS directly. They come last, so the rows of
+/// HiGHS stay in the same order as the built rows.
+#[cfg(feature = "highs")]
+fn add_ranged_rows(
+    model: &mut highs::Model,
+    vars: &[Variable],
+    rows: &[BuiltRow],
+) -> Result<(), MessageError> {
+    let columns: HashMap<_, _> = vars.iter().copied().zip(0..).collect();
+    for row in rows.iter().filter(|r| r.constraint().is_none()) {
+        let (lower, upper) = row.bounds();
+        let constant = row.expr.constant();
+        let (index, factors): (Vec<_>, Vec<_>) = (&row.expr)
+            .linear_coefficients()
+            .map(|(v, f)| (columns[&v], f))
+            .unzip();
+        if !raw::add_row(model, lower - constant, upper - constant, &index, &factors) {
+            return Err(MessageError::solver("could not add ranged constraint"));
+        }
+    }
+    Ok(())
+}
+
+/// Brings the variables and all references to them into the form that the
+/// model is built from. Returns the warnings about the options and the
+/// starting values.
+fn prepare(input: &mut MessageProblem) -> Result<Vec<String>, MessageError> {
+    let options = &mut input.options;
+    options.verbose = options.or_problem("verbose", options.verbose, input.verbose);
+    options.integrality_tolerance = options.or_problem(
+        "integrality_tolerance",
+        options.integrality_tolerance,
+        input.integrality_tolerance,
+    );
+    let mut warnings = input.options.check()?;
+    let arrays = std::mem::take(&mut input.variable_arrays);
+    input
+        .variables
+        .extend(arrays.iter().flat_map(VariableArray::expand));
+    for v in &mut input.variables {
+        v.apply_defaults(&input.variable_defaults);
+        v.drop_infinite_bounds();
+        v.expand_binary()?;
+        if input.relax_integrality {
+            v.integer = Some(false);
+        }
+        v.expand_fixed()?;
+        v.check_semicontinuous()?;
+    }
+    warnings.extend(priority_warning(&input.variables));
+    add_sensed_rows(input)?;
+    expand_templates(input)?;
+    parse_expressions(input)?;
+    if input.direction.is_none() && input.has_objective() {
+        return Err(MessageError::validation(
+            "direction is required for problems with an objective",
+        ));
+    }
+    names::canonicalize(input)?;
+    warnings.extend(apply_starts(input)?);
+    Ok(warnings)
+}
+
+/// Warns about the continuous variables with a branching priority, which is
+/// meant after their integrality was settled.
+fn priority_warning(variables: &[VariableDef]) -> Option<String> {
+    let names: Vec<_> = variables
+        .iter()
+        .filter(|v| v.branch_priority.is_some() && !v.is_integer())
+        .map(|v| format!("'{}'", v.name))
+        .collect();
+    (!names.is_empty()).then(|| {
+        format!(
+            "branch_priority is ignored for the continuous variables {}",
+            names.join(", ")
+        )
+    })
+}
+
+/// A problem that is built into a model, with everything needed to report
+/// its solution. Unlike the problem, it borrows nothing from the input, which
+/// can be dropped before solving.
+struct Ready {
+    model: Model,
+    report: Report,
+}
+
+/// Everything besides the model that is needed to report its solution.
+struct Report {
+    built: Built,
+    layout: Layout,
+    extras: Extras,
+    start_warnings: Vec<String>,
+    model_dump: Option<dump::ModelDump>,
+    capture_log: bool,
+    parse_ms: f64,
+    start: Instant, // of building
+}
+
+impl Ready {
+    /// Builds a problem that was read from `start` on. Fails if the problem
+    /// is invalid.
+    fn new(mut input: MessageProblem, start: Instant) -> Result<Self, MessageError> {
+        let parse_ms = ms(start, Instant::now());
+        let start = Instant::now();
+        let start_warnings = prepare(&mut input)?;
+        #[cfg(feature = "microlp")]
+        if input.options.backend() == Backend::Microlp {
+            microlp::check(&input)?;
+        }
+        let layout = Layout::new(&input);
+        let extras = Extras::new(&input)?;
+        let capture_log = input.options.capture_log;
+        let (model, built) = build(input)?;
+        #[cfg(feature = "highs")]
+        let model_dump = match (&model, &extras.dump) {
+            (Model::Highs(model), Some((format, columns))) => {
+                Some(dump_model(model, &built, *format, columns)?)
+            }
+            _ => None,
+        };
+        // only HiGHS can write the model
+        #[cfg(not(feature = "highs"))]
+        let model_dump = None;
+        let report = Report {
+            built,
+            layout,
+            extras,
+            start_warnings,
+            model_dump,
+            capture_log,
+            parse_ms,
+            start,
+        };
+        Ok(Ready { model, report })
+    }
+}
+
+/// Builds and solves a problem that was read from `start` on.
+fn solve_problem(input: MessageProblem, start: Instant) -> Result<MessageSolution, MessageError> {
+    Ready::new(input, start).and_then(solve_ready)
+}
+
+/// Solves a built problem with the backend that it was built for. Fails if
+/// the solver does not reach a conclusive result.
+fn solve_ready(ready: Ready) -> Result<MessageSolution, MessageError> {
+    match ready.model {
+        #[cfg(feature = "highs")]
+        Model::Highs(model) => solve_highs(model, ready.report),
+        #[cfg(feature = "microlp")]
+        Model::Microlp(model) => microlp::solve(model, ready.report),
+    }
+}
+
+#[cfg(feature = "highs")]
+fn solve_highs(mut model: highs::Model, report: Report) -> Result<MessageSolution, MessageError> {
+    let log = report
+        .capture_log
+        .then(|| raw::LogFile::capture(&mut model))
+        .map(|log| log.ok_or_else(|| MessageError::solver("could not capture the log")))
+        .transpose()?;
+    let mut res = solve_highs_model(model, report)?;
+    // the model is gone, so HiGHS has closed the log file
+    if let Some(log) = log {
+        res.log = Some(
+            log.read()
+                .ok_or_else(|| MessageError::solver("could not read the log"))?,
+        );
+    }
+    Ok(res)
+}
+
+#[cfg(feature = "highs")]
+fn solve_highs_model(
+    mut model: highs::Model,
+    report: Report,
+) -> Result<MessageSolution, MessageError> {
+    let Report {
+        built,
+        layout,
+        extras,
+        start_warnings,
+        model_dump,
+        parse_ms,
+        start,
+        ..
+    } = report;
+    let built_at = Instant::now();
+    let presolve = raw::presolve_reductions(&mut model);
+    let solved = model
+        .try_solve()
+        .map_err(|e| MessageError::solver(format!("HiGHS failed to run: {e:?}")))?;
+    let (mut solved, optima, stage_failure) = solve_stages(solved, &built.stages)?;
+    let solved_at = Instant::now();
+    let highs_status = solved.status();
+
+    let has_incumbent = solved.primal_solution_status() == HighsSolutionStatus::Feasible;
+    let status = match stage_failure {
+        Some(status) => status,
+        None => classify(&solved, has_incumbent)?,
+    };
+    let mut res = if has_incumbent && status.has_values() {
+        let sol = solved.get_solution();
+        let mut res = incumbent(sol.columns(), status, &built, &extras);
+        // dual information is only meaningful at an optimal basis
+        if built.is_lp && status == Status::Optimal {
+            add_dual_details(&mut res, &mut solved, &sol, &built, extras.sensitivity)?;
+        }
+        res
+    } else {
+        MessageSolution::new(status, vec![])
+    };
+    res.warnings.extend(start_warnings);
+    if !built.stages.is_empty() {
+        res.stage_objectives = Some(optima);
+    }
+    let Built {
+        vars,
+        num_inputs,
+        dropped_coefficients,
+        rows,
+        is_lp,
+        objective_offset,
+        option_warning,
+        ..
+    } = built;
+    res.warnings.extend(option_warning);
+    if let Some(explain) = extras.explain.filter(|_| status == Status::Infeasible) {
+        res.infeasibility = Some(explain_infeasibility(explain, &vars, num_inputs, &rows));
+    }
+    add_row_names(&mut res, &rows);
+    if status == Status::Unbounded {
+        res.ray = raw::primal_ray(&solved, num_inputs);
+    }
+    if !is_lp {
+        add_mip_bounds(&mut res, &solved, objective_offset);
+    }
+    res.stats = Some(Stats {
+        parse_ms,
+        build_ms: ms(start, built_at),
+        solve_ms: ms(built_at, solved_at),
+        simplex_iterations: raw::int_info(&solved, c"simplex_iteration_count"),
+        nodes: raw::int64_info(&solved, c"mip_node_count").filter(|n| !is_lp && *n >= 0),
+        presolve: presolve.filter(|_| status.has_values()).map(Presolve::from),
+        dropped_coefficients,
+        threads: extras.threads,
+        seed: raw::int_option(&solved, c"random_seed"),
+        primal_feasibility_tolerance: raw::double_option(&solved, c"primal_feasibility_tolerance"),
+        dual_feasibility_tolerance: raw::double_option(&solved, c"dual_feasibility_tolerance"),
+        memory: extras.memory.map(memory::with_peak),
+        coefficients: Coefficients::of(&rows),
+        emphasis: extras.emphasis,
+        branch_priorities_native: extras.priorities.then_some(false),
+    });
+
+    let integrality_tolerance = layout
+        .integrality_tolerance
+        .or_else(|| raw::double_option(&solved, c"mip_feasibility_tolerance"))
+        .unwrap_or(1e-6);
+    if let Some((integers, limit)) = extras.pool
+        && limit > 0
+        && has_incumbent
+        && status.has_values()
+    {
+        let pool = pool::collect(solved, &integers, num_inputs, objective_offset, limit);
+        res.solutions = Some(pool);
+    }
+
+    if let Some(path) = &extras.solution_file {
+        write_solution_file(&mut res, highs_status, path, &layout.names, &rows);
+    }
+    layout.present(&mut res, integrality_tolerance)?;
+    res.model_dump = model_dump;
+    Ok(res)
+}
+
+/// Adds the best bound and the gap of a MIP.
+#[cfg(feature = "highs")]
+fn add_mip_bounds(res: &mut MessageSolution, solved: &highs::SolvedModel, objective_offset: f64) {
+    // HiGHS never sees the objective offset
+    res.best_bound = raw::double_info(solved, c"mip_dual_bound")
+        .filter(|b| b.is_finite())
+        .map(|b| b + objective_offset);
+    res.mip_gap = raw::double_info(solved, c"mip_gap").filter(|g| g.is_finite());
+}
+
+/// Names of the columns that the input defines, the variables followed by
+/// the results of `max_of` and `min_of`.
+fn column_names(input: &MessageProblem) -> Vec<String> {
+    let variables = input.variables.iter().map(|v| v.name.to_string());
+    let extrema = input.max_of.iter().chain(&input.min_of);
+    variables
+        .chain(extrema.map(|e| e.name.to_string()))
+        .collect()
+}
+
+/// Finds out why a problem is infeasible for `explain_infeasibility`.
+#[cfg(feature = "highs")]
+fn explain_infeasibility(
+    (mut columns, max_solves): (Vec<iis::Column>, usize),
+    vars: &[Variable],
+    num_inputs: usize,
+    rows: &[BuiltRow],
+) -> iis::Iis {
+    // auxiliary columns are not bounded
+    let free = || iis::Column {
+        min: f64::NEG_INFINITY,
+        max: f64::INFINITY,
+        integer: false,
+    };
+    columns.extend(iter::repeat_with(free).take(vars.len() - num_inputs));
+    iis::explain(vars, &columns, rows, max_solves)
+}
+
+/// Writes the solution for `write_solution_file` before it is presented.
+/// Failing to write is only a warning, as the solution is still worth
+/// returning.
+#[cfg(feature = "highs")]
+fn write_solution_file(
+    res: &mut MessageSolution,
+    status: HighsModelStatus,
+    path: &str,
+    columns: &[String],
+    rows: &[BuiltRow],
+) {
+    let rows: Vec<_> = rows.iter().map(|r| r.name.clone()).collect();
+    let text = solfile::write(res, status, columns, &rows);
+    if let Err(e) = std::fs::write(path, text) {
+        res.warnings
+            .push(format!("could not write the solution file {path}: {e}"));
+    }
+}
+
+/// Writes the model as built for `dump_model`.
+#[cfg(feature = "highs")]
+fn dump_model(
+    model: &highs::Model,
+    built: &Built,
+    format: dump::Format,
+    columns: &[String],
+) -> Result<dump::ModelDump, MessageError> {
+    if !built.quadratic.is_empty() {
+        return Err(MessageError::validation(
+            "dump_model does not support objective_quadratic",
+        ));
+    }
+    let lp =
+        raw::lp(model).ok_or_else(|| MessageError::solver("could not read the built model"))?;
+    let rows: Vec<_> = built.rows.iter().map(|r| r.name.clone()).collect();
+    Ok(dump::write(
+        &lp,
+        format,
+        columns,
+        &rows,
+        built.objective_offset,
+    ))
+}
+
+/// Opt-in results, captured before the problem is built.
+struct Extras {
+    sensitivity: bool,
+    explain: Option<(Vec<iis::Column>, usize)>, // with the maximum number of solves
+    pool: Option<(Vec<pool::IntegerColumn>, usize)>, // with the solution limit
+    breakdown: Option<Vec<(usize, f64)>>,       // variable index and factor per term
+    costs: Vec<(usize, f64)>,                   // variable index and cost, for the breakdown
+    solution_file: Option<String>,
+    threads: Option<u32>,
+    memory: Option<Memory>,
+    emphasis: Option<BTreeMap<&'static str, String>>,
+    dump: Option<(dump::Format, Vec<String>)>, // with the column names
+    priorities: bool,                          // whether a variable has a branch_priority
+}
+impl Extras {
+    fn new(input: &MessageProblem) -> Result<Self, MessageError> {
+        let explain = input.explain_infeasibility.then(|| {
+            let columns = input.variables.iter().map_into().collect();
+            let max_solves = input.explain_infeasibility_max_solves.unwrap_or(1000);
+            (columns, max_solves)
+        });
+        let pool = input
+            .solution_limit
+            .map(|limit| integer_columns(&input.variables).map(|c| (c, limit)))
+            .transpose()?;
+        // unknown variables are rejected when building the objective
+        let breakdown = input.objective_breakdown.then(|| {
+            let names = || input.variables.iter().map(|v| v.name.as_ref());
+            let terms = input.objective.iter();
+            let mut terms: Vec<_> = terms
+                .filter_map(|c| Some((c.position(names()).ok()?, c.factor())))
+                .collect();
+            // duplicates are rejected when building the objective, too
+            let _ = input.duplicate_policy.apply(&mut terms);
+            terms
+        });
+        let costs = input.variables.iter().enumerate();
+        let costs = costs.filter_map(|(i, v)| Some((i, v.cost?)));
+        Ok(Extras {
+            sensitivity: input.sensitivity,
+            explain,
+            pool,
+            costs: if breakdown.is_some() {
+                costs.collect()
+            } else {
+                vec![]
+            },
+            breakdown,
+            solution_file: input.write_solution_file.clone(),
+            threads: input.options.threads(),
+            memory: memory::check(input)?,
+            emphasis: input.options.emphasis.map(options::listed),
+            dump: input
+                .dump_model
+                .as_ref()
+                .map(|d| (d.format, column_names(input))),
+            priorities: input.variables.iter().any(|v| v.branch_priority.is_some()),
+        })
+    }
+}
+
+/// How variable values are reported, captured before the problem is built.
+struct Layout {
+    names: Vec<String>,
+    integer: Vec<bool>,
+    output: Output,
+    sparse_tolerance: Option<f64>,
+    non_finite: NonFinite,
+    integrality_tolerance: Option<f64>,
+    decimal_places: Option<u8>,
+    interior: bool, // the duals of LPs come from interior point without crossover
+    relaxed: bool,
+    feasibility: bool, // nothing is optimised
+}
+impl Layout {
+    fn new(input: &MessageProblem) -> Self {
+        Layout {
+            names: input.variables.iter().map(|v| v.name.to_string()).collect(),
+            integer: input
+                .variables
+                .iter()
+                .map(VariableDef::is_integer)
+                .collect(),
+            output: input.output,
+            sparse_tolerance: input
+                .sparse_output
+                .then(|| input.zero_tolerance.unwrap_or(1e-9)),
+            non_finite: input.non_finite,
+            integrality_tolerance: input.options.integrality_tolerance,
+            decimal_places: input.options.decimal_places,
+            interior: input.options.solver_algorithm == Some(Algorithm::Ipm)
+                && input.options.crossover == Some(false),
+            relaxed: input.relax_integrality,
+            feasibility: !input.has_objective(),
+        }
+    }
+
+    /// Rounds integer variables and lays out the values as requested. Values
+    /// of integer variables that are not integral within `tolerance` are
+    /// kept as they are and reported in `warnings`. Fails on non-finite
+    /// values unless they should be reported as strings.
+    fn present(self, res: &mut MessageSolution, tolerance: f64) -> Result<(), MessageError> {
+        if self.interior && res.duals.is_some() {
+            res.interior = Some(true);
+        }
+        res.relaxed = self.relaxed.then_some(true);
+        res.feasibility = self.feasibility.then_some(true);
+        for (i, value) in res.values.iter_mut().enumerate() {
+            let Value::Continuous(v) = *value else {
+                continue;
+            };
+            if !v.is_finite() {
+                if self.non_finite == NonFinite::Error {
+                    return Err(MessageError::solver(format!(
+                        "variable '{}' has non-finite value {v}",
+                        self.names[i]
+                    )));
+                }
+                *value = Value::NonFinite(if v.is_nan() {
+                    "nan"
+                } else if v > 0.0 {
+                    "inf"
+                } else {
+                    "-inf"
+                });
+            } else if self.integer[i] {
+                if let Some(n) = integral(v, tolerance) {
+                    *value = Value::Integer(n);
+                } else {
+                    res.warnings.push(format!(
+                        "integer variable '{}' has fractional value {v}",
+                        self.names[i]
+                    ));
+                    res.nonintegral_variables.push(self.names[i].clone());
+                }
+            }
+        }
+        if !res.values.is_empty() && self.integer.contains(&true) {
+            res.integral = Some(res.nonintegral_variables.is_empty());
+        }
+        if let Some(places) = self.decimal_places {
+            round_all(res, 10f64.powi(i32::from(places)));
+        }
+
+        if let Some(tolerance) = self.sparse_tolerance {
+            let values = std::mem::take(&mut res.values);
+            let nonzeros = values
+                .into_iter()
+                .enumerate()
+                .filter(|(_, value)| match value {
+                    Value::Integer(n) => *n != 0,
+                    Value::Continuous(v) => v.abs() > tolerance,
+                    Value::NonFinite(_) => true,
+                })
+                .map(|(index, value)| SparseValue {
+                    index,
+                    name: (self.output == Output::Named).then(|| self.names[index].clone()),
+                    value,
+                });
+            res.nonzeros = Some(nonzeros.collect());
+        } else if self.output == Output::Named {
+            let named = self.names.into_iter().zip(res.values.iter().copied());
+            res.named_values = Some(named.collect());
+        }
+        Ok(())
+    }
+}
+
+/// Rounds every reported number to multiples of `1 / scale`. Integer
+/// variables are already integral and keep their values.
+fn round_all(res: &mut MessageSolution, scale: f64) {
+    let round = |v: &mut f64| *v = round_to(*v, scale);
+    let round_vec = |v: &mut Vec<f64>| v.iter_mut().for_each(round);
+    for value in &mut res.values {
+        if let Value::Continuous(v) = value {
+            round(v);
+        }
+    }
+    if let Some(objective) = &mut res.objective {
+        round(objective);
+    }
+    [
+        &mut res.duals,
+        &mut res.reduced_costs,
+        &mut res.activities,
+        &mut res.slacks,
+        &mut res.ray,
+        &mut res.stage_objectives,
+    ]
+    .into_iter()
+    .flatten()
+    .for_each(round_vec);
+    for range in [&mut res.cost_ranges, &mut res.rhs_ranges]
+        .into_iter()
+        .flatten()
+    {
+        range.iter_mut().flatten().for_each(round);
+    }
+    if let Some(breakdown) = &mut res.objective_breakdown {
+        [
+            &mut breakdown.terms,
+            &mut breakdown.quadratic,
+            &mut breakdown.piecewise,
+            &mut breakdown.abs,
+            &mut breakdown.costs,
+            &mut breakdown.blended,
+        ]
+        .into_iter()
+        .for_each(round_vec);
+        round(&mut breakdown.offset);
+    }
+    for solution in res.solutions.iter_mut().flatten() {
+        round_vec(&mut solution.values);
+        round(&mut solution.objective);
+    }
+    if let Some(bound) = &mut res.best_bound {
+        round(bound);
+    }
+}
+fn round_to(value: f64, scale: f64) -> f64 {
+    let scaled = value * scale;
+    if !scaled.is_finite() {
+        return value;
+    }
+    // adding zero turns -0.0 into 0.0
+    scaled.round() / scale + 0.0
+}
+
+/// Rounds `value` if it is within `tolerance` of an integer that `f64` can
+/// represent exactly.
+fn integral(value: f64, tolerance: f64) -> Option<i64> {
+    let rounded = value.round();
+    if (value - rounded).abs() > tolerance || rounded.abs() > 9_007_199_254_740_992.0 {
+        return None;
+    }
+    #[allow(clippy::cast_possible_truncation)] // in range, checked above
+    let n = rounded as i64;
+    Some(n)
+}
+
+/// Keys the reported duals and slacks by row name if any rows have names.
+fn add_row_names(res: &mut MessageSolution, rows: &[BuiltRow]) {
+    if rows.iter().all(|r| r.name.is_none()) {
+        return;
+    }
+    let named = |values: &Option<Vec<f64>>| {
+        let values = values.as_ref()?;
+        let named = rows.iter().zip(values);
+        Some(
+            named
+                .filter_map(|(r, &v)| Some((r.name.clone()?, v)))
+                .collect(),
+        )
+    };
+    res.named_duals = named(&res.duals);
+    res.named_slacks = named(&res.slacks);
+}
+
+/// Turns the column values of a solved model into a solution along with
+/// everything derived from them, except for what only HiGHS can tell.
+fn incumbent(x: &[f64], status: Status, built: &Built, extras: &Extras) -> MessageSolution {
+    let assignment: HashMap<_, _> = built.vars.iter().copied().zip(x.iter().copied()).collect();
+    let quadratic: Vec<_> = built
+        .quadratic
+        .iter()
+        .map(|&(a, b, f)| f * x[a] * x[b])
+        .collect();
+    let piecewise: Vec<_> = built
+        .piecewise
+        .iter()
+        .map(|segments| segments.iter().map(|&(col, f)| f * x[col]).sum::<f64>())
+        .collect();
+    let abs: Vec<_> = built.abs.iter().map(|&(col, f)| f * x[col]).collect();
+    let mut res = MessageSolution::incumbent(
+        status,
+        // auxiliary columns come after the input variables
+        x[..built.num_inputs].to_vec(),
+        IntoAffineExpression::eval_with(&built.objective, &assignment)
+            + quadratic.iter().sum::<f64>()
+            + piecewise.iter().sum::<f64>()
+            + abs.iter().sum::<f64>(),
+    );
+    let parts: Vec<_> = built
+        .blended
+        .iter()
+        .map(|(_, e)| IntoAffineExpression::eval_with(e, &assignment))
+        .collect();
+    let weighted: Vec<_> = built
+        .blended
+        .iter()
+        .zip(&parts)
+        .map(|((w, _), p)| w * p)
+        .collect();
+    if let Some(total) = res.objective.filter(|_| !parts.is_empty()) {
+        let first = total - weighted.iter().sum::<f64>();
+        res.stage_objectives = Some(iter::once(first).chain(parts).collect());
+    }
+    let activities: Vec<_> = built
+        .rows
+        .iter()
+        .map(|r| IntoAffineExpression::eval_with(&r.expr, &assignment))
+        .collect();
+    let slacks = built.rows.iter().zip(&activities);
+    res.slacks = Some(slacks.map(|(r, &a)| r.slack(a)).collect());
+    res.activities = Some(activities);
+    if let Some(terms) = &extras.breakdown {
+        let contributions = terms.iter().map(|&(i, factor)| factor * x[i]);
+        res.objective_breakdown = Some(ObjectiveBreakdown {
+            terms: contributions.collect(),
+            quadratic,
+            piecewise,
+            abs,
+            costs: extras.costs.iter().map(|&(i, cost)| cost * x[i]).collect(),
+            // the offsets of blended objectives are part of their values
+            offset: built.objective_offset
+                - built
+                    .blended
+                    .iter()
+                    .map(|(w, e)| w * e.constant())
+                    .sum::<f64>(),
+            blended: weighted,
+        });
+    }
+    res
+}
+
+/// Adds duals, reduced costs, the basis, and optionally ranging to the
+/// solution of a pure LP solved to optimality. Auxiliary columns and rows
+/// are left out.
+#[cfg(feature = "highs")]
+fn add_dual_details(
+    res: &mut MessageSolution,
+    solved: &mut highs::SolvedModel,
+    sol: &highs::Solution,
+    built: &Built,
+    sensitivity: bool,
+) -> Result<(), MessageError> {
+    let (num_cols, num_rows) = (sol.columns().len(), sol.rows().len());
+    let (vars, rows) = (built.num_inputs, built.rows.len());
+    res.duals = Some(sol.dual_rows()[..rows].to_vec());
+    res.reduced_costs = Some(sol.dual_columns()[..vars].to_vec());
+    if let Some((col_status, row_status)) = raw::basis(solved, num_cols, num_rows) {
+        res.basis = Some(Basis {
+            columns: col_status[..vars]
+                .iter()
+                .map(|&s| s.try_into())
+                .collect::<Result<_, _>>()?,
+            rows: row_status[..rows]
+                .iter()
+                .map(|&s| s.try_into())
+                .collect::<Result<_, _>>()?,
+        });
+    }
+    if sensitivity && let Some((mut costs, mut rhs)) = raw::ranging(solved, num_cols, num_rows) {
+        costs.truncate(vars);
+        rhs.truncate(rows);
+        res.cost_ranges = Some(costs);
+        res.rhs_ranges = Some(rhs);
+    }
+    Ok(())
+}
+
+/// Collects the integer variables for the solution pool, which can only
+/// exclude values of bounded variables.
+fn integer_columns(variables: &[VariableDef]) -> Result<Vec<pool::IntegerColumn>, MessageError> {
+    variables
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| v.is_integer())
+        .map(|(index, v)| match (v.min, v.max) {
+            (Some(min), Some(max)) if min.is_finite() && max.is_finite() => {
+                Ok(pool::IntegerColumn {
+                    index,
+                    // semi-integer variables can also be zero
+                    min: if v.semicontinuous { min.min(0.0) } else { min }.ceil(),
+                    max: max.floor(),
+                })
+            }
+            _ => Err(MessageError::validation(format!(
+                "solution_limit requires bounds on integer variable '{}'",
+                v.name
+            ))),
+        })
+        .collect()
+}
+
+/// Frees a string allocated by [`solve`]. It is not called `free`, since that
+/// would replace the `free` of the C library in every executable that links
+/// this crate, like the command line interface and the tests.
+///
+/// # Safety
+/// This may only be called for pointers returned from [`solve`], and it may
+/// only be done once per pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn free_string(s: *mut c_char) {
+    if s.is_null() {
+        return;
+    }
+    unsafe {
+        let _ = CString::from_raw(s);
+    }
+}
+
+/// Frees a buffer allocated by [`solve2`].
+///
+/// # Safety
+/// This may only be called for pointers returned from [`solve2`], with the
+/// length that was written to `out_len`, and it may only be done once per
+/// pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn free_buffer(buffer: *mut u8, len: usize) {
+    unsafe { free_bytes(buffer, len) }
+}
+
+/// Frees a buffer allocated by [`solve_msgpack`].
+///
+/// # Safety
+/// This may only be called for pointers returned from [`solve_msgpack`],
+/// with the length that was written to `out_len`, and it may only be done
+/// once per pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn free_msgpack(buffer: *mut u8, len: usize) {
+    unsafe { free_bytes(buffer, len) }
+}
+
+/// Frees a buffer allocated by [`solve_cbor`].
+///
+/// # Safety
+/// See [`free_msgpack`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn free_cbor(buffer: *mut u8, len: usize) {
+    unsafe { free_bytes(buffer, len) }
+}
+
+/// Frees a buffer allocated by [`solve_proto`].
+///
+/// # Safety
+/// See [`free_msgpack`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn free_proto(buffer: *mut u8, len: usize) {
+    unsafe { free_bytes(buffer, len) }
+}
+
+/// # Safety
+/// See [`free_msgpack`].
+unsafe fn free_bytes(buffer: *mut u8, len: usize) {
+    if buffer.is_null() {
+        return;
+    }
+    unsafe {
+        let _ = Box::from_raw(std::ptr::slice_from_raw_parts_mut(buffer, len));
+    }
+}
+
+/// Maps the HiGHS model status to the status reported to the caller.
+#[cfg(feature = "highs")]
+fn classify(solved: &highs::SolvedModel, has_incumbent: bool) -> Result<Status, MessageError> {
+    Ok(match solved.status() {
+        HighsModelStatus::Infeasible | HighsModelStatus::UnboundedOrInfeasible => {
+            Status::Infeasible
+        }
+        HighsModelStatus::Unbounded => Status::Unbounded,
+        HighsModelStatus::Optimal if has_incumbent => Status::Optimal,
+        HighsModelStatus::ReachedTimeLimit => Status::TimeLimit,
+        HighsModelStatus::ReachedInterrupt => Status::Interrupted,
+        // HiGHS reports node limits as solution limits
+        HighsModelStatus::ReachedSolutionLimit
+            if raw::int64_info(solved, c"mip_node_count")
+                .zip(raw::int_option(solved, c"mip_max_nodes"))
+                .is_some_and(|(nodes, max)| nodes >= i64::from(max)) =>
+        {
+            Status::NodeLimit
+        }
+        // stopped at objective_bound or objective_target, not proven optimal
+        HighsModelStatus::ObjectiveBound
+        | HighsModelStatus::ObjectiveTarget
+        | HighsModelStatus::ReachedSolutionLimit
+        | HighsModelStatus::ReachedIterationLimit
+        | HighsModelStatus::ReachedMemoryLimit
+            if has_incumbent =>
+        {
+            Status::Feasible
+        }
+        HighsModelStatus::ReachedIterationLimit => Status::IterationLimit,
+        HighsModelStatus::ReachedSolutionLimit => Status::SolutionLimit,
+        other => return Err(MessageError::solver(format!("{other:?}"))),
+    })
+}
+
+fn ms(from: Instant, to: Instant) -> f64 {
+    (to - from).as_secs_f64() * 1000.0
+}
+
+fn to_expr(
+    vars: &Vec<(&str, Variable)>,
+    off: f64,
+    coeff: &[CoeffVar],
+    policy: DuplicatePolicy,
+    cleanup: &Cleanup,
+) -> Result<Expression, MessageError> {
+    let terms = resolve(|| vars.iter().map(|(name, _)| *name), coeff, policy)?;
+    let terms = terms.into_iter().filter(|&(_, f)| cleanup.keep(f));
+    let linear: Expression = terms.map(|(i, f)| f * vars[i].1).sum();
+    Ok(off.into_expression() + linear)
+}
+/// Resolves terms to variable indices and factors.
+fn resolve<'a, I: Iterator<Item = &'a str>>(
+    names: impl Fn() -> I,
+    coeff: &[CoeffVar],
+    policy: DuplicatePolicy,
+) -> Result<Vec<(usize, f64)>, MessageError> {
+    let mut terms = coeff
+        .iter()
+        .map(|c| Ok((c.position(names())?, c.factor())))
+        .collect::<Result<Vec<_>, MessageError>>()?;
+    policy.apply(&mut terms).map_err(|i| {
+        let name = names().nth(i).unwrap_or_default();
+        MessageError::validation(format!("duplicate variable '{name}'"))
+    })?;
+    Ok(terms)
 }
 pub trait MapIntoExt: Iterator {
     /// Performs `.map(|x| x.into())`
@@ -0,0 +1,660 @@
+//! Reader for problems in the CPLEX LP format, which maps them onto a
+//! [`MessageProblem`] like [`crate::parse_mps`].
+
+use crate::{CoeffVar, Direction, MessageError, MessageProblem, Op, RangedConstraint, VariableDef};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// Reads a problem in LP format with an objective and the sections
+/// `Subject To`, `Bounds`, `Generals`, `Binaries` and `Semi-Continuous`.
+/// Rows become ranged constraints in their order, named by their labels.
+///
+/// # Errors
+/// Fails with a parse error that names the line and column of anything it
+/// cannot read.
+pub fn parse_lp(text: &str) -> Result<MessageProblem<'static>, MessageError> {
+    let mut sections: Vec<(Section, Vec<Token>)> = vec![];
+    for (i, line) in text.lines().enumerate() {
+        let number = i + 1;
+        // comments start with a backslash
+        let line = line.split_once('\\').map_or(line, |(code, _)| code);
+        let start = match keyword(line) {
+            Some((Section::End, _)) => break,
+            Some((section, len)) => {
+                sections.push((section, vec![]));
+                len
+            }
+            None if !sections.is_empty() || line.trim().is_empty() => 0,
+            None => {
+                let column = line.len() - line.trim_start().len() + 1;
+                return Err(error(number, column, "expected Minimize or Maximize"));
+            }
+        };
+        if let Some((_, tokens)) = sections.last_mut() {
+            tokenize(line, number, start, tokens)?;
+        }
+    }
+
+    let mut reader = Reader::default();
+    for (section, tokens) in sections {
+        let mut parser = Parser { tokens, pos: 0 };
+        match section {
+            Section::Objective(maximise) => reader.objective(&mut parser, maximise)?,
+            Section::Constraints => {
+                while !parser.done() {
+                    reader.constraint(&mut parser)?;
+                }
+            }
+            Section::Bounds => {
+                while !parser.done() {
+                    reader.bound(&mut parser)?;
+                }
+            }
+            Section::Generals | Section::Binaries | Section::Semis => {
+                while let Some(token) = parser.next() {
+                    if !matches!(token.kind, Kind::Name) {
+                        return Err(token.error("expected a variable"));
+                    }
+                    let v = reader.variable(&token.text);
+                    match section {
+                        Section::Generals => v.integer = Some(true),
+                        Section::Binaries => {
+                            (v.min, v.max, v.integer) = (Some(0.0), Some(1.0), Some(true));
+                        }
+                        _ => v.semicontinuous = true,
+                    }
+                }
+            }
+            Section::End => unreachable!("stops reading"),
+        }
+    }
+    Ok(reader.finish())
+}
+
+#[derive(Clone, Copy)]
+enum Section {
+    Objective(bool), // maximise
+    Constraints,
+    Bounds,
+    Generals,
+    Binaries,
+    Semis,
+    End,
+}
+
+/// Finds the section that `line` starts, and where its keyword ends.
+fn keyword(line: &str) -> Option<(Section, usize)> {
+    const KEYWORDS: [(&str, Section); 21] = [
+        ("minimize", Section::Objective(false)),
+        ("minimise", Section::Objective(false)),
+        ("minimum", Section::Objective(false)),
+        ("min", Section::Objective(false)),
+        ("maximize", Section::Objective(true)),
+        ("maximise", Section::Objective(true)),
+        ("maximum", Section::Objective(true)),
+        ("max", Section::Objective(true)),
+        ("subject to", Section::Constraints),
+        ("such that", Section::Constraints),
+        ("s.t.", Section::Constraints),
+        ("st", Section::Constraints),
+        ("bounds", Section::Bounds),
+        ("bound", Section::Bounds),
+        ("generals", Section::Generals),
+        ("general", Section::Generals),
+        ("gen", Section::Generals),
+        ("binaries", Section::Binaries),
+        ("binary", Section::Binaries),
+        ("semi-continuous", Section::Semis),
+        ("end", Section::End),
+    ];
+    let trimmed = line.trim_start();
+    let lead = line.len() - trimmed.len();
+    let lower = trimmed.to_ascii_lowercase();
+    KEYWORDS.iter().find_map(|&(word, section)| {
+        let rest = lower.strip_prefix(word)?;
+        rest.chars()
+            .next()
+            .is_none_or(char::is_whitespace)
+            .then_some((section, lead + word.len()))
+    })
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Number(f64),
+    Name,
+    Plus,
+    Minus,
+    Colon,
+    Compare(Op),
+}
+struct Token {
+    kind: Kind,
+    line: usize,
+    column: usize, // in characters, counted from 1
+    text: String,
+}
+impl Token {
+    fn error(&self, message: &str) -> MessageError {
+        error(
+            self.line,
+            self.column,
+            &format!("{message}, found '{}'", self.text),
+        )
+    }
+    /// Reads numbers including the infinities, which look like names.
+    fn number(&self) -> Option<f64> {
+        match self.kind {
+            Kind::Number(value) => Some(value),
+            Kind::Name if matches!(self.text.to_ascii_lowercase().as_str(), "inf" | "infinity") => {
+                Some(f64::INFINITY)
+            }
+            _ => None,
+        }
+    }
+}
+
+fn error(line: usize, column: usize, message: &str) -> MessageError {
+    MessageError::parse(format!("line {line}, column {column}: {message}"))
+        .on_line(line)
+        .in_column(column)
+}
+
+/// Splits `line` from byte `start` on into tokens.
+fn tokenize(
+    line: &str,
+    number: usize,
+    start: usize,
+    tokens: &mut Vec<Token>,
+) -> Result<(), MessageError> {
+    let first = line[..start].chars().count();
+    let chars: Vec<char> = line[start..].chars().collect();
+    let name_char = |c: char| c.is_alphanumeric() || "_.[]!\"#$%&()/,;?@'{}|~".contains(c);
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let begin = i;
+        i += 1;
+        let kind = match c {
+            _ if c.is_whitespace() => continue,
+            '+' => Kind::Plus,
+            '-' => Kind::Minus,
+            ':' => Kind::Colon,
+            '<' | '>' | '=' => {
+                // also =< and =>, and < and > without the equal sign
+                let second = chars.get(i).copied();
+                let op = match (c, second) {
+                    ('<', _) | ('=', Some('<')) => Op::Leq,
+                    ('>', _) | ('=', Some('>')) => Op::Geq,
+                    _ => Op::Eq,
+                };
+                if second == Some('=') || c == '=' && matches!(second, Some('<' | '>')) {
+                    i += 1;
+                }
+                Kind::Compare(op)
+            }
+            '0'..='9' | '.' => {
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                // an exponent needs a digit, possibly after a sign
+                let digit_at = |j: usize| chars.get(j).is_some_and(char::is_ascii_digit);
+                if matches!(chars.get(i), Some('e' | 'E')) {
+                    let sign = usize::from(matches!(chars.get(i + 1), Some('+' | '-')));
+                    if digit_at(i + 1 + sign) {
+                        i += 1 + sign;
+                        while digit_at(i) {
+                            i += 1;
+                        }
+                    }
+                }
+                let text: String = chars[begin..i].iter().collect();
+                let Ok(value) = text.parse() else {
+                    let message = format!("'{text}' is not a number");
+                    return Err(error(number, first + begin + 1, &message));
+                };
+                Kind::Number(value)
+            }
+            '[' | '^' => {
+                let message = "quadratic terms are not supported";
+                return Err(error(number, first + begin + 1, message));
+            }
+            _ if name_char(c) => {
+                while i < chars.len() && name_char(chars[i]) {
+                    i += 1;
+                }
+                Kind::Name
+            }
+            _ => {
+                let message = format!("unexpected character '{c}'");
+                return Err(error(number, first + begin + 1, &message));
+            }
+        };
+        tokens.push(Token {
+            kind,
+            line: number,
+            column: first + begin + 1,
+            text: chars[begin..i].iter().collect(),
+        });
+    }
+    Ok(())
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+impl Parser {
+    fn done(&self) -> bool {
+        self.pos >= self.tokens.len()
+    }
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+    fn next(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.pos);
+        self.pos += 1;
+        token
+    }
+    /// The error for a missing token, at the end of the last one.
+    fn end_error(&self, message: &str) -> MessageError {
+        let last = self
+            .tokens
+            .last()
+            .expect("sections with statements have tokens");
+        let column = last.column + last.text.chars().count();
+        error(last.line, column, &format!("{message}, found the end"))
+    }
+    fn end_error_or_token(&self, message: &str) -> MessageError {
+        match self.peek() {
+            Some(token) => token.error(message),
+            None => self.end_error(message),
+        }
+    }
+    fn expect(&mut self, message: &str) -> Result<&Token, MessageError> {
+        if self.done() {
+            return Err(self.end_error(message));
+        }
+        Ok(self.next().expect("not done"))
+    }
+
+    /// Skips a label `name:` and returns it.
+    fn label(&mut self) -> Option<String> {
+        let [name, colon] = self.tokens.get(self.pos..self.pos + 2)? else {
+            return None;
+        };
+        if !matches!((name.kind, colon.kind), (Kind::Name, Kind::Colon)) {
+            return None;
+        }
+        self.pos += 2;
+        Some(name.text.clone())
+    }
+
+    /// Reads `term { (+|-) term }` with terms like `2 x`, `- x` and `3`.
+    fn linear(&mut self) -> Result<Linear, MessageError> {
+        let mut linear = Linear::default();
+        loop {
+            let mut sign = 1.0;
+            while let Some(Kind::Plus | Kind::Minus) = self.peek().map(|t| t.kind) {
+                if matches!(self.next().map(|t| t.kind), Some(Kind::Minus)) {
+                    sign = -sign;
+                }
+            }
+            let token = self.expect("expected a number or a variable")?;
+            let (factor, name) = match (token.number(), token.kind) {
+                (Some(value), Kind::Number(_)) => {
+                    let factor = sign * value;
+                    match self.peek() {
+                        Some(t) if matches!(t.kind, Kind::Name) && t.number().is_none() => {
+                            let name = t.text.clone();
+                            self.pos += 1;
+                            (factor, Some(name))
+                        }
+                        _ => (factor, None),
+                    }
+                }
+                (Some(value), _) => (sign * value, None),
+                (None, Kind::Name) => (sign, Some(token.text.clone())),
+                _ => return Err(token.error("expected a number or a variable")),
+            };
+            match name {
+                Some(name) => linear.terms.push((name, factor)),
+                None => linear.constant += factor,
+            }
+            if !matches!(self.peek().map(|t| t.kind), Some(Kind::Plus | Kind::Minus)) {
+                return Ok(linear);
+            }
+        }
+    }
+    fn compare(&mut self) -> Option<Op> {
+        match self.peek().map(|t| t.kind) {
+            Some(Kind::Compare(op)) => {
+                self.pos += 1;
+                Some(op)
+            }
+            _ => None,
+        }
+    }
+    /// Reads a signed constant.
+    fn constant(&mut self) -> Result<f64, MessageError> {
+        let mut sign = 1.0;
+        while let Some(Kind::Plus | Kind::Minus) = self.peek().map(|t| t.kind) {
+            if matches!(self.next().map(|t| t.kind), Some(Kind::Minus)) {
+                sign = -sign;
+            }
+        }
+        let token = self.expect("expected a number")?;
+        token
+            .number()
+            .map(|value| sign * value)
+            .ok_or_else(|| token.error("expected a number"))
+    }
+}
+
+#[derive(Default)]
+struct Linear {
+    terms: Vec<(String, f64)>,
+    constant: f64,
+}
+
+#[derive(Default)]
+struct Reader {
+    maximise: bool,
+    objective: Vec<CoeffVar<'static>>,
+    constant: f64,
+    constraints: Vec<RangedConstraint<'static>>,
+    columns: HashMap<String, usize>,
+    variables: Vec<VariableDef<'static>>,
+}
+impl Reader {
+    /// Declares a variable on first use, with the default bounds `[0, inf)`.
+    fn variable(&mut self, name: &str) -> &mut VariableDef<'static> {
+        let next = self.variables.len();
+        let col = *self.columns.entry(name.to_owned()).or_insert(next);
+        if col == next {
+            self.variables.push(VariableDef {
+                name: name.to_owned().into(),
+                min: Some(0.0),
+                max: None,
+                initial: None,
+                integer: Some(false),
+                binary: false,
+                semicontinuous: false,
+                fixed: None,
+                cost: None,
+                branch_priority: None,
+            });
+        }
+        &mut self.variables[col]
+    }
+    fn terms(&mut self, linear: Linear) -> Vec<CoeffVar<'static>> {
+        linear
+            .terms
+            .into_iter()
+            .map(|(name, factor)| {
+                self.variable(&name);
+                CoeffVar::Named {
+                    name: name.into(),
+                    factor,
+                }
+            })
+            .collect()
+    }
+
+    fn objective(&mut self, parser: &mut Parser, maximise: bool) -> Result<(), MessageError> {
+        self.maximise = maximise;
+        parser.label();
+        if parser.done() {
+            return Ok(());
+        }
+        let linear = parser.linear()?;
+        if let Some(token) = parser.peek() {
+            return Err(token.error("expected + or -"));
+        }
+        self.constant += linear.constant;
+        let terms = self.terms(linear);
+        self.objective.extend(terms);
+        Ok(())
+    }
+
+    /// Reads `[name:] expr op constant` or `constant op expr [op constant]`.
+    fn constraint(&mut self, parser: &mut Parser) -> Result<(), MessageError> {
+        let name = parser.label();
+        let left = parser.linear()?;
+        let Some(op) = parser.compare() else {
+            return Err(parser.end_error_or_token("expected <=, >= or ="));
+        };
+        let (linear, lower, upper) = if left.terms.is_empty() {
+            let mut middle = parser.linear()?;
+            let (mut lower, mut upper) = range(flip(op), left.constant);
+            if let Some(op) = parser.compare() {
+                let (l, u) = range(op, parser.constant()?);
+                (lower, upper) = (lower.max(l), upper.min(u));
+            }
+            // constants in the middle move to both sides
+            let constant = std::mem::take(&mut middle.constant);
+            (middle, lower - constant, upper - constant)
+        } else {
+            let rhs = parser.constant()? - left.constant;
+            let (lower, upper) = range(op, rhs);
+            (left, lower, upper)
+        };
+        let terms = self.terms(linear);
+        self.constraints.push(RangedConstraint {
+            terms,
+            lower,
+            upper,
+            name: name.map(Cow::Owned),
+        });
+        Ok(())
+    }
+
+    /// Reads `x op constant`, `constant op x [op constant]` or `x free`.
+    fn bound(&mut self, parser: &mut Parser) -> Result<(), MessageError> {
+        let (mut lower, mut upper) = (None, None);
+        let mut set = |op: Op, value: f64| match op {
+            Op::Leq => upper = Some(value),
+            Op::Geq => lower = Some(value),
+            Op::Eq => (lower, upper) = (Some(value), Some(value)),
+        };
+        let starts_with_name = parser
+            .peek()
+            .is_some_and(|t| matches!(t.kind, Kind::Name) && t.number().is_none());
+        let name = if starts_with_name {
+            parser.next().expect("peeked").text.clone()
+        } else {
+            let value = parser.constant()?;
+            let Some(op) = parser.compare() else {
+                return Err(parser.end_error_or_token("expected <=, >= or ="));
+            };
+            set(flip(op), value);
+            let token = parser.expect("expected a variable")?;
+            if !matches!(token.kind, Kind::Name) || token.number().is_some() {
+                return Err(token.error("expected a variable"));
+            }
+            token.text.clone()
+        };
+        let free = parser
+            .peek()
+            .is_some_and(|t| matches!(t.kind, Kind::Name) && t.text.eq_ignore_ascii_case("free"));
+        if free {
+            parser.pos += 1;
+            (lower, upper) = (Some(f64::NEG_INFINITY), Some(f64::INFINITY));
+        } else if let Some(op) = parser.compare() {
+            set(op, parser.constant()?);
+        } else if starts_with_name {
+            return Err(parser.end_error_or_token("expected <=, >=, = or free"));
+        }
+        let v = self.variable(&name);
+        if let Some(max) = upper {
+            // a negative upper bound drops the default lower bound
+            if max < 0.0 && lower.is_none() && v.min == Some(0.0) {
+                v.min = None;
+            }
+            v.max = Some(max);
+        }
+        if let Some(min) = lower {
+            v.min = Some(min);
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> MessageProblem<'static> {
+        MessageProblem {
+            direction: Some(if self.maximise {
+                Direction::Max
+            } else {
+                Direction::Min
+            }),
+            variables: self.variables,
+            objective: self.objective,
+            objective_offset: self.constant,
+            ranged_constraints: self.constraints,
+            ..MessageProblem::default()
+        }
+    }
+}
+
+/// The operator with its sides swapped.
+fn flip(op: Op) -> Op {
+    match op {
+        Op::Leq => Op::Geq,
+        Op::Geq => Op::Leq,
+        Op::Eq => Op::Eq,
+    }
+}
+
+/// The range of `expr op value`.
+fn range(op: Op, value: f64) -> (f64, f64) {
+    match op {
+        Op::Leq => (f64::NEG_INFINITY, value),
+        Op::Geq => (value, f64::INFINITY),
+        Op::Eq => (value, value),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)] // the numbers are read, not computed
+mod tests {
+    use super::*;
+
+    fn terms(terms: &[CoeffVar]) -> Vec<(String, f64)> {
+        let terms = terms.iter().map(|term| match term {
+            CoeffVar::Named { name, factor } => (name.to_string(), *factor),
+            _ => unreachable!("the reader names its terms"),
+        });
+        terms.collect()
+    }
+
+    fn error(text: &str) -> String {
+        parse_lp(text).err().unwrap().message
+    }
+
+    #[test]
+    fn reads_every_section() {
+        let problem = parse_lp(
+            r"\ a comment
+Maximize
+ obj: 3 x + 2 y - z + 4
+Subject To
+ c1: x + y <= 4 \ another comment
+ c2: - x + z >= -2
+ 1 <= x - y <= 3
+ c4: x + 1 = 2
+Bounds
+ y <= 3
+ -1 <= z <= inf
+ w free
+ v <= -1
+Generals
+ y
+Binaries
+ b
+Semi-Continuous
+ z
+End
+ ignored",
+        )
+        .unwrap();
+        assert!(problem.direction == Some(Direction::Max));
+        assert_eq!(problem.objective_offset, 4.0);
+        let objective = terms(&problem.objective);
+        assert_eq!(
+            objective,
+            [("x".into(), 3.0), ("y".into(), 2.0), ("z".into(), -1.0)]
+        );
+
+        let rows = &problem.ranged_constraints;
+        let names: Vec<_> = rows.iter().map(|r| r.name.as_deref()).collect();
+        assert_eq!(names, [Some("c1"), Some("c2"), None, Some("c4")]);
+        let bounds: Vec<_> = rows.iter().map(|r| (r.lower, r.upper)).collect();
+        assert_eq!(
+            bounds,
+            [
+                (f64::NEG_INFINITY, 4.0),
+                (-2.0, f64::INFINITY),
+                (1.0, 3.0),
+                (1.0, 1.0)
+            ]
+        );
+        assert_eq!(terms(&rows[3].terms), [("x".into(), 1.0)]);
+
+        let variable = |name: &str| {
+            let variable = problem.variables.iter().find(|v| v.name == name);
+            let v = variable.unwrap();
+            (v.min, v.max, v.integer, v.semicontinuous)
+        };
+        assert_eq!(variable("x"), (Some(0.0), None, Some(false), false));
+        assert_eq!(variable("y"), (Some(0.0), Some(3.0), Some(true), false));
+        assert_eq!(
+            variable("z"),
+            (Some(-1.0), Some(f64::INFINITY), Some(false), true)
+        );
+        let free = (
+            Some(f64::NEG_INFINITY),
+            Some(f64::INFINITY),
+            Some(false),
+            false,
+        );
+        assert_eq!(variable("w"), free);
+        assert_eq!(variable("v"), (None, Some(-1.0), Some(false), false));
+        assert_eq!(variable("b"), (Some(0.0), Some(1.0), Some(true), false));
+    }
+
+    #[test]
+    fn errors_name_the_line_and_column() {
+        let e = parse_lp("min x\nst\n c: x +\n").err().unwrap();
+        assert_eq!((e.line, e.column), (Some(3), Some(8)));
+        assert_eq!(
+            e.message,
+            "line 3, column 8: expected a number or a variable, found the end"
+        );
+        let errors = [
+            ("x + y", "line 1, column 1: expected Minimize or Maximize"),
+            (
+                "min x\nst\n  x + y\n",
+                "line 3, column 8: expected <=, >= or =, found the end",
+            ),
+            (
+                "min x^2",
+                "line 1, column 6: quadratic terms are not supported",
+            ),
+            (
+                "min\n x + 1.2.3",
+                "line 2, column 6: '1.2.3' is not a number",
+            ),
+            (
+                "min x\nbounds\n x\n",
+                "line 3, column 3: expected <=, >=, = or free, found the end",
+            ),
+            (
+                "min x\ngenerals\n 3\n",
+                "line 3, column 2: expected a variable, found '3'",
+            ),
+            ("min x y", "line 1, column 7: expected + or -, found 'y'"),
+        ];
+        for (text, message) in errors {
+            assert_eq!(error(text), message, "{text}");
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! Estimate of the memory that solving a problem takes, so that problems
+//! which would exhaust the memory of the host are rejected before they are
+//! built. HiGHS has no memory limit of its own.
+
+use crate::{Memory, MessageError, MessageProblem};
+
+// rough upper bounds of what HiGHS keeps per column, row and nonzero while
+// solving, including the copies of presolve and the factorization
+const COLUMN_BYTES: u64 = 256;
+const ROW_BYTES: u64 = 256;
+const NONZERO_BYTES: u64 = 96;
+const FORMULA: &str = "256 * columns + 256 * rows + 96 * nonzeros";
+
+/// Estimates the memory of a prepared problem if it has a memory limit, and
+/// fails if the estimate exceeds it.
+pub fn check(input: &MessageProblem) -> Result<Option<Memory>, MessageError> {
+    let Some(limit_mb) = input.options.memory_limit_mb else {
+        return Ok(None);
+    };
+    let (columns, rows, nonzeros) = count(input);
+    let estimated_bytes = COLUMN_BYTES
+        .saturating_mul(columns)
+        .saturating_add(ROW_BYTES.saturating_mul(rows))
+        .saturating_add(NONZERO_BYTES.saturating_mul(nonzeros));
+    if estimated_bytes > limit_mb.saturating_mul(1 << 20) {
+        return Err(MessageError::resource_limit(format!(
+            "the problem needs about {} MB, more than memory_limit_mb of {limit_mb} \
+             ({columns} columns, {rows} rows and {nonzeros} nonzeros, estimated as {FORMULA} bytes)",
+            estimated_bytes.div_ceil(1 << 20)
+        )));
+    }
+    Ok(Some(Memory {
+        limit_mb,
+        columns,
+        rows,
+        nonzeros,
+        estimated_bytes,
+        formula: FORMULA,
+        peak_bytes: None,
+    }))
+}
+
+/// Counts the columns, rows and nonzeros that the problem has at least.
+/// Auxiliary columns and rows, like those of piecewise terms, are left out.
+fn count(input: &MessageProblem) -> (u64, u64, u64) {
+    let mut rows = 0;
+    let mut nonzeros = 0;
+    let mut add = |len: usize| {
+        rows += 1;
+        nonzeros += len as u64;
+    };
+    let term_lists = [
+        &input.constraints,
+        &input.equalities,
+        &input.geq_constraints,
+    ];
+    term_lists.into_iter().flatten().for_each(|t| add(t.len()));
+    input.rows.iter().for_each(|r| add(r.terms.len()));
+    input
+        .ranged_constraints
+        .iter()
+        .for_each(|r| add(r.terms.len()));
+    // the indicator variable is in the row, too
+    input.indicators.iter().for_each(|r| add(r.terms.len() + 1));
+    for row in &input.dense_rows {
+        add(row.iter().filter(|&&v| v != 0.0).count());
+    }
+    if let Some(matrix) = &input.matrix {
+        rows += matrix.ops.len() as u64;
+        nonzeros += matrix.values.len() as u64;
+    }
+    nonzeros += input.objective_quadratic.len() as u64;
+    (input.variables.len() as u64, rows, nonzeros)
+}
+
+/// Adds the most memory that the process has used so far to an estimate.
+pub fn with_peak(mut memory: Memory) -> Memory {
+    memory.peak_bytes = peak_bytes();
+    memory
+}
+
+/// The peak resident set size of the process, which is only known on Linux.
+fn peak_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find_map(|l| l.strip_prefix("VmHWM:"))?;
+    let kb: u64 = line.trim().strip_suffix("kB")?.trim().parse().ok()?;
+    Some(kb * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(problem: &str) -> Result<Option<Memory>, MessageError> {
+        super::check(&serde_json::from_str(problem).unwrap())
+    }
+
+    #[test]
+    fn counts_columns_rows_and_nonzeros() {
+        let problem = r#"{
+            "variables": [{"name": "x"}, {"name": "y"}, {"name": "b", "binary": true}],
+            "objective": [],
+            "constraints": [[{"name": "x", "factor": 1}, {"name": "y", "factor": 1}]],
+            "rows": [{"terms": [{"name": "x", "factor": 1}], "op": "<=", "rhs": 1}],
+            "indicators": [{"variable": "b", "terms": [{"name": "y", "factor": 1}], "op": "<=", "rhs": 0}],
+            "dense_rows": [[1, 0, 2]],
+            "verbose": false,
+            "options": {"memory_limit_mb": 1}
+        }"#;
+        let memory = check(problem).unwrap().unwrap();
+        assert_eq!((memory.columns, memory.rows, memory.nonzeros), (3, 4, 7));
+        assert_eq!(memory.estimated_bytes, 3 * 256 + 4 * 256 + 7 * 96);
+        assert_eq!(memory.peak_bytes, None);
+        assert!(with_peak(memory).peak_bytes.is_some_and(|peak| peak > 0));
+
+        let problem = r#"{"variables": [], "objective": [], "verbose": false}"#;
+        assert!(check(problem).unwrap().is_none());
+    }
+
+    #[test]
+    fn rejects_problems_over_the_limit() {
+        let variables: Vec<_> = (0..5000)
+            .map(|i| format!(r#"{{"name": "x{i}"}}"#))
+            .collect();
+        let problem = format!(
+            r#"{{"variables": [{}], "objective": [], "verbose": false,
+            "options": {{"memory_limit_mb": 1}}}}"#,
+            variables.join(",")
+        );
+        let error = check(&problem).err().unwrap();
+        assert_eq!(error.kind, crate::ErrorKind::ResourceLimit);
+        assert_eq!(
+            error.message,
+            "the problem needs about 2 MB, more than memory_limit_mb of 1 (5000 columns, 0 rows \
+             and 0 nonzeros, estimated as 256 * columns + 256 * rows + 96 * nonzeros bytes)"
+        );
+    }
+}
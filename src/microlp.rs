@@ -0,0 +1,165 @@
+//! The pure-Rust backend of `good_lp`, for hosts that cannot ship HiGHS. It
+//! solves linear and integer problems to optimality, and rejects the parts
+//! of the protocol that only HiGHS can express or report. Solutions have
+//! values, the objective, activities and slacks, but no duals, basis or
+//! bounds of the search.
+
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+use crate::wasm::Instant;
+use crate::{
+    BuiltRow, Coefficients, MessageError, MessageProblem, MessageSolution, Options, Report, Stats,
+    Status, VariableDef, add_row_names, incumbent, memory, ms,
+};
+use good_lp::solvers::microlp::MicroLpProblem;
+use good_lp::variable::UnsolvedProblem;
+use good_lp::{ResolutionError, Solution, SolverModel, constraint, microlp};
+#[cfg(not(all(feature = "wasm", target_arch = "wasm32")))]
+use std::time::Instant;
+
+/// Rejects the parts of a problem that microlp cannot solve or report.
+pub fn check(input: &MessageProblem) -> Result<(), MessageError> {
+    let unsupported = [
+        ("objectives", !input.objectives.is_empty() && !input.blend),
+        ("objective_quadratic", !input.objective_quadratic.is_empty()),
+        ("piecewise", !input.piecewise.is_empty()),
+        ("abs_terms", !input.abs_terms.is_empty()),
+        ("max_of", !input.max_of.is_empty()),
+        ("min_of", !input.min_of.is_empty()),
+        ("sos", !input.sos.is_empty()),
+        ("indicators", !input.indicators.is_empty()),
+        (
+            "semicontinuous",
+            input.variables.iter().any(|v| v.semicontinuous),
+        ),
+        ("explain_infeasibility", input.explain_infeasibility),
+        ("sensitivity", input.sensitivity),
+        ("solution_limit", input.solution_limit.is_some()),
+        ("dump_model", input.dump_model.is_some()),
+        ("write_solution_file", input.write_solution_file.is_some()),
+        ("capture_log", input.options.capture_log),
+    ];
+    match unsupported.iter().find(|(_, used)| *used) {
+        Some((key, _)) => Err(MessageError::validation(format!(
+            "{key} is not supported by the solver microlp"
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Warns about the settings that only HiGHS reads.
+pub fn ignored(options: &Options, variables: &[VariableDef]) -> Option<String> {
+    let settings = [
+        (
+            "initial values",
+            variables.iter().any(|v| v.initial.is_some()),
+        ),
+        (
+            "branch_priority",
+            variables.iter().any(|v| v.branch_priority.is_some()),
+        ),
+        ("time_limit_seconds", options.time_limit_seconds.is_some()),
+        ("mip_gap", options.mip_gap.is_some()),
+        ("threads", options.threads.is_some()),
+        ("verbose", options.verbose == Some(true)),
+        ("seed", options.seed.is_some()),
+        ("presolve", options.presolve.is_some()),
+        ("solver_algorithm", options.solver_algorithm.is_some()),
+        ("crossover", options.crossover.is_some()),
+        (
+            "primal_feasibility_tolerance",
+            options.primal_feasibility_tolerance.is_some(),
+        ),
+        (
+            "dual_feasibility_tolerance",
+            options.dual_feasibility_tolerance.is_some(),
+        ),
+        ("node_limit", options.node_limit.is_some()),
+        (
+            "improving_solution_limit",
+            options.improving_solution_limit.is_some(),
+        ),
+        ("scaling", options.scaling.is_some()),
+        ("emphasis", options.emphasis.is_some()),
+        ("raw_options", !options.raw_options.is_empty()),
+    ];
+    let ignored: Vec<_> = settings
+        .iter()
+        .filter(|(_, set)| *set)
+        .map(|(setting, _)| *setting)
+        .collect();
+    (!ignored.is_empty()).then(|| {
+        format!(
+            "the solver microlp ignores {}, which only HiGHS supports",
+            ignored.join(", ")
+        )
+    })
+}
+
+/// Hands the objective and the rows to microlp, which takes ranged rows as
+/// one row per finite bound.
+pub fn model(problem: UnsolvedProblem, rows: &[BuiltRow]) -> MicroLpProblem {
+    let mut model = problem.using(microlp);
+    for row in rows {
+        if let Some(constraint) = row.constraint() {
+            model.add_constraint(constraint);
+            continue;
+        }
+        let (lower, upper) = row.bounds();
+        if lower.is_finite() {
+            model.add_constraint(constraint!(row.expr.clone() >= lower));
+        }
+        if upper.is_finite() {
+            model.add_constraint(constraint!(row.expr.clone() <= upper));
+        }
+    }
+    model
+}
+
+/// Solves a model of [`model`] and reports its solution like the HiGHS
+/// path does for the parts that both have.
+pub fn solve(model: MicroLpProblem, report: Report) -> Result<MessageSolution, MessageError> {
+    let Report {
+        built,
+        layout,
+        extras,
+        start_warnings,
+        parse_ms,
+        start,
+        ..
+    } = report;
+    let built_at = Instant::now();
+    let solved = model.solve();
+    let solved_at = Instant::now();
+    let mut res = match solved {
+        Ok(solution) => {
+            let x: Vec<_> = built.vars.iter().map(|&v| solution.value(v)).collect();
+            incumbent(&x, Status::Optimal, &built, &extras)
+        }
+        Err(ResolutionError::Infeasible) => MessageSolution::new(Status::Infeasible, vec![]),
+        Err(ResolutionError::Unbounded) => MessageSolution::new(Status::Unbounded, vec![]),
+        Err(e) => return Err(MessageError::solver(format!("microlp failed to run: {e}"))),
+    };
+    res.warnings.extend(start_warnings);
+    res.warnings.extend(built.option_warning);
+    add_row_names(&mut res, &built.rows);
+    res.stats = Some(Stats {
+        parse_ms,
+        build_ms: ms(start, built_at),
+        solve_ms: ms(built_at, solved_at),
+        simplex_iterations: None,
+        nodes: None,
+        presolve: None, // microlp has no presolve
+        dropped_coefficients: built.dropped_coefficients,
+        threads: None,
+        seed: None,
+        primal_feasibility_tolerance: None,
+        dual_feasibility_tolerance: None,
+        memory: extras.memory.map(memory::with_peak),
+        coefficients: Coefficients::of(&built.rows),
+        emphasis: None,
+        branch_priorities_native: None,
+    });
+    let tolerance = layout.integrality_tolerance.unwrap_or(1e-6);
+    layout.present(&mut res, tolerance)?;
+    Ok(res)
+}
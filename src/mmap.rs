@@ -0,0 +1,235 @@
+//! Read-only memory maps of files, so that large problems are parsed without
+//! reading them into memory first. Only on 64-bit Unix, where `mmap` takes a
+//! 64-bit offset, and files are read elsewhere.
+
+use std::fs::File;
+use std::io;
+use std::ops::Deref;
+use std::path::Path;
+
+/// Contents of a file, which are unmapped when dropped.
+pub struct Mapping {
+    #[cfg(all(unix, target_pointer_width = "64"))]
+    map: sys::Map,
+    #[cfg(not(all(unix, target_pointer_width = "64")))]
+    bytes: Vec<u8>,
+}
+
+impl Mapping {
+    #[cfg(all(unix, target_pointer_width = "64"))]
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let len = usize::try_from(file.metadata()?.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "file too large"))?;
+        Ok(Mapping {
+            map: sys::Map::new(&file, len)?,
+        })
+    }
+    #[cfg(not(all(unix, target_pointer_width = "64")))]
+    pub fn open(path: &Path) -> io::Result<Self> {
+        use std::io::Read;
+        let mut bytes = vec![];
+        File::open(path)?.read_to_end(&mut bytes)?;
+        Ok(Mapping { bytes })
+    }
+}
+
+impl Deref for Mapping {
+    type Target = [u8];
+    #[cfg(all(unix, target_pointer_width = "64"))]
+    fn deref(&self) -> &[u8] {
+        self.map.bytes()
+    }
+    #[cfg(not(all(unix, target_pointer_width = "64")))]
+    fn deref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+#[cfg(all(unix, target_pointer_width = "64"))]
+mod sys {
+    use std::ffi::{c_int, c_void};
+    use std::fs::File;
+    use std::io;
+    use std::os::fd::AsRawFd;
+
+    // the same on Linux and the BSDs including macOS
+    const PROT_READ: c_int = 1;
+    const MAP_PRIVATE: c_int = 2;
+    const MAP_FAILED: *mut c_void = usize::MAX as *mut c_void;
+
+    unsafe extern "C" {
+        fn mmap(
+            addr: *mut c_void,
+            len: usize,
+            prot: c_int,
+            flags: c_int,
+            fd: c_int,
+            offset: i64,
+        ) -> *mut c_void;
+        fn munmap(addr: *mut c_void, len: usize) -> c_int;
+    }
+
+    pub struct Map {
+        ptr: *mut c_void,
+        len: usize,
+    }
+    impl Map {
+        pub fn new(file: &File, len: usize) -> io::Result<Self> {
+            // empty files cannot be mapped
+            if len == 0 {
+                return Ok(Map {
+                    ptr: std::ptr::null_mut(),
+                    len,
+                });
+            }
+            let fd = file.as_raw_fd();
+            let ptr = unsafe { mmap(std::ptr::null_mut(), len, PROT_READ, MAP_PRIVATE, fd, 0) };
+            if ptr == MAP_FAILED {
+                return Err(io::Error::last_os_error());
+            }
+            // the mapping stays valid after closing the file
+            Ok(Map { ptr, len })
+        }
+        pub fn bytes(&self) -> &[u8] {
+            if self.ptr.is_null() {
+                return &[];
+            }
+            unsafe { std::slice::from_raw_parts(self.ptr.cast(), self.len) }
+        }
+    }
+    impl Drop for Map {
+        fn drop(&mut self) {
+            if !self.ptr.is_null() {
+                unsafe { munmap(self.ptr, self.len) };
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CoeffVar, MessageProblem, free_string, solve_file_mmap};
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::borrow::Cow;
+    use std::cell::Cell;
+    use std::ffi::{CStr, CString};
+    use std::path::PathBuf;
+
+    thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    /// The system allocator, which counts the allocations of each thread so
+    /// that tests running in parallel do not disturb each other.
+    struct Counting;
+    unsafe impl GlobalAlloc for Counting {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            ALLOCATIONS.with(|n| n.set(n.get() + 1));
+            unsafe { System.alloc(layout) }
+        }
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            unsafe { System.dealloc(ptr, layout) }
+        }
+    }
+    #[global_allocator]
+    static COUNTING: Counting = Counting;
+
+    const N: usize = 20_000;
+
+    fn cost(i: usize) -> usize {
+        i % 7 + 1
+    }
+
+    /// A problem over `n` variables `x0`, `x1`, … that may each be taken once,
+    /// but at most half of them. The `x` of the names is written as an escape
+    /// if `escaped`.
+    fn problem(n: usize, escaped: bool) -> String {
+        let x = if escaped { r"\u0078" } else { "x" };
+        let list = |item: &dyn Fn(usize) -> String| (0..n).map(item).collect::<Vec<_>>().join(",");
+        let variables = list(&|i| format!(r#"{{"name": "{x}{i}", "min": 0, "max": 1}}"#));
+        let objective = list(&|i| format!(r#"{{"name": "{x}{i}", "factor": {}}}"#, cost(i)));
+        let terms = list(&|i| format!(r#"{{"name": "{x}{i}", "factor": 1}}"#));
+        format!(
+            r#"{{"direction": "max", "variables": [{variables}], "objective": [{objective}],
+            "rows": [{{"name": "half", "terms": [{terms}], "op": "<=", "rhs": {}}}],
+            "verbose": false}}"#,
+            n / 2
+        )
+    }
+
+    fn write_temp(name: &str, text: &str) -> PathBuf {
+        let file = format!("knorpelsolve-mmap-{name}-{}.json", std::process::id());
+        let path = std::env::temp_dir().join(file);
+        std::fs::write(&path, text).unwrap();
+        path
+    }
+
+    /// Parses a problem and counts the allocations of this thread meanwhile.
+    fn parse_counting(bytes: &[u8]) -> (MessageProblem<'_>, usize) {
+        let before = ALLOCATIONS.with(Cell::get);
+        let input = serde_json::from_slice(bytes).unwrap();
+        (input, ALLOCATIONS.with(Cell::get) - before)
+    }
+
+    /// The names of the variables and the names in their terms.
+    fn names<'a>(input: &'a MessageProblem) -> Vec<&'a Cow<'a, str>> {
+        let terms = input.objective.iter().chain(&input.rows[0].terms);
+        let terms = terms.filter_map(|term| match term {
+            CoeffVar::Named { name, .. } => Some(name),
+            _ => None,
+        });
+        input
+            .variables
+            .iter()
+            .map(|v| &v.name)
+            .chain(terms)
+            .collect()
+    }
+
+    #[test]
+    fn names_are_borrowed_from_the_mapping() {
+        let path = write_temp("names", &problem(N, false));
+        let mapping = Mapping::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let (input, allocations) = parse_counting(&mapping);
+        let borrowed = names(&input);
+        assert_eq!(borrowed.len(), 3 * N);
+        assert!(borrowed.iter().all(|name| matches!(name, Cow::Borrowed(_))));
+        assert!(matches!(input.rows[0].name, Some(Cow::Borrowed("half"))));
+
+        // the same names, but each has to be unescaped into its own string
+        let escaped = problem(N, true);
+        let (escaped_input, escaped_allocations) = parse_counting(escaped.as_bytes());
+        let owned = names(&escaped_input);
+        assert_eq!(owned, borrowed);
+        assert!(owned.iter().all(|name| matches!(name, Cow::Owned(_))));
+        assert!(
+            allocations + 3 * N <= escaped_allocations,
+            "{allocations} allocations when borrowing, {escaped_allocations} otherwise"
+        );
+    }
+
+    #[test]
+    fn solves_large_files() {
+        // names are looked up one by one, which makes larger problems slow
+        let n = N / 4;
+        let path = write_temp("solve", &problem(n, false));
+        let c_path = CString::new(path.to_str().unwrap()).unwrap();
+        let response = unsafe { solve_file_mmap(c_path.as_ptr()) };
+        let bytes = unsafe { CStr::from_ptr(response) }.to_bytes();
+        let solution: serde_json::Value = serde_json::from_slice(bytes).unwrap();
+        unsafe { free_string(response.cast_mut()) };
+        std::fs::remove_file(&path).unwrap();
+
+        let mut costs: Vec<_> = (0..n).map(cost).collect();
+        costs.sort_unstable();
+        let best: usize = costs.iter().rev().take(n / 2).sum();
+        assert_eq!(solution["status"], "optimal");
+        assert_eq!(solution["values"].as_array().unwrap().len(), n);
+        #[allow(clippy::cast_precision_loss)] // the sum is far below 2^52
+        let best = best as f64;
+        assert!((solution["objective"].as_f64().unwrap() - best).abs() < 1e-6);
+    }
+}
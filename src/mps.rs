@@ -0,0 +1,426 @@
+//! Reader for problems in free MPS format, which maps them onto a
+//! [`MessageProblem`].
+
+use crate::{CoeffVar, Direction, MessageError, MessageProblem, RangedConstraint, VariableDef};
+use std::collections::HashMap;
+
+/// Reads a problem in free MPS format with the sections `NAME`, `OBJSENSE`,
+/// `ROWS`, `COLUMNS`, `RHS`, `RANGES` and `BOUNDS`. The first `N` row is the
+/// objective, and further `N` rows are dropped. Rows become ranged
+/// constraints in their order, named like in the file.
+///
+/// # Errors
+/// Fails with a parse error that names the line of anything it cannot read.
+pub fn parse_mps(text: &str) -> Result<MessageProblem<'static>, MessageError> {
+    let mut reader = Reader::default();
+    let mut section = Section::Name;
+    for (i, line) in text.lines().enumerate() {
+        let number = i + 1;
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.is_empty() || line.starts_with('*') {
+            continue;
+        }
+        let at = |message: String| {
+            MessageError::parse(format!("line {number}: {message}")).on_line(number)
+        };
+        // only data lines are indented
+        if line.starts_with(char::is_whitespace) {
+            reader.line(section, &fields).map_err(at)?;
+            continue;
+        }
+        section = match fields[0] {
+            "NAME" => Section::Name,
+            "OBJSENSE" => Section::ObjSense,
+            "ROWS" => Section::Rows,
+            "COLUMNS" => Section::Columns,
+            "RHS" => Section::Rhs,
+            "RANGES" => Section::Ranges,
+            "BOUNDS" => Section::Bounds,
+            "ENDATA" => break,
+            other => {
+                return Err(MessageError::parse(format!(
+                    "section {other} on line {number} is not supported"
+                ))
+                .on_line(number));
+            }
+        };
+        if section == Section::ObjSense
+            && let Some(sense) = fields.get(1)
+        {
+            reader.sense(sense).map_err(at)?;
+        }
+    }
+    Ok(reader.finish())
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Name,
+    ObjSense,
+    Rows,
+    Columns,
+    Rhs,
+    Ranges,
+    Bounds,
+}
+
+#[derive(Clone, Copy)]
+enum RowRef {
+    Objective,
+    Free,
+    Constraint(usize),
+}
+
+#[derive(Default)]
+struct Reader {
+    maximise: bool,
+    has_objective: bool,
+    rows: HashMap<String, RowRef>,
+    constraints: Vec<Constraint>,
+    objective: Vec<CoeffVar<'static>>,
+    constant: f64,
+    columns: HashMap<String, usize>,
+    variables: Vec<VariableDef<'static>>,
+    integer: bool, // between integer markers
+}
+struct Constraint {
+    name: String,
+    sense: char, // `L`, `G` or `E`
+    terms: Vec<CoeffVar<'static>>,
+    rhs: f64,
+    range: Option<f64>,
+}
+
+impl Reader {
+    fn line(&mut self, section: Section, fields: &[&str]) -> Result<(), String> {
+        match section {
+            Section::Name => Ok(()),
+            Section::ObjSense => self.sense(fields[0]),
+            Section::Rows => self.row(fields),
+            Section::Columns => self.column(fields),
+            Section::Rhs => self.rhs(fields),
+            Section::Ranges => self.range(fields),
+            Section::Bounds => self.bound(fields),
+        }
+    }
+
+    fn sense(&mut self, sense: &str) -> Result<(), String> {
+        self.maximise = match sense {
+            "MAX" | "MAXIMIZE" => true,
+            "MIN" | "MINIMIZE" => false,
+            _ => return Err(format!("unknown objective sense '{sense}'")),
+        };
+        Ok(())
+    }
+
+    fn row(&mut self, fields: &[&str]) -> Result<(), String> {
+        let [kind, name] = fields else {
+            return Err("expected a row type and a name".to_owned());
+        };
+        let row = match kind.to_ascii_uppercase().as_str() {
+            "N" if self.has_objective => RowRef::Free,
+            "N" => {
+                self.has_objective = true;
+                RowRef::Objective
+            }
+            sense @ ("L" | "G" | "E") => {
+                self.constraints.push(Constraint {
+                    name: (*name).to_owned(),
+                    sense: sense.chars().next().unwrap_or_default(),
+                    terms: vec![],
+                    rhs: 0.0,
+                    range: None,
+                });
+                RowRef::Constraint(self.constraints.len() - 1)
+            }
+            _ => return Err(format!("unknown row type '{kind}'")),
+        };
+        if self.rows.insert((*name).to_owned(), row).is_some() {
+            return Err(format!("row '{name}' is declared twice"));
+        }
+        Ok(())
+    }
+
+    fn column(&mut self, fields: &[&str]) -> Result<(), String> {
+        if let [_, "'MARKER'", marker] = fields {
+            self.integer = match *marker {
+                "'INTORG'" => true,
+                "'INTEND'" => false,
+                _ => return Err(format!("unknown marker {marker}")),
+            };
+            return Ok(());
+        }
+        let Some((name, entries)) = fields.split_first() else {
+            return Err("expected a column".to_owned());
+        };
+        if entries.is_empty() || !entries.len().is_multiple_of(2) {
+            return Err("expected a column and pairs of rows and values".to_owned());
+        }
+        let next = self.variables.len();
+        let col = *self.columns.entry((*name).to_owned()).or_insert(next);
+        if col == next {
+            self.variables.push(VariableDef {
+                name: (*name).to_owned().into(),
+                min: Some(0.0),
+                max: None,
+                initial: None,
+                integer: Some(self.integer),
+                binary: false,
+                semicontinuous: false,
+                fixed: None,
+                cost: None,
+                branch_priority: None,
+            });
+        }
+        for pair in entries.chunks(2) {
+            let term = CoeffVar::Named {
+                name: (*name).to_owned().into(),
+                factor: number(pair[1])?,
+            };
+            match self.row_ref(pair[0])? {
+                RowRef::Objective => self.objective.push(term),
+                RowRef::Free => {}
+                RowRef::Constraint(i) => self.constraints[i].terms.push(term),
+            }
+        }
+        Ok(())
+    }
+
+    fn rhs(&mut self, fields: &[&str]) -> Result<(), String> {
+        for (row, value) in pairs(fields)? {
+            match self.row_ref(row)? {
+                // the objective row holds the negated constant
+                RowRef::Objective => self.constant = -value,
+                RowRef::Free => {}
+                RowRef::Constraint(i) => self.constraints[i].rhs = value,
+            }
+        }
+        Ok(())
+    }
+
+    fn range(&mut self, fields: &[&str]) -> Result<(), String> {
+        for (row, value) in pairs(fields)? {
+            let RowRef::Constraint(i) = self.row_ref(row)? else {
+                return Err(format!("row '{row}' cannot have a range"));
+            };
+            self.constraints[i].range = Some(value);
+        }
+        Ok(())
+    }
+
+    fn bound(&mut self, fields: &[&str]) -> Result<(), String> {
+        let kind = fields[0].to_ascii_uppercase();
+        // the name of the bound set is optional
+        let valued = !matches!(kind.as_str(), "FR" | "MI" | "PL" | "BV");
+        let (name, value) = match (valued, &fields[1..]) {
+            (true, [_, name, value] | [name, value]) => (*name, Some(number(value)?)),
+            // binaries may be given a value, which is ignored
+            (false, [name, value]) if value.parse::<f64>().is_ok() => (*name, None),
+            (false, [_, name] | [name] | [_, name, _]) => (*name, None),
+            _ => return Err(format!("expected a column for bound type {kind}")),
+        };
+        let col = *self
+            .columns
+            .get(name)
+            .ok_or_else(|| format!("unknown column '{name}'"))?;
+        let v = &mut self.variables[col];
+        let value = value.unwrap_or_default();
+        match kind.as_str() {
+            "UP" => {
+                // a negative upper bound drops the default lower bound
+                if value < 0.0 && v.min == Some(0.0) {
+                    v.min = None;
+                }
+                v.max = Some(value);
+            }
+            "LO" => v.min = Some(value),
+            "FX" => (v.min, v.max) = (Some(value), Some(value)),
+            "FR" => (v.min, v.max) = (None, None),
+            "MI" => v.min = None,
+            "PL" => v.max = None,
+            "BV" => (v.min, v.max, v.integer) = (Some(0.0), Some(1.0), Some(true)),
+            "LI" => (v.min, v.integer) = (Some(value), Some(true)),
+            "UI" => (v.max, v.integer) = (Some(value), Some(true)),
+            "SC" => (v.max, v.semicontinuous) = (Some(value), true),
+            _ => return Err(format!("unknown bound type {kind}")),
+        }
+        Ok(())
+    }
+
+    fn row_ref(&self, name: &str) -> Result<RowRef, String> {
+        self.rows
+            .get(name)
+            .copied()
+            .ok_or_else(|| format!("unknown row '{name}'"))
+    }
+
+    fn finish(self) -> MessageProblem<'static> {
+        let ranged_constraints = self
+            .constraints
+            .into_iter()
+            .map(|c| {
+                let (b, r) = (c.rhs, c.range.map(f64::abs));
+                let (lower, upper) = match (c.sense, r) {
+                    ('L', None) => (f64::NEG_INFINITY, b),
+                    ('L', Some(r)) => (b - r, b),
+                    ('G', None) => (b, f64::INFINITY),
+                    ('G', Some(r)) => (b, b + r),
+                    // the sign of the range tells the side of equalities
+                    (_, Some(r)) if c.range < Some(0.0) => (b - r, b),
+                    (_, Some(r)) => (b, b + r),
+                    (_, None) => (b, b),
+                };
+                RangedConstraint {
+                    terms: c.terms,
+                    lower,
+                    upper,
+                    name: Some(c.name.into()),
+                }
+            })
+            .collect();
+        MessageProblem {
+            direction: Some(if self.maximise {
+                Direction::Max
+            } else {
+                Direction::Min
+            }),
+            variables: self.variables,
+            objective: self.objective,
+            objective_offset: self.constant,
+            ranged_constraints,
+            ..MessageProblem::default()
+        }
+    }
+}
+
+/// Reads the pairs of rows and values after an optional set name.
+fn pairs<'a>(fields: &[&'a str]) -> Result<Vec<(&'a str, f64)>, String> {
+    let entries = if fields.len().is_multiple_of(2) {
+        fields
+    } else {
+        &fields[1..]
+    };
+    if entries.is_empty() {
+        return Err("expected pairs of rows and values".to_owned());
+    }
+    entries
+        .chunks(2)
+        .map(|pair| Ok((pair[0], number(pair[1])?)))
+        .collect()
+}
+
+fn number(text: &str) -> Result<f64, String> {
+    text.parse()
+        .map_err(|_| format!("'{text}' is not a number"))
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)] // the numbers are read, not computed
+mod tests {
+    use super::*;
+
+    fn error(text: &str) -> String {
+        parse_mps(text).err().unwrap().message
+    }
+
+    #[test]
+    fn reads_every_section() {
+        let problem = parse_mps(
+            "* a comment
+NAME          example
+OBJSENSE
+    MAX
+ROWS
+ N  obj
+ N  other
+ L  lim
+ G  low
+ E  eq1
+ E  eq2
+COLUMNS
+    x         obj       1   lim       1
+    x         other     5
+    MARKER    'MARKER'  'INTORG'
+    y         obj       2   low       1
+    y         eq1       1   eq2       1
+    MARKER    'MARKER'  'INTEND'
+    z         eq2       1
+RHS
+    RHS       obj       -3  lim       4
+    RHS       low       1   eq1       2
+    RHS       eq2       5
+RANGES
+    RNG       lim       2   eq1       -1
+    RNG       eq2       3
+BOUNDS
+ UP BND       x         -1
+ BV BND       y
+ SC BND       z         8
+ENDATA
+this is not read",
+        )
+        .unwrap();
+        assert!(problem.direction == Some(Direction::Max));
+        assert_eq!(problem.objective_offset, 3.0);
+        assert_eq!(problem.objective.len(), 2);
+
+        let rows = &problem.ranged_constraints;
+        let names: Vec<_> = rows.iter().map(|r| r.name.as_deref().unwrap()).collect();
+        assert_eq!(names, ["lim", "low", "eq1", "eq2"]);
+        let bounds: Vec<_> = rows.iter().map(|r| (r.lower, r.upper)).collect();
+        assert_eq!(
+            bounds,
+            [(2.0, 4.0), (1.0, f64::INFINITY), (1.0, 2.0), (5.0, 8.0)]
+        );
+        assert_eq!(rows[3].terms.len(), 2);
+
+        let variables = problem.variables.iter();
+        let variables: Vec<_> = variables
+            .map(|v| (v.name.as_ref(), v.min, v.max, v.integer, v.semicontinuous))
+            .collect();
+        assert_eq!(
+            variables,
+            [
+                ("x", None, Some(-1.0), Some(false), false),
+                ("y", Some(0.0), Some(1.0), Some(true), false),
+                ("z", Some(0.0), Some(8.0), Some(false), true)
+            ]
+        );
+    }
+
+    #[test]
+    fn errors_name_the_line() {
+        let e = parse_mps("ROWS\n N obj\n X c\n").err().unwrap();
+        assert_eq!(e.line, Some(3));
+        assert_eq!(e.message, "line 3: unknown row type 'X'");
+        let errors = [
+            ("SOS\n", "section SOS on line 1 is not supported"),
+            ("OBJSENSE\n    UP\n", "line 2: unknown objective sense 'UP'"),
+            (
+                "ROWS\n N obj\n N obj\n",
+                "line 3: row 'obj' is declared twice",
+            ),
+            ("COLUMNS\n x obj 1\n", "line 2: unknown row 'obj'"),
+            (
+                "ROWS\n N obj\nCOLUMNS\n x obj\n",
+                "line 4: expected a column and pairs of rows and values",
+            ),
+            (
+                "ROWS\n N obj\nCOLUMNS\n x obj one\n",
+                "line 4: 'one' is not a number",
+            ),
+            (
+                "ROWS\n N obj\nRANGES\n RNG obj 1\n",
+                "line 4: row 'obj' cannot have a range",
+            ),
+            ("BOUNDS\n UP BND x 1\n", "line 2: unknown column 'x'"),
+            (
+                "ROWS\n N obj\nCOLUMNS\n x obj 1\nBOUNDS\n XX BND x 1\n",
+                "line 6: unknown bound type XX",
+            ),
+        ];
+        for (text, message) in errors {
+            assert_eq!(error(text), message, "{text}");
+        }
+    }
+}
@@ -0,0 +1,142 @@
+//! `MessagePack` encoding of the same documents that are otherwise exchanged
+//! as JSON, with `rmp-serde`. Problems are decoded into JSON values so that
+//! they are read by the usual deserialiser.
+
+use crate::values::Seed;
+use serde::Serialize;
+use serde::de::DeserializeSeed;
+use serde_json::Value;
+use std::io::Cursor;
+
+/// Decodes a single `MessagePack` value that spans all of `bytes`, as
+/// [`Seed`] reads it. Binary data and extension types are not supported.
+pub fn decode(bytes: &[u8]) -> Result<Value, String> {
+    let mut deserializer = rmp_serde::Deserializer::new(Cursor::new(bytes));
+    let value = Seed::default()
+        .deserialize(&mut deserializer)
+        .map_err(|e| format!("{e} after {} bytes", deserializer.position()))?;
+    let end = deserializer.position();
+    if end < bytes.len() as u64 {
+        return Err(format!("trailing bytes at offset {end}"));
+    }
+    Ok(value)
+}
+
+/// Encodes a document with structs as maps, using the smallest
+/// representation of each number.
+pub fn encode(value: &impl Serialize) -> Vec<u8> {
+    rmp_serde::to_vec_named(value).expect("documents can be encoded as MessagePack")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::values::MAX_DEPTH;
+    use serde_json::json;
+
+    #[test]
+    fn decodes_every_kind_of_value() {
+        assert_eq!(decode(&[0x93, 0x01, 0x02, 0x03]), Ok(json!([1, 2, 3])));
+        assert_eq!(decode(&[0x81, 0xa1, b'a', 0xc3]), Ok(json!({"a": true})));
+        assert_eq!(decode(&[0xc0]), Ok(Value::Null));
+        assert_eq!(decode(&[0xff]), Ok(json!(-1)));
+        assert_eq!(decode(&[0xd0, 0x80]), Ok(json!(-128)));
+        assert_eq!(decode(&[0xcd, 0x01, 0x00]), Ok(json!(256)));
+        assert_eq!(decode(&[0xca, 0x3f, 0xc0, 0x00, 0x00]), Ok(json!(1.5)));
+        let mut double = vec![0xcb];
+        double.extend(0.1_f64.to_be_bytes());
+        assert_eq!(decode(&double), Ok(json!(0.1)));
+        assert_eq!(decode(&[0xd9, 0x02, b'h', b'i']), Ok(json!("hi")));
+        assert_eq!(decode(&[0xdc, 0x00, 0x01, 0xc2]), Ok(json!([false])));
+    }
+
+    #[test]
+    fn encodes_the_smallest_representation() {
+        assert_eq!(encode(&json!(127)), [0x7f]);
+        assert_eq!(encode(&json!(128)), [0xcc, 0x80]);
+        assert_eq!(encode(&json!(-32)), [0xe0]);
+        assert_eq!(encode(&json!(-33)), [0xd0, 0xdf]);
+        assert_eq!(encode(&json!(65_536)), [0xce, 0x00, 0x01, 0x00, 0x00]);
+        assert_eq!(encode(&json!("a".repeat(31)))[0], 0xbf);
+        assert_eq!(encode(&json!("a".repeat(32)))[..2], [0xd9, 32]);
+        assert_eq!(encode(&json!(vec![0; 16]))[..3], [0xdc, 0x00, 16]);
+    }
+
+    #[test]
+    fn round_trips_documents() {
+        let value = json!({
+            "names": ["x", "ÿ", "a".repeat(300)],
+            "values": (0..100).map(|i| f64::from(i) / 7.0).collect::<Vec<_>>(),
+            "nested": {"empty": {}, "list": [], "min": i64::MIN, "max": u64::MAX},
+        });
+        assert_eq!(decode(&encode(&value)), Ok(value));
+    }
+
+    #[test]
+    fn infinite_floats_become_strings() {
+        let mut bytes = vec![0x92, 0xcb];
+        bytes.extend(f64::INFINITY.to_be_bytes());
+        bytes.push(0xcb);
+        bytes.extend(f64::NEG_INFINITY.to_be_bytes());
+        assert_eq!(decode(&bytes), Ok(json!(["inf", "-inf"])));
+        let mut nan = vec![0xcb];
+        nan.extend(f64::NAN.to_be_bytes());
+        assert_eq!(
+            decode(&nan),
+            Err("NaN is not a number after 9 bytes".into())
+        );
+    }
+
+    #[test]
+    fn rejects_what_is_not_a_document() {
+        let error = |bytes: &[u8]| decode(bytes).unwrap_err();
+        assert_eq!(error(&[0x01, 0x02]), "trailing bytes at offset 1");
+        assert!(error(&[0x92, 0x01]).ends_with("after 2 bytes"));
+        assert!(error(&[0xc1]).starts_with("wrong msgpack marker"));
+        assert_eq!(
+            error(&[0x81, 0x01, 0x01]),
+            "invalid type: integer `1`, expected a string after 2 bytes"
+        );
+        assert!(error(&[0xc4, 0x00]).starts_with("invalid type: byte array"));
+        assert!(error(&[0xd4, 0x01, 0x02]).starts_with("invalid type: newtype struct"));
+        assert!(error(&[0xa1, 0xff]).starts_with("string found to be invalid utf8"));
+        assert_eq!(
+            error(&[0x82, 0xa1, b'a', 0x01, 0xa1, b'a', 0x02]),
+            "duplicate key 'a' after 7 bytes"
+        );
+        let deep = [0x91; MAX_DEPTH + 2];
+        assert!(error(&deep).starts_with("nesting deeper than 128"));
+    }
+
+    #[test]
+    fn solves_like_json() {
+        let problem = json!({
+            "direction": "max",
+            "variables": [{"name": "x", "max": 10}, {"name": "y", "max": 10, "integer": true}],
+            "objective": [{"name": "x", "factor": 1}, {"name": "y", "factor": 2}],
+            "expressions": ["3*x + 7*y <= 31", "x - y >= 0.1"],
+            "verbose": false
+        });
+        let input = problem.to_string();
+        let response = unsafe { crate::solve(input.as_ptr(), input.len()) };
+        let text = unsafe { std::ffi::CStr::from_ptr(response) };
+        let json: Value = serde_json::from_slice(text.to_bytes()).unwrap();
+        unsafe { crate::free_string(response.cast_mut()) };
+
+        let input = encode(&problem);
+        let mut len = 0;
+        let output = unsafe { crate::solve_msgpack(input.as_ptr(), input.len(), &raw mut len) };
+        let packed = decode(unsafe { std::slice::from_raw_parts(output, len) }).unwrap();
+        unsafe { crate::free_msgpack(output, len) };
+        assert_eq!(packed["status"], "optimal");
+        let bits = |solution: &Value| -> Vec<u64> {
+            let values = solution["values"].as_array().unwrap();
+            values
+                .iter()
+                .map(|v| v.as_f64().unwrap().to_bits())
+                .collect()
+        };
+        assert_eq!(bits(&packed), bits(&json));
+        assert_eq!(packed["objective"], json["objective"]);
+    }
+}
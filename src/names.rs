@@ -0,0 +1,207 @@
+//! Matching of referenced names against the declared ones for inputs whose
+//! spelling of names differs, such as in case.
+
+use crate::{CoeffVar, MessageError, MessageProblem, element_name};
+use serde::{Deserialize, Deserializer};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// How a referenced name is compared to the names of the variables.
+#[derive(Deserialize, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NameMatching {
+    #[default]
+    Exact,
+    CaseInsensitive,
+    Trim, // ignore surrounding whitespace
+}
+impl NameMatching {
+    /// The form of `name` in which names have to be equal.
+    pub(crate) fn normalize(self, name: &str) -> Cow<'_, str> {
+        match self {
+            NameMatching::Exact => Cow::Borrowed(name),
+            NameMatching::CaseInsensitive => Cow::Owned(name.to_lowercase()),
+            NameMatching::Trim => Cow::Borrowed(name.trim()),
+        }
+    }
+}
+
+/// A name that is borrowed from the input unless it has escapes, since
+/// serde only borrows into a `Cow` that is a field itself.
+#[derive(Deserialize)]
+#[serde(transparent)]
+struct Name<'a>(#[serde(borrow)] Cow<'a, str>);
+
+/// Reads a list of names like [`Name`].
+pub fn list<'de: 'a, 'a, D: Deserializer<'de>>(d: D) -> Result<Vec<Cow<'a, str>>, D::Error> {
+    let names = Vec::<Name>::deserialize(d)?;
+    Ok(names.into_iter().map(|Name(name)| name).collect())
+}
+/// Reads an optional name like [`Name`].
+pub fn optional<'de: 'a, 'a, D: Deserializer<'de>>(d: D) -> Result<Option<Cow<'a, str>>, D::Error> {
+    Ok(Option::<Name>::deserialize(d)?.map(|Name(name)| name))
+}
+
+/// Replaces every referenced name by the declared name that it matches, so
+/// that the rest of the pipeline can compare names exactly. Names that match
+/// nothing are left for the usual error. Fails if two declared names match
+/// each other.
+pub fn canonicalize(input: &mut MessageProblem) -> Result<(), MessageError> {
+    let mode = input.name_matching;
+    if mode == NameMatching::Exact {
+        return Ok(());
+    }
+    let extrema = input.max_of.iter().chain(&input.min_of).map(|e| &e.name);
+    let mut declared: HashMap<String, String> = HashMap::new();
+    for name in input.variables.iter().map(|v| &v.name).chain(extrema) {
+        let key = mode.normalize(name).into_owned();
+        if let Some(other) = declared.insert(key, name.to_string())
+            && other != *name
+        {
+            return Err(MessageError::validation(format!(
+                "variables '{other}' and '{name}' cannot be told apart by name_matching"
+            )));
+        }
+    }
+    let names = Names { mode, declared };
+
+    names.terms(&mut input.objective);
+    for o in &mut input.objectives {
+        names.terms(&mut o.terms);
+    }
+    for q in &mut input.objective_quadratic {
+        names.name(&mut q.name_a);
+        names.name(&mut q.name_b);
+    }
+    for p in &mut input.piecewise {
+        names.name(&mut p.variable);
+    }
+    for a in &mut input.abs_terms {
+        names.terms(&mut a.terms);
+    }
+    for e in input.max_of.iter_mut().chain(&mut input.min_of) {
+        for expr in &mut e.expressions {
+            names.terms(&mut expr.terms);
+        }
+    }
+    let lists = [
+        &mut input.constraints,
+        &mut input.equalities,
+        &mut input.geq_constraints,
+    ];
+    for terms in lists.into_iter().flatten() {
+        names.terms(terms);
+    }
+    for r in &mut input.rows {
+        names.terms(&mut r.terms);
+    }
+    for r in &mut input.ranged_constraints {
+        names.terms(&mut r.terms);
+    }
+    for s in &mut input.sos {
+        s.variables.iter_mut().for_each(|v| names.name(v));
+    }
+    for i in &mut input.indicators {
+        names.name(&mut i.variable);
+        names.terms(&mut i.terms);
+    }
+    for s in &mut input.partial_start {
+        names.name(&mut s.name);
+    }
+    Ok(())
+}
+
+struct Names {
+    mode: NameMatching,
+    declared: HashMap<String, String>, // normalized to declared names
+}
+impl Names {
+    fn name(&self, name: &mut Cow<'_, str>) {
+        if let Some(declared) = self.declared.get(self.mode.normalize(name).as_ref()) {
+            *name = Cow::Owned(declared.clone());
+        }
+    }
+    /// Also turns array elements into named terms, since the array name has
+    /// to match, too.
+    fn terms(&self, terms: &mut [CoeffVar]) {
+        for term in terms {
+            match term {
+                CoeffVar::Named { name, .. } => self.name(name),
+                CoeffVar::Element {
+                    array,
+                    indices,
+                    factor,
+                } => {
+                    let mut name = Cow::Owned(element_name(array, indices));
+                    self.name(&mut name);
+                    let factor = *factor;
+                    *term = CoeffVar::Named { name, factor };
+                }
+                CoeffVar::Indexed { .. } => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canonical(problem: &str) -> Result<Vec<String>, String> {
+        let mut input: MessageProblem = serde_json::from_str(problem).unwrap();
+        canonicalize(&mut input).map_err(|e| e.message)?;
+        let terms = input.objective.iter().map(|term| match term {
+            CoeffVar::Named { name, .. } => name.to_string(),
+            _ => "not named".to_owned(),
+        });
+        Ok(terms
+            .chain(
+                input
+                    .sos
+                    .into_iter()
+                    .flat_map(|s| s.variables)
+                    .map(String::from),
+            )
+            .collect())
+    }
+
+    #[test]
+    fn references_take_the_declared_spelling() {
+        let problem = r#"{
+            "direction": "max",
+            "verbose": false,
+            "name_matching": "case_insensitive",
+            "variables": [{"name": "Flow[0]"}, {"name": "Stock"}],
+            "objective": [
+                {"name": "STOCK", "factor": 1},
+                {"array": "flow", "indices": [0], "factor": 1},
+                {"name": "unknown", "factor": 1}
+            ],
+            "sos": [{"type": 1, "variables": ["stock", "FLOW[0]"]}]
+        }"#;
+        let names = canonical(problem).unwrap();
+        assert_eq!(names, ["Stock", "Flow[0]", "unknown", "Stock", "Flow[0]"]);
+
+        let trimmed = problem
+            .replace("case_insensitive", "trim")
+            .replace("STOCK", " Stock ");
+        assert_eq!(canonical(&trimmed).unwrap()[0], "Stock");
+        let exact = problem.replace("case_insensitive", "exact");
+        assert_eq!(canonical(&exact).unwrap()[0], "STOCK");
+    }
+
+    #[test]
+    fn declared_names_have_to_stay_apart() {
+        let problem = r#"{
+            "direction": "max",
+            "verbose": false,
+            "name_matching": "case_insensitive",
+            "variables": [{"name": "x"}, {"name": "X"}],
+            "objective": []
+        }"#;
+        assert_eq!(
+            canonical(problem).unwrap_err(),
+            "variables 'x' and 'X' cannot be told apart by name_matching"
+        );
+    }
+}
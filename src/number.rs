@@ -0,0 +1,182 @@
+//! Numbers that may also be given as strings, for inputs that cannot express
+//! infinities or exact fractions in JSON.
+
+use serde::{Deserialize, Deserializer, de};
+
+/// Number given as a JSON number or as a string that holds a decimal, a
+/// fraction `p/q`, or one of `inf`, `+inf` and `-inf` in any case.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum Number {
+    Number(f64),
+    Text(String),
+}
+impl Number {
+    pub fn value<E: de::Error>(self, field: &str) -> Result<f64, E> {
+        match self {
+            Number::Number(value) => Ok(value),
+            Number::Text(text) => parse(&text).ok_or_else(|| {
+                E::custom(format!(
+                    "{field} must be a number, a fraction p/q, \"inf\" or \"-inf\", not \"{text}\""
+                ))
+            }),
+        }
+    }
+    /// Like [`Number::value`], without the infinities.
+    pub fn finite<E: de::Error>(self, field: &str) -> Result<f64, E> {
+        let value = self.value(field)?;
+        if value.is_finite() {
+            Ok(value)
+        } else {
+            Err(E::custom(format!("{field} must be finite, not {value}")))
+        }
+    }
+}
+
+fn parse(text: &str) -> Option<f64> {
+    match text.to_ascii_lowercase().as_str() {
+        "inf" | "+inf" => return Some(f64::INFINITY),
+        "-inf" => return Some(f64::NEG_INFINITY),
+        _ => {}
+    }
+    if let Some((p, q)) = text.split_once('/') {
+        return fraction(p, q);
+    }
+    // the standard parser also takes spellings like `nan` and `infinity`
+    let is_decimal = text.chars().any(|c| c.is_ascii_digit())
+        && text
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '+' | '-'));
+    is_decimal.then(|| text.parse().ok()).flatten()
+}
+
+/// Rounds `p/q` correctly. This is exact as long as numerator and
+/// denominator are at most 2^53 in their lowest terms, where both are exact
+/// as floats and a single division rounds the quotient.
+fn fraction(p: &str, q: &str) -> Option<f64> {
+    let (negative, p) = match p.strip_prefix('-') {
+        Some(p) => (true, p),
+        None => (false, p.strip_prefix('+').unwrap_or(p)),
+    };
+    let digits = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+    if !digits(p) || !digits(q) {
+        return None;
+    }
+    let (mut p, mut q) = (p.parse::<u128>().ok()?, q.parse::<u128>().ok()?);
+    if q == 0 {
+        return None;
+    }
+    let d = gcd(p, q);
+    (p, q) = (p / d, q / d);
+    let exact = 1 << 53;
+    if p > exact || q > exact {
+        return None;
+    }
+    #[allow(clippy::cast_precision_loss)] // both are exact
+    let value = p as f64 / q as f64;
+    Some(if negative { -value } else { value })
+}
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+pub fn min_bound<'de, D: Deserializer<'de>>(d: D) -> Result<Option<f64>, D::Error> {
+    Option::<Number>::deserialize(d)?
+        .map(|n| n.value("min"))
+        .transpose()
+}
+pub fn max_bound<'de, D: Deserializer<'de>>(d: D) -> Result<Option<f64>, D::Error> {
+    Option::<Number>::deserialize(d)?
+        .map(|n| n.value("max"))
+        .transpose()
+}
+pub fn lower_bound<'de, D: Deserializer<'de>>(d: D) -> Result<f64, D::Error> {
+    Number::deserialize(d)?.value("lower")
+}
+pub fn upper_bound<'de, D: Deserializer<'de>>(d: D) -> Result<f64, D::Error> {
+    Number::deserialize(d)?.value("upper")
+}
+pub fn rhs<'de, D: Deserializer<'de>>(d: D) -> Result<f64, D::Error> {
+    Number::deserialize(d)?.value("rhs")
+}
+pub fn factor<'de, D: Deserializer<'de>>(d: D) -> Result<f64, D::Error> {
+    Number::deserialize(d)?.finite("factor")
+}
+pub fn objective_offset<'de, D: Deserializer<'de>>(d: D) -> Result<f64, D::Error> {
+    Number::deserialize(d)?.finite("objective_offset")
+}
+pub fn constraint_offsets<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<f64>, D::Error> {
+    offsets(d, "constraint_offsets")
+}
+pub fn equalities_offsets<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<f64>, D::Error> {
+    offsets(d, "equalities_offsets")
+}
+pub fn geq_offsets<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<f64>, D::Error> {
+    offsets(d, "geq_offsets")
+}
+pub fn sensed_rhs<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<f64>, D::Error> {
+    offsets(d, "rhs")
+}
+/// Offsets of rows, whose errors name the row.
+fn offsets<'de, D: Deserializer<'de>>(d: D, field: &str) -> Result<Vec<f64>, D::Error> {
+    Vec::<Number>::deserialize(d)?
+        .into_iter()
+        .enumerate()
+        .map(|(i, n)| n.finite(&format!("{field}[{i}]")))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Value, json};
+
+    fn value(json: Value) -> Result<f64, String> {
+        let number = Number::deserialize(json).unwrap();
+        number
+            .value::<serde_json::Error>("rhs")
+            .map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn reads_decimals_fractions_and_infinities() {
+        assert_eq!(value(json!(1.5)), Ok(1.5));
+        assert_eq!(value(json!("2.5e1")), Ok(25.0));
+        assert_eq!(value(json!("-1/3")), Ok(-1.0 / 3.0));
+        assert_eq!(value(json!("+6/4")), Ok(1.5));
+        assert_eq!(value(json!("INF")), Ok(f64::INFINITY));
+        assert_eq!(value(json!("-inf")), Ok(f64::NEG_INFINITY));
+        // in lowest terms, both fit into a float exactly
+        let p = (1_u64 << 53) * 3;
+        assert_eq!(value(json!(format!("{p}/6"))), Ok(4_503_599_627_370_496.0));
+    }
+
+    #[test]
+    fn rejects_other_strings() {
+        for text in ["nan", "infinity", "1/0", "1/-2", "", "1e", "0x10"] {
+            let error = value(json!(text)).unwrap_err();
+            let expected = format!(
+                "rhs must be a number, a fraction p/q, \"inf\" or \"-inf\", not \"{text}\""
+            );
+            assert_eq!(error, expected);
+        }
+        assert_eq!(value(json!("9007199254740993/1")).ok(), None);
+        let number = Number::deserialize(json!("inf")).unwrap();
+        let error = number.finite::<serde_json::Error>("factor").unwrap_err();
+        assert_eq!(error.to_string(), "factor must be finite, not inf");
+    }
+
+    #[test]
+    fn errors_of_offsets_name_the_row() {
+        let mut deserializer = serde_json::Deserializer::from_str(r#"[1, "1/2", "x"]"#);
+        let error = geq_offsets(&mut deserializer).unwrap_err().to_string();
+        assert!(
+            error.starts_with("geq_offsets[2] must be a number"),
+            "{error}"
+        );
+    }
+}
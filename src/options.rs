@@ -0,0 +1,115 @@
+//! Bundles of HiGHS options that stand for the higher-level settings of
+//! [`crate::Options`], kept in one place so that each mapping is visible.
+
+use crate::Emphasis;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Value of a HiGHS option.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Setting {
+    Bool(bool),
+    Int(i32),
+    Double(f64),
+}
+use Setting::{Bool, Double, Int};
+
+impl Setting {
+    #[cfg(feature = "highs")]
+    pub fn apply(self, model: &mut highs::Model, name: &str) {
+        match self {
+            Bool(value) => model.set_option(name, value),
+            Int(value) => model.set_option(name, value),
+            Double(value) => model.set_option(name, value),
+        }
+    }
+}
+/// Writes the value like HiGHS in its option files.
+impl fmt::Display for Setting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Bool(value) => write!(f, "{value}"),
+            Int(value) => write!(f, "{value}"),
+            Double(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+/// The MIP options of an emphasis. Balanced lists the defaults of HiGHS, so
+/// that every emphasis sets the same options.
+pub fn emphasis(emphasis: Emphasis) -> &'static [(&'static str, Setting)] {
+    match emphasis {
+        Emphasis::Balanced => &[
+            ("mip_heuristic_effort", Double(0.05)),
+            ("mip_heuristic_run_rins", Bool(true)),
+            ("mip_heuristic_run_rens", Bool(true)),
+            ("mip_pool_soft_limit", Int(10_000)),
+            ("mip_pscost_minreliable", Int(8)),
+        ],
+        // more time in heuristics, fewer cuts and cheaper branching
+        Emphasis::Feasibility => &[
+            ("mip_heuristic_effort", Double(0.3)),
+            ("mip_heuristic_run_rins", Bool(true)),
+            ("mip_heuristic_run_rens", Bool(true)),
+            ("mip_pool_soft_limit", Int(2_000)),
+            ("mip_pscost_minreliable", Int(2)),
+        ],
+        // little time in heuristics, more cuts and more reliable branching
+        Emphasis::Optimality => &[
+            ("mip_heuristic_effort", Double(0.01)),
+            ("mip_heuristic_run_rins", Bool(false)),
+            ("mip_heuristic_run_rens", Bool(false)),
+            ("mip_pool_soft_limit", Int(30_000)),
+            ("mip_pscost_minreliable", Int(16)),
+        ],
+    }
+}
+
+/// The options of an emphasis with their values as text, for the stats.
+pub fn listed(emphasis: Emphasis) -> BTreeMap<&'static str, String> {
+    self::emphasis(emphasis)
+        .iter()
+        .map(|(name, setting)| (*name, setting.to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EMPHASES: [Emphasis; 3] = [
+        Emphasis::Balanced,
+        Emphasis::Feasibility,
+        Emphasis::Optimality,
+    ];
+
+    #[test]
+    fn every_emphasis_sets_the_same_options() {
+        let names = |e| {
+            emphasis(e)
+                .iter()
+                .map(|(name, _)| *name)
+                .collect::<Vec<_>>()
+        };
+        for e in EMPHASES {
+            assert_eq!(names(e), names(Emphasis::Balanced));
+        }
+        // of the same types
+        for e in EMPHASES {
+            let settings = emphasis(e).iter().zip(emphasis(Emphasis::Balanced));
+            for ((name, setting), (_, balanced)) in settings {
+                let same = std::mem::discriminant(setting) == std::mem::discriminant(balanced);
+                assert!(same, "{name}");
+            }
+        }
+    }
+
+    #[test]
+    fn values_are_listed_like_highs_writes_them() {
+        let listed = listed(Emphasis::Optimality);
+        assert_eq!(listed["mip_heuristic_effort"], "0.01");
+        assert_eq!(listed["mip_heuristic_run_rins"], "false");
+        assert_eq!(listed["mip_pool_soft_limit"], "30000");
+        assert_eq!(Double(1.0).to_string(), "1");
+    }
+}
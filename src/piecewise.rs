@@ -0,0 +1,61 @@
+//! Piecewise-linear objective terms, expanded into one column per segment.
+
+use crate::raw;
+use highs::Model;
+use highs_sys::HighsInt;
+
+/// Adds the term for column `x` with `slopes[k]` between `breakpoints[k]`
+/// and `breakpoints[k + 1]`, which is zero at the first breakpoint. The
+/// breakpoints have to increase. Only the last one may be infinite, and
+/// only if `in_order` is set.
+///
+/// Each segment gets a column for the part of `x` that falls into it. If
+/// the solver prefers the segments in their order anyway, because the
+/// term is convex in the direction of the objective, nothing else is
+/// needed. Otherwise, binaries enforce that a segment is only used once
+/// the previous one is full.
+///
+/// Returns the columns of the segments with their slopes, which are their
+/// costs.
+pub fn add(
+    model: &mut Model,
+    x: HighsInt,
+    breakpoints: &[f64],
+    slopes: &[f64],
+    in_order: bool,
+) -> Option<Vec<(HighsInt, f64)>> {
+    let lengths: Vec<_> = breakpoints.windows(2).map(|w| w[1] - w[0]).collect();
+    let segments = lengths
+        .iter()
+        .zip(slopes)
+        .map(|(&len, &slope)| raw::add_column(model, slope, 0.0, len))
+        .collect::<Option<Vec<_>>>()?;
+    // x - sum of segments == first breakpoint
+    let index: Vec<_> = [x].into_iter().chain(segments.iter().copied()).collect();
+    let factors: Vec<_> = [1.0]
+        .into_iter()
+        .chain(segments.iter().map(|_| -1.0))
+        .collect();
+    if !raw::add_row(model, breakpoints[0], breakpoints[0], &index, &factors) {
+        return None;
+    }
+    if !in_order {
+        for (k, pair) in segments.windows(2).enumerate() {
+            // segment k is full if full is set, and segment k + 1 is empty otherwise
+            let full = raw::add_binary(model)?;
+            let (len, next) = (lengths[k], lengths[k + 1]);
+            if !raw::add_row(model, 0.0, f64::INFINITY, &[pair[0], full], &[1.0, -len])
+                || !raw::add_row(
+                    model,
+                    f64::NEG_INFINITY,
+                    0.0,
+                    &[pair[1], full],
+                    &[1.0, -next],
+                )
+            {
+                return None;
+            }
+        }
+    }
+    Some(segments.into_iter().zip(slopes.iter().copied()).collect())
+}
@@ -0,0 +1,108 @@
+//! Solution pool built by cutting off the integer part of the last solution
+//! and solving again until enough distinct solutions were found.
+
+#[cfg(feature = "highs")]
+use crate::raw;
+#[cfg(feature = "highs")]
+use highs::{HighsSolutionStatus, Model, SolvedModel};
+#[cfg(feature = "highs")]
+use highs_sys::HighsInt;
+use serde::Serialize;
+
+/// Integer variable with finite bounds.
+pub struct IntegerColumn {
+    pub index: usize,
+    pub min: f64,
+    pub max: f64,
+}
+
+/// Feasible assignment in the solution pool.
+#[derive(Serialize)]
+pub struct PoolSolution {
+    pub values: Vec<f64>,
+    pub objective: f64,
+}
+
+/// Collects up to `limit` solutions, starting with the incumbent of
+/// `solved`. Each round excludes the values of the integer variables of the
+/// previous solution, so continuous variables may repeat between solutions.
+#[cfg(feature = "highs")]
+pub fn collect(
+    mut solved: SolvedModel,
+    integers: &[IntegerColumn],
+    num_vars: usize,
+    objective_offset: f64,
+    limit: usize,
+) -> Vec<PoolSolution> {
+    let mut pool = vec![];
+    loop {
+        let values = solved.get_solution().columns()[..num_vars].to_vec();
+        // HiGHS never sees the objective offset
+        let objective = solved.objective_value() + objective_offset;
+        let mut model = Model::from(solved);
+        let excluded = pool.len() + 1 < limit && exclude(&mut model, integers, &values);
+        pool.push(PoolSolution { values, objective });
+        if !excluded {
+            break;
+        }
+        match model.try_solve() {
+            Ok(next) if next.primal_solution_status() == HighsSolutionStatus::Feasible => {
+                solved = next;
+            }
+            _ => break,
+        }
+    }
+    pool
+}
+
+/// Adds a no-good cut that requires at least one integer variable to differ
+/// from `values`. Binaries enter the cut directly, general integers get two
+/// auxiliary binaries for moving up or down. Returns false if nothing could
+/// be excluded.
+#[cfg(feature = "highs")]
+fn exclude(model: &mut Model, integers: &[IntegerColumn], values: &[f64]) -> bool {
+    let mut index = vec![];
+    let mut factors = vec![];
+    let mut lower = 1.0;
+    for col in integers.iter().filter(|c| c.min < c.max) {
+        let Ok(x) = HighsInt::try_from(col.index) else {
+            return false;
+        };
+        let v = values[col.index].round();
+        if col.min >= 0.0 && col.max <= 1.0 {
+            index.push(x);
+            if v > 0.5 {
+                factors.push(-1.0);
+                lower -= 1.0;
+            } else {
+                factors.push(1.0);
+            }
+            continue;
+        }
+        if v < col.max {
+            // up = 1 implies x >= v + 1
+            let Some(up) = raw::add_binary(model) else {
+                return false;
+            };
+            let big_m = v + 1.0 - col.min;
+            if !raw::add_row(model, col.min, f64::INFINITY, &[x, up], &[1.0, -big_m]) {
+                return false;
+            }
+            index.push(up);
+            factors.push(1.0);
+        }
+        if v > col.min {
+            // down = 1 implies x <= v - 1
+            let Some(down) = raw::add_binary(model) else {
+                return false;
+            };
+            let big_m = col.max - v + 1.0;
+            if !raw::add_row(model, f64::NEG_INFINITY, col.max, &[x, down], &[1.0, big_m]) {
+                return false;
+            }
+            index.push(down);
+            factors.push(1.0);
+        }
+    }
+    !index.is_empty() && raw::add_row(model, lower, f64::INFINITY, &index, &factors)
+}
@@ -0,0 +1,407 @@
+//! Protocol Buffers encoding of problems and responses, with the messages
+//! that `build.rs` generates from `proto/knorpelsolve.proto` for prost. The
+//! messages are transcoded to and from JSON values with serde.
+
+use prost::Message;
+use serde::Deserialize;
+use serde_json::Value;
+
+#[allow(clippy::all, clippy::pedantic)] // generated
+mod pb {
+    include!(concat!(env!("OUT_DIR"), "/knorpelsolve.rs"));
+}
+
+/// Decodes a `Problem` message into its JSON form. Unknown fields are
+/// skipped, and infinite doubles become the strings `"inf"` and `"-inf"`.
+pub fn decode(bytes: &[u8]) -> Result<Value, String> {
+    let problem = pb::Problem::decode(bytes).map_err(|e| {
+        let e = e.to_string();
+        let prefix = "failed to decode Protobuf message: ";
+        e.strip_prefix(prefix)
+            .map_or_else(|| e.clone(), str::to_owned)
+    })?;
+    serde_json::to_value(problem).map_err(|e| e.to_string())
+}
+
+/// Encodes a solution or an error document as a `Response` message. Keys
+/// that the messages do not have make it an internal error instead.
+pub fn encode(value: &Value) -> Vec<u8> {
+    use pb::response::Result::{Error, Solution};
+    let result = match value.get("status") {
+        Some(status) if status == "error" => {
+            let mut error = value.clone();
+            if let Value::Object(map) = &mut error {
+                map.remove("status");
+            }
+            pb::Error::deserialize(error).map(Error)
+        }
+        _ => pb::Solution::deserialize(value).map(Solution),
+    };
+    let result = result.unwrap_or_else(|e| {
+        Error(pb::Error {
+            kind: pb::ErrorKind::Internal.into(),
+            message: format!("could not encode the response: {e}"),
+            ..pb::Error::default()
+        })
+    });
+    pb::Response {
+        result: Some(result),
+    }
+    .encode_to_vec()
+}
+
+/// The JSON form of fields, for the serde impls of the messages.
+mod json {
+    use crate::number::Number;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer, de, ser};
+    use std::collections::BTreeMap;
+
+    /// JSON names of the values of an enum from 1 on, since 0 is
+    /// unspecified.
+    pub trait Names {
+        const NAMES: &'static [&'static str];
+    }
+
+    pub fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+        *value == T::default()
+    }
+
+    /// A double, which is the string `"inf"` or `"-inf"` if it is infinite.
+    pub struct Double(pub f64);
+    impl Serialize for Double {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            match self.0 {
+                f if f.is_nan() => Err(ser::Error::custom("NaN is not a number")),
+                f if f.is_infinite() => {
+                    serializer.serialize_str(if f > 0.0 { "inf" } else { "-inf" })
+                }
+                f => serializer.serialize_f64(f),
+            }
+        }
+    }
+    impl<'de> Deserialize<'de> for Double {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            Number::deserialize(deserializer)?
+                .value("a double")
+                .map(Double)
+        }
+    }
+
+    /// A double that is 0 if it is null, like if it is left out.
+    pub mod double {
+        use super::{Deserialize, Deserializer, Double, Serialize, Serializer};
+        #[allow(clippy::trivially_copy_pass_by_ref)] // the signature of serialize_with
+        pub fn serialize<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+            Double(*value).serialize(serializer)
+        }
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+            Ok(Option::<Double>::deserialize(deserializer)?.map_or(0.0, |d| d.0))
+        }
+    }
+
+    pub mod optional_double {
+        use super::{Deserialize, Deserializer, Double, Serialize, Serializer};
+        #[allow(clippy::ref_option)] // the signature of serialize_with
+        pub fn serialize<S: Serializer>(
+            value: &Option<f64>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            value.map(Double).serialize(serializer)
+        }
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<f64>, D::Error> {
+            Ok(Option::<Double>::deserialize(deserializer)?.map(|d| d.0))
+        }
+    }
+
+    pub mod doubles {
+        use super::{Deserialize, Deserializer, Double, Serializer};
+        pub fn serialize<S: Serializer>(values: &[f64], serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_seq(values.iter().map(|&value| Double(value)))
+        }
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Vec<f64>, D::Error> {
+            let values = Vec::<Double>::deserialize(deserializer)?;
+            Ok(values.into_iter().map(|d| d.0).collect())
+        }
+    }
+
+    pub mod double_map {
+        use super::{BTreeMap, Deserialize, Deserializer, Double, Serializer};
+        pub fn serialize<S: Serializer>(
+            values: &BTreeMap<String, f64>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            serializer.collect_map(values.iter().map(|(key, &value)| (key, Double(value))))
+        }
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<BTreeMap<String, f64>, D::Error> {
+            let values = BTreeMap::<String, Double>::deserialize(deserializer)?;
+            Ok(values.into_iter().map(|(key, d)| (key, d.0)).collect())
+        }
+    }
+
+    fn name<E: Names, Err: ser::Error>(value: i32) -> Result<&'static str, Err> {
+        let name = usize::try_from(value - 1)
+            .ok()
+            .and_then(|i| E::NAMES.get(i));
+        name.copied()
+            .ok_or_else(|| Err::custom(format!("unknown enum value {value}")))
+    }
+
+    fn value<E: Names, Err: de::Error>(name: &str) -> Result<i32, Err> {
+        let i = E::NAMES.iter().position(|n| *n == name);
+        let i = i.ok_or_else(|| Err::unknown_variant(name, E::NAMES))?;
+        Ok(i32::try_from(i + 1).expect("enums are small"))
+    }
+
+    #[allow(clippy::trivially_copy_pass_by_ref)] // the signature of serialize_with
+    pub fn enum_name<E: Names, S: Serializer>(
+        value: &i32,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(name::<E, _>(*value)?)
+    }
+
+    /// The value of a name, which is unspecified if it is null.
+    pub fn enum_value<'de, E: Names, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<i32, D::Error> {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(name) => value::<E, _>(&name),
+            None => Ok(0),
+        }
+    }
+
+    pub fn enum_names<E: Names, S: Serializer>(
+        values: &[i32],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let names: Vec<_> = values
+            .iter()
+            .map(|&v| name::<E, S::Error>(v))
+            .collect::<Result<_, _>>()?;
+        names.serialize(serializer)
+    }
+
+    pub fn enum_values<'de, E: Names, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<i32>, D::Error> {
+        let names = Vec::<String>::deserialize(deserializer)?;
+        names.iter().map(|name| value::<E, _>(name)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::json::Names;
+    use super::*;
+    use serde_json::json;
+
+    fn hex(text: &str) -> Vec<u8> {
+        let text = text.replace(' ', "");
+        let digit = |i| u8::from_str_radix(&text[i..i + 2], 16).unwrap();
+        (0..text.len()).step_by(2).map(digit).collect()
+    }
+
+    fn problem(value: &Value) -> Vec<u8> {
+        pb::Problem::deserialize(value).unwrap().encode_to_vec()
+    }
+
+    fn response(bytes: &[u8]) -> Value {
+        serde_json::to_value(pb::Response::decode(bytes).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn enum_names_are_the_json_ones() {
+        use crate::{BasisStatus, ErrorKind, Status};
+        let names = |values: Vec<Value>| -> Vec<String> {
+            values
+                .iter()
+                .map(|v| v.as_str().unwrap().to_owned())
+                .collect()
+        };
+        let statuses = [
+            Status::Optimal,
+            Status::Feasible,
+            Status::Unbounded,
+            Status::Infeasible,
+            Status::TimeLimit,
+            Status::NodeLimit,
+            Status::Interrupted,
+            Status::StageUnbounded,
+            Status::StageInfeasible,
+            Status::IterationLimit,
+            Status::SolutionLimit,
+        ];
+        let statuses = statuses.map(|s| serde_json::to_value(s).unwrap());
+        assert_eq!(names(statuses.to_vec()), pb::Status::NAMES);
+        let kinds = [
+            ErrorKind::Parse,
+            ErrorKind::Validation,
+            ErrorKind::Solver,
+            ErrorKind::Internal,
+            ErrorKind::ResourceLimit,
+        ];
+        let kinds = kinds.map(|k| serde_json::to_value(k).unwrap());
+        assert_eq!(names(kinds.to_vec()), pb::ErrorKind::NAMES);
+        let bases = [
+            BasisStatus::Lower,
+            BasisStatus::Basic,
+            BasisStatus::Upper,
+            BasisStatus::Free,
+            BasisStatus::Nonbasic,
+        ];
+        let bases = bases.map(|b| serde_json::to_value(b).unwrap());
+        assert_eq!(names(bases.to_vec()), pb::BasisStatus::NAMES);
+        assert_eq!(pb::Op::NAMES, ["<=", ">=", "=="]);
+    }
+
+    #[test]
+    fn decodes_the_wire_format() {
+        // direction 2 in field 3, a variable "x" with max 3 in field 4, and a
+        // term "x" with factor 2 in field 9
+        let problem = hex("1802 220c 0a0178 190000000000000840 4a0c 0a0178 290000000000000040");
+        let decoded = decode(&problem).unwrap();
+        assert_eq!(decoded["direction"], "max");
+        assert_eq!(decoded["variables"], json!([{"name": "x", "max": 3.0}]));
+        let term = json!({"name": "x", "factor": 2.0, "indices": []});
+        assert_eq!(decoded["objective"], json!([term]));
+        assert_eq!(decoded["constraints"], json!([]));
+        assert_eq!(decoded.get("options"), None);
+
+        // an unknown field and an enum that is unspecified
+        let decoded = decode(&hex("f8ff0701 1800")).unwrap();
+        assert_eq!(decoded.get("direction"), None);
+        // the last value of a field counts
+        let decoded = decode(&hex("0a0161 0a0162")).unwrap();
+        assert_eq!(decoded["id"], "b");
+    }
+
+    #[test]
+    fn encodes_the_wire_format() {
+        let solution = json!({"status": "optimal", "values": [1.5, 2.0], "objective": 3.0});
+        // status 1, values packed, objective 3
+        let expected = "0a1d 1001 1a10 000000000000f83f 0000000000000040 21 0000000000000840";
+        assert_eq!(encode(&solution), hex(expected));
+        let error = json!({"status": "error", "kind": "parse", "message": "m"});
+        assert_eq!(encode(&error), hex("1205 0801 12016d"));
+        // infinite ends of ranges, which are null in JSON
+        let ranges = json!({"status": "optimal", "cost_ranges": [[2.0, null], [null, 1]]});
+        assert_eq!(
+            response(&encode(&ranges))["solution"]["cost_ranges"],
+            json!([[2.0, "inf"], ["-inf", 1.0]])
+        );
+        // keys that the messages do not have
+        let unknown = response(&encode(&json!({"colour": "red"})));
+        assert_eq!(unknown["error"]["kind"], "internal");
+        let message = unknown["error"]["message"].as_str().unwrap();
+        assert!(
+            message.starts_with("could not encode the response: unknown field `colour`"),
+            "{message}"
+        );
+    }
+
+    #[test]
+    fn round_trips_problems() {
+        let problem = json!({
+            "id": "knapsack",
+            "direction": "max",
+            "variables": [{"name": "a", "binary": true}, {"name": "b", "min": "-inf", "max": 2.5, "integer": true}],
+            "objective": [{"name": "a", "factor": 3}, {"name": "b", "factor": 2}],
+            "rows": [{"terms": [{"name": "a", "factor": 2}, {"name": "b", "factor": 1}], "op": "<=", "rhs": 4}],
+            "sensed_rows": {"terms": [[{"index": 1, "factor": 1}]], "senses": [">="], "rhs": [1]},
+            "constraint_templates": [{"name": "t", "index": "i", "range": [0, 1], "terms": [{"name": "a{i}", "factor": 1}], "op": "==", "rhs": 1}],
+            "options": {"pretty_output": true, "decimal_places": 3, "raw_options": {"random_seed": "3"}}
+        });
+        assert_eq!(
+            normal(&decode(&super::tests::problem(&problem)).unwrap()),
+            normal(&problem)
+        );
+    }
+
+    /// The value with numbers as doubles and without empty lists, like the
+    /// messages hold it.
+    fn normal(value: &Value) -> Value {
+        match value {
+            Value::Number(n) => json!(n.as_f64()),
+            Value::Array(items) => items.iter().map(normal).collect(),
+            Value::Object(map) => map
+                .iter()
+                .filter(|(_, value)| **value != json!([]))
+                .map(|(key, value)| (key.clone(), normal(value)))
+                .collect(),
+            _ => value.clone(),
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_messages() {
+        let error = |bytes: &str| decode(&hex(bytes)).unwrap_err();
+        assert_eq!(
+            error("0801"),
+            "Problem.id: invalid wire type: Varint (expected LengthDelimited)"
+        );
+        assert_eq!(error("0a0561"), "Problem.id: buffer underflow");
+        assert_eq!(error("1807"), "unknown enum value 7");
+        assert_eq!(
+            error("0a01ff"),
+            "Problem.id: invalid string value: data is not UTF-8 encoded"
+        );
+        assert_eq!(
+            error("2203 0a01ff"),
+            "Variable.name: Problem.variables: invalid string value: data is not UTF-8 encoded"
+        );
+        assert_eq!(error("5100000000 0000f8ff"), "NaN is not a number");
+        assert_eq!(error("a706"), "invalid wire type value: 7");
+        assert_eq!(error("ffffffffffffffffffff01"), "invalid varint");
+    }
+
+    /// Solves through the C entry point and decodes the `Response`.
+    fn solve(bytes: &[u8]) -> Value {
+        let mut len = 0;
+        let response = unsafe { crate::solve_proto(bytes.as_ptr(), bytes.len(), &raw mut len) };
+        let value = super::tests::response(unsafe { std::slice::from_raw_parts(response, len) });
+        unsafe { crate::free_proto(response, len) };
+        value
+    }
+
+    #[test]
+    fn solves_like_json() {
+        let problem = json!({
+            "direction": "max",
+            "variables": [{"name": "a", "binary": true}, {"name": "b", "max": 2, "integer": true}],
+            "objective": [{"name": "a", "factor": 3}, {"name": "b", "factor": 2}],
+            "rows": [{"terms": [{"name": "a", "factor": 2}, {"name": "b", "factor": 1}], "op": "<=", "rhs": 4}],
+            "output": "named",
+            "verbose": false
+        });
+        let solution = &solve(&super::tests::problem(&problem))["solution"];
+        let input = problem.to_string();
+        let response = unsafe { crate::solve(input.as_ptr(), input.len()) };
+        let text = unsafe { std::ffi::CStr::from_ptr(response) };
+        let json: Value = serde_json::from_slice(text.to_bytes()).unwrap();
+        unsafe { crate::free_string(response.cast_mut()) };
+        assert_eq!(solution["status"], "optimal");
+        assert_eq!(solution["objective"], 7.0);
+        for key in [
+            "objective",
+            "values",
+            "named_values",
+            "activities",
+            "slacks",
+        ] {
+            assert_eq!(normal(&solution[key]), normal(&json[key]), "{key}");
+        }
+
+        let error = &solve(&hex("0a01ff"))["error"];
+        assert_eq!(error["kind"], "parse");
+        assert_eq!(
+            error["message"],
+            "invalid protobuf: Problem.id: invalid string value: data is not UTF-8 encoded"
+        );
+    }
+}
@@ -0,0 +1,511 @@
+//! Queries against the HiGHS C API that the `highs` crate does not expose.
+
+use crate::Range;
+use crate::dump::Lp;
+use highs::{Model, SolvedModel};
+use highs_sys::{
+    HighsInt, kHighsHessianFormatTriangular, kHighsMatrixFormatRowwise, kHighsMaximumStringLength,
+    kHighsObjSenseMaximize, kHighsObjSenseMinimize, kHighsStatusError, kHighsStatusOk,
+    kHighsVarTypeImplicitInteger, kHighsVarTypeInteger, kHighsVarTypeSemiContinuous,
+    kHighsVarTypeSemiInteger,
+};
+use std::collections::BTreeMap;
+use std::ffi::{CStr, CString};
+use std::ptr::{null, null_mut};
+
+/// Reads an integer info value such as `simplex_iteration_count`.
+pub fn int_info(model: &SolvedModel, name: &CStr) -> Option<HighsInt> {
+    let mut value = 0;
+    let status =
+        unsafe { highs_sys::Highs_getIntInfoValue(model.as_ptr(), name.as_ptr(), &raw mut value) };
+    (status == kHighsStatusOk).then_some(value)
+}
+
+/// Reads a 64-bit integer info value such as `mip_node_count`.
+pub fn int64_info(model: &SolvedModel, name: &CStr) -> Option<i64> {
+    let mut value = 0;
+    let status = unsafe {
+        highs_sys::Highs_getInt64InfoValue(model.as_ptr(), name.as_ptr(), &raw mut value)
+    };
+    (status == kHighsStatusOk).then_some(value)
+}
+
+/// Reads a floating point info value such as `mip_gap`.
+pub fn double_info(model: &SolvedModel, name: &CStr) -> Option<f64> {
+    let mut value = 0.0;
+    let status = unsafe {
+        highs_sys::Highs_getDoubleInfoValue(model.as_ptr(), name.as_ptr(), &raw mut value)
+    };
+    (status == kHighsStatusOk).then_some(value)
+}
+
+/// Reads the current value of an integer option such as `mip_max_nodes`.
+pub fn int_option(model: &SolvedModel, name: &CStr) -> Option<HighsInt> {
+    let mut value = 0;
+    let status = unsafe {
+        highs_sys::Highs_getIntOptionValue(model.as_ptr(), name.as_ptr(), &raw mut value)
+    };
+    (status == kHighsStatusOk).then_some(value)
+}
+
+/// Reads the current value of a floating point option such as
+/// `mip_feasibility_tolerance`.
+pub fn double_option(model: &SolvedModel, name: &CStr) -> Option<f64> {
+    let mut value = 0.0;
+    let status = unsafe {
+        highs_sys::Highs_getDoubleOptionValue(model.as_ptr(), name.as_ptr(), &raw mut value)
+    };
+    (status == kHighsStatusOk).then_some(value)
+}
+
+/// Sets an option by its name to a boolean, a number or a string, which is
+/// read like in an options file. Returns whether HiGHS knows the option and
+/// accepts the value.
+pub fn set_option(model: &mut Model, name: &str, value: &serde_json::Value) -> bool {
+    use serde_json::Value;
+    let Ok(name) = CString::new(name) else {
+        return false;
+    };
+    let (ptr, name) = (model.as_mut_ptr(), name.as_ptr());
+    let mut kind = 0;
+    if unsafe { highs_sys::Highs_getOptionType(ptr, name, &raw mut kind) } != kHighsStatusOk {
+        return false;
+    }
+    let status = match (kind, value) {
+        (highs_sys::kHighsOptionTypeBool, &Value::Bool(value)) => unsafe {
+            highs_sys::Highs_setBoolOptionValue(ptr, name, HighsInt::from(value))
+        },
+        (highs_sys::kHighsOptionTypeInt, Value::Number(n)) => {
+            let Some(value) = n.as_i64().and_then(|n| HighsInt::try_from(n).ok()) else {
+                return false;
+            };
+            unsafe { highs_sys::Highs_setIntOptionValue(ptr, name, value) }
+        }
+        (highs_sys::kHighsOptionTypeDouble, Value::Number(n)) => {
+            let Some(value) = n.as_f64() else {
+                return false;
+            };
+            unsafe { highs_sys::Highs_setDoubleOptionValue(ptr, name, value) }
+        }
+        // HiGHS parses strings for options of every type
+        (_, Value::String(value)) => {
+            let Ok(value) = CString::new(value.as_str()) else {
+                return false;
+            };
+            unsafe { highs_sys::Highs_setStringOptionValue(ptr, name, value.as_ptr()) }
+        }
+        _ => return false,
+    };
+    status == kHighsStatusOk
+}
+
+/// Reads the current value of a string option such as `presolve`.
+pub fn string_option(model: &Model, name: &CStr) -> Option<String> {
+    let mut value = vec![0; usize::try_from(kHighsMaximumStringLength).ok()?];
+    let status = unsafe {
+        highs_sys::Highs_getStringOptionValue(model.as_ptr(), name.as_ptr(), value.as_mut_ptr())
+    };
+    let value = unsafe { CStr::from_ptr(value.as_ptr()) };
+    (status == kHighsStatusOk).then(|| value.to_string_lossy().into_owned())
+}
+
+/// Reads the basis status of every column and row, if HiGHS has a valid
+/// basis.
+pub fn basis(
+    model: &SolvedModel,
+    num_cols: usize,
+    num_rows: usize,
+) -> Option<(Vec<HighsInt>, Vec<HighsInt>)> {
+    let mut cols = vec![0; num_cols];
+    let mut rows = vec![0; num_rows];
+    let status =
+        unsafe { highs_sys::Highs_getBasis(model.as_ptr(), cols.as_mut_ptr(), rows.as_mut_ptr()) };
+    (status == kHighsStatusOk).then_some((cols, rows))
+}
+
+/// Reads the first `num_cols` entries of a primal ray proving unboundedness,
+/// if HiGHS found one.
+pub fn primal_ray(model: &SolvedModel, num_cols: usize) -> Option<Vec<f64>> {
+    let mut has_ray = 0;
+    // HiGHS also writes the auxiliary columns
+    let all_cols = unsafe { highs_sys::Highs_getNumCol(model.as_ptr()) };
+    let mut ray = vec![0.0; usize::try_from(all_cols).ok()?.max(num_cols)];
+    let status = unsafe {
+        highs_sys::Highs_getPrimalRay(model.as_ptr(), &raw mut has_ray, ray.as_mut_ptr())
+    };
+    ray.truncate(num_cols);
+    (status == kHighsStatusOk && has_ray != 0).then_some(ray)
+}
+
+/// Reads the ranging of every column cost and every row bound. Only
+/// meaningful for LPs solved to optimality.
+pub fn ranging(
+    model: &mut SolvedModel,
+    num_cols: usize,
+    num_rows: usize,
+) -> Option<(Vec<Range>, Vec<Range>)> {
+    // value, objective, entering and leaving variable for each direction
+    let values = |n| [vec![0.0; n], vec![0.0; n], vec![0.0; n], vec![0.0; n]];
+    let vars = |n| [vec![0; n], vec![0; n], vec![0; n], vec![0; n]];
+    let [mut cost_up, mut cost_up_obj, mut cost_dn, mut cost_dn_obj] = values(num_cols);
+    let [
+        mut cost_up_in,
+        mut cost_up_out,
+        mut cost_dn_in,
+        mut cost_dn_out,
+    ] = vars(num_cols);
+    let [
+        mut bound_up,
+        mut bound_up_obj,
+        mut bound_dn,
+        mut bound_dn_obj,
+    ] = values(num_cols);
+    let [
+        mut bound_up_in,
+        mut bound_up_out,
+        mut bound_dn_in,
+        mut bound_dn_out,
+    ] = vars(num_cols);
+    let [mut row_up, mut row_up_obj, mut row_dn, mut row_dn_obj] = values(num_rows);
+    let [mut row_up_in, mut row_up_out, mut row_dn_in, mut row_dn_out] = vars(num_rows);
+    let status = unsafe {
+        highs_sys::Highs_getRanging(
+            model.as_mut_ptr(),
+            cost_up.as_mut_ptr(),
+            cost_up_obj.as_mut_ptr(),
+            cost_up_in.as_mut_ptr(),
+            cost_up_out.as_mut_ptr(),
+            cost_dn.as_mut_ptr(),
+            cost_dn_obj.as_mut_ptr(),
+            cost_dn_in.as_mut_ptr(),
+            cost_dn_out.as_mut_ptr(),
+            bound_up.as_mut_ptr(),
+            bound_up_obj.as_mut_ptr(),
+            bound_up_in.as_mut_ptr(),
+            bound_up_out.as_mut_ptr(),
+            bound_dn.as_mut_ptr(),
+            bound_dn_obj.as_mut_ptr(),
+            bound_dn_in.as_mut_ptr(),
+            bound_dn_out.as_mut_ptr(),
+            row_up.as_mut_ptr(),
+            row_up_obj.as_mut_ptr(),
+            row_up_in.as_mut_ptr(),
+            row_up_out.as_mut_ptr(),
+            row_dn.as_mut_ptr(),
+            row_dn_obj.as_mut_ptr(),
+            row_dn_in.as_mut_ptr(),
+            row_dn_out.as_mut_ptr(),
+        )
+    };
+    let pairs = |dn: Vec<f64>, up: Vec<f64>| dn.into_iter().zip(up).map(|(d, u)| [d, u]).collect();
+    (status == kHighsStatusOk).then(|| (pairs(cost_dn, cost_up), pairs(row_dn, row_up)))
+}
+
+/// Appends the row `lower <= sum(values[i] * x[index[i]]) <= upper`.
+pub fn add_row(
+    model: &mut Model,
+    lower: f64,
+    upper: f64,
+    index: &[HighsInt],
+    values: &[f64],
+) -> bool {
+    let Ok(len) = HighsInt::try_from(index.len()) else {
+        return false;
+    };
+    let status = unsafe {
+        highs_sys::Highs_addRow(
+            model.as_mut_ptr(),
+            lower,
+            upper,
+            len,
+            index.as_ptr(),
+            values.as_ptr(),
+        )
+    };
+    status == kHighsStatusOk
+}
+
+/// Hands HiGHS the values of the first columns as a starting solution, with
+/// infinity for those without a value. The other columns, like those
+/// appended for SOS or piecewise terms, are left for HiGHS to complete.
+pub fn set_start(model: &mut Model, values: &[f64]) -> bool {
+    let ptr = model.as_mut_ptr();
+    let Ok(num_cols) = usize::try_from(unsafe { highs_sys::Highs_getNumCol(ptr) }) else {
+        return false;
+    };
+    // infinity is what HiGHS takes for undefined values
+    let mut col_value = values.to_vec();
+    col_value.resize(num_cols.max(values.len()), f64::INFINITY);
+    let status =
+        unsafe { highs_sys::Highs_setSolution(ptr, col_value.as_ptr(), null(), null(), null()) };
+    status != kHighsStatusError
+}
+
+/// Appends a column outside of all rows and returns its index.
+pub fn add_column(model: &mut Model, cost: f64, lower: f64, upper: f64) -> Option<HighsInt> {
+    let ptr = model.as_mut_ptr();
+    let status = unsafe { highs_sys::Highs_addCol(ptr, cost, lower, upper, 0, null(), null()) };
+    (status == kHighsStatusOk).then(|| unsafe { highs_sys::Highs_getNumCol(ptr) } - 1)
+}
+
+/// Appends a binary column without cost and returns its index.
+pub fn add_binary(model: &mut Model) -> Option<HighsInt> {
+    let col = add_column(model, 0.0, 0.0, 1.0)?;
+    let ptr = model.as_mut_ptr();
+    let status = unsafe { highs_sys::Highs_changeColIntegrality(ptr, col, kHighsVarTypeInteger) };
+    (status == kHighsStatusOk).then_some(col)
+}
+
+/// Objective factors of all columns.
+pub fn costs(model: &Model) -> Option<Vec<f64>> {
+    let ptr = model.as_ptr();
+    let num_cols = unsafe { highs_sys::Highs_getNumCol(ptr) };
+    let mut costs = vec![0.0; usize::try_from(num_cols).ok()?];
+    if num_cols == 0 {
+        return Some(costs);
+    }
+    let (mut got, mut num_nz) = (0, 0);
+    let status = unsafe {
+        highs_sys::Highs_getColsByRange(
+            ptr,
+            0,
+            num_cols - 1,
+            &raw mut got,
+            costs.as_mut_ptr(),
+            null_mut(),
+            null_mut(),
+            &raw mut num_nz,
+            null_mut(),
+            null_mut(),
+            null_mut(),
+        )
+    };
+    (status == kHighsStatusOk).then_some(costs)
+}
+
+/// Replaces the objective factors of all columns and the direction.
+pub fn set_objective(model: &mut Model, costs: &[f64], maximise: bool) -> bool {
+    let ptr = model.as_mut_ptr();
+    let sense = if maximise {
+        kHighsObjSenseMaximize
+    } else {
+        kHighsObjSenseMinimize
+    };
+    if unsafe { highs_sys::Highs_changeObjectiveSense(ptr, sense) } != kHighsStatusOk {
+        return false;
+    }
+    let Ok(num_cols) = HighsInt::try_from(costs.len()) else {
+        return false;
+    };
+    num_cols == 0
+        || unsafe { highs_sys::Highs_changeColsCostByRange(ptr, 0, num_cols - 1, costs.as_ptr()) }
+            == kHighsStatusOk
+}
+
+/// Sets the quadratic part of the objective from terms `factor * a * b` over
+/// column indices. It covers all columns, so it has to be set last.
+pub fn set_hessian(model: &mut Model, terms: &[(usize, usize, f64)]) -> bool {
+    let ptr = model.as_mut_ptr();
+    let dim = unsafe { highs_sys::Highs_getNumCol(ptr) };
+    let Ok(num_cols) = usize::try_from(dim) else {
+        return false;
+    };
+    // HiGHS minimises 1/2 x'Qx and takes the lower triangle of Q by column
+    let mut columns = vec![BTreeMap::new(); num_cols];
+    for &(a, b, factor) in terms {
+        let value = if a == b { 2.0 * factor } else { factor };
+        *columns[a.min(b)].entry(a.max(b)).or_insert(0.0) += value;
+    }
+    let (mut start, mut index, mut value) = (vec![], vec![], vec![]);
+    for column in columns {
+        start.push(index.len());
+        for (row, v) in column {
+            index.push(row);
+            value.push(v);
+        }
+    }
+    let ints = |v: Vec<usize>| {
+        v.into_iter()
+            .map(HighsInt::try_from)
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let (Ok(start), Ok(index)) = (ints(start), ints(index)) else {
+        return false;
+    };
+    let Ok(num_nz) = HighsInt::try_from(value.len()) else {
+        return false;
+    };
+    let status = unsafe {
+        highs_sys::Highs_passHessian(
+            ptr,
+            dim,
+            num_nz,
+            kHighsHessianFormatTriangular,
+            start.as_ptr(),
+            index.as_ptr(),
+            value.as_ptr(),
+        )
+    };
+    status == kHighsStatusOk
+}
+
+/// Lets a column be zero or within its bounds. It takes integer values
+/// within its bounds if `integer` is set.
+pub fn set_semicontinuous(model: &mut Model, col: HighsInt, integer: bool) -> bool {
+    let var_type = if integer {
+        kHighsVarTypeSemiInteger
+    } else {
+        kHighsVarTypeSemiContinuous
+    };
+    let ptr = model.as_mut_ptr();
+    unsafe { highs_sys::Highs_changeColIntegrality(ptr, col, var_type) == kHighsStatusOk }
+}
+
+/// Reads back everything about the model but its quadratic objective terms.
+pub fn lp(model: &Model) -> Option<Lp> {
+    let ptr = model.as_ptr();
+    let (num_cols, num_rows, num_nz) = unsafe {
+        (
+            highs_sys::Highs_getNumCol(ptr),
+            highs_sys::Highs_getNumRow(ptr),
+            highs_sys::Highs_getNumNz(ptr),
+        )
+    };
+    let len = |n: HighsInt| usize::try_from(n).ok();
+    let (cols, rows, nz) = (len(num_cols)?, len(num_rows)?, len(num_nz)?);
+    let (mut costs, mut col_lower, mut col_upper) =
+        (vec![0.0; cols], vec![0.0; cols], vec![0.0; cols]);
+    let (mut row_lower, mut row_upper) = (vec![0.0; rows], vec![0.0; rows]);
+    // with one more start so that each row ends where the next one starts
+    let (mut start, mut index, mut value) = (vec![0; rows + 1], vec![0; nz], vec![0.0; nz]);
+    let mut integrality = vec![0; cols];
+    let (mut got_cols, mut got_rows, mut got_nz, mut sense, mut offset) = (0, 0, 0, 0, 0.0);
+    let status = unsafe {
+        highs_sys::Highs_getLp(
+            ptr,
+            kHighsMatrixFormatRowwise,
+            &raw mut got_cols,
+            &raw mut got_rows,
+            &raw mut got_nz,
+            &raw mut sense,
+            &raw mut offset,
+            costs.as_mut_ptr(),
+            col_lower.as_mut_ptr(),
+            col_upper.as_mut_ptr(),
+            row_lower.as_mut_ptr(),
+            row_upper.as_mut_ptr(),
+            start.as_mut_ptr(),
+            index.as_mut_ptr(),
+            value.as_mut_ptr(),
+            integrality.as_mut_ptr(),
+        )
+    };
+    if status != kHighsStatusOk {
+        return None;
+    }
+    start[rows] = num_nz;
+    let entries = start
+        .windows(2)
+        .map(|w| {
+            let range = len(w[0])?..len(w[1])?;
+            let index = index.get(range.clone())?.iter().map(|&i| len(i));
+            index
+                .zip(&value[range])
+                .map(|(i, &v)| Some((i?, v)))
+                .collect()
+        })
+        .collect::<Option<_>>()?;
+    let var_type = |types: &[HighsInt]| integrality.iter().map(|t| types.contains(t)).collect();
+    Some(Lp {
+        maximise: sense == kHighsObjSenseMaximize,
+        costs,
+        columns: col_lower.into_iter().zip(col_upper).collect(),
+        integer: var_type(&[
+            kHighsVarTypeInteger,
+            kHighsVarTypeSemiInteger,
+            kHighsVarTypeImplicitInteger,
+        ]),
+        semicontinuous: var_type(&[kHighsVarTypeSemiContinuous, kHighsVarTypeSemiInteger]),
+        rows: row_lower.into_iter().zip(row_upper).collect(),
+        entries,
+    })
+}
+
+/// A temporary file that HiGHS writes its log to, removed when dropped. It
+/// is created exclusively and only its owner may read it, so that no one
+/// else can read the log or put a file of their own in its place. The
+/// logging callback of the C API cannot capture the log, since HiGHS passes
+/// no input data to it for log messages and the wrapper dereferences that.
+pub struct LogFile(tempfile::NamedTempFile);
+
+impl LogFile {
+    /// Sends the log of `model` to a new file instead of the console.
+    pub fn capture(model: &mut Model) -> Option<Self> {
+        let file = tempfile::Builder::new()
+            .prefix("knorpelsolve-")
+            .suffix(".log")
+            .tempfile()
+            .ok()?;
+        let path = serde_json::Value::String(file.path().to_str()?.to_owned());
+        let captured = set_option(model, "output_flag", &true.into())
+            && set_option(model, "log_to_console", &false.into())
+            && set_option(model, "log_file", &path);
+        captured.then_some(LogFile(file))
+    }
+
+    /// Reads the log once the model is dropped, which closes the file.
+    pub fn read(&self) -> Option<String> {
+        std::fs::read_to_string(self.0.path()).ok()
+    }
+}
+
+/// Presolves the model before it is solved and returns how many rows,
+/// columns, and nonzeros presolve removed, which is nothing if presolve is
+/// off. The model itself stays as it is.
+pub fn presolve_reductions(model: &mut Model) -> Option<[HighsInt; 3]> {
+    // presolving explicitly ignores the option
+    if string_option(model, c"presolve").as_deref() == Some("off") {
+        return Some([0; 3]);
+    }
+    let ptr = model.as_mut_ptr();
+    let original = unsafe {
+        [
+            highs_sys::Highs_getNumRow(ptr),
+            highs_sys::Highs_getNumCol(ptr),
+            highs_sys::Highs_getNumNz(ptr),
+        ]
+    };
+    if unsafe { highs_sys::Highs_presolve(ptr) } != kHighsStatusOk {
+        return None;
+    }
+    let presolved = unsafe {
+        [
+            highs_sys::Highs_getPresolvedNumRow(ptr),
+            highs_sys::Highs_getPresolvedNumCol(ptr),
+            highs_sys::Highs_getPresolvedNumNz(ptr),
+        ]
+    };
+    Some([0, 1, 2].map(|i| original[i] - presolved[i]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_files_are_private_and_removed() {
+        let mut model = highs::RowProblem::default().optimise(highs::Sense::Minimise);
+        let log = LogFile::capture(&mut model).unwrap();
+        let path = log.0.path().to_owned();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        drop(model.solve());
+        assert!(log.read().unwrap().contains("HiGHS"));
+        drop(log);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,341 @@
+//! Minimal HTTP server that answers `POST /solve` with the JSON protocol,
+//! for deployments that would otherwise wrap the C ABI in a sidecar.
+
+use crate::{ErrorKind, JSON, Reply, answer_decoded, compressed};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Condvar, Mutex, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+const MAX_HEAD: u64 = 64 << 10; // bytes of the request line and headers
+const TIMEOUT: Duration = Duration::from_secs(30); // for reading and writing
+
+/// Settings of a [`Server`].
+pub struct Config {
+    pub address: String, // to bind to, like 127.0.0.1:8080
+    // replaces the time limit of every request if set
+    pub time_limit_seconds: Option<f64>,
+    pub max_concurrent: usize, // problems that are solved at the same time
+    // connections that are open at the same time, further ones wait to be
+    // accepted
+    pub max_connections: usize,
+    pub max_body_bytes: usize, // at most `max_concurrent` bodies are held at once
+}
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            address: "127.0.0.1:8080".to_owned(),
+            time_limit_seconds: None,
+            max_concurrent: thread::available_parallelism().map_or(1, usize::from),
+            max_connections: 64,
+            max_body_bytes: 64 << 20,
+        }
+    }
+}
+
+/// HTTP response with a JSON body.
+pub struct Response {
+    pub status: u16,
+    pub body: Vec<u8>,
+    pub gzip: bool, // the body is compressed because of `compress_output`
+}
+impl Response {
+    fn error(status: u16, message: &str) -> Self {
+        let body = serde_json::json!({"status": "error", "kind": "parse", "message": message});
+        Response {
+            status,
+            body: body.to_string().into_bytes(),
+            gzip: false,
+        }
+    }
+}
+
+pub struct Server {
+    config: Config,
+    permits: Semaphore,
+    connections: Semaphore,
+}
+impl Server {
+    #[must_use]
+    pub fn new(config: Config) -> Self {
+        let permits = Semaphore::new(config.max_concurrent.max(1));
+        let connections = Semaphore::new(config.max_connections.max(1));
+        Server {
+            config,
+            permits,
+            connections,
+        }
+    }
+
+    /// Answers a request without any I/O. Solutions are returned with 200
+    /// whatever their status, parse and validation errors with 400, problems
+    /// over the memory limit with 413, and solver and internal errors with
+    /// 500.
+    pub fn handle(&self, method: &str, path: &str, body: &[u8]) -> Response {
+        if let Err(response) = route(method, path) {
+            return response;
+        }
+        let _permit = self.permits.acquire();
+        self.solve(body)
+    }
+
+    /// Answers the body of a `POST /solve` while holding a permit.
+    fn solve(&self, body: &[u8]) -> Response {
+        let mut overrides = serde_json::Map::new();
+        if let Some(seconds) = self.config.time_limit_seconds {
+            overrides.insert("time_limit_seconds".to_owned(), seconds.into());
+        }
+        let (reply, options) = answer_decoded(body, &JSON, &overrides);
+        let status = match &reply {
+            Reply::Error(e) => match e.kind {
+                ErrorKind::Parse | ErrorKind::Validation => 400,
+                ErrorKind::ResourceLimit => 413,
+                ErrorKind::Solver | ErrorKind::Internal => 500,
+            },
+            Reply::Solution(_) => 200,
+        };
+        let body = if options.pretty_output {
+            serde_json::to_vec_pretty(&reply)
+        } else {
+            serde_json::to_vec(&reply)
+        };
+        let body = body.unwrap_or_else(|_| crate::INTERNAL_ERROR.into());
+        Response {
+            status,
+            body: compressed(body, &options),
+            gzip: options.compress_output,
+        }
+    }
+
+    /// Binds to the configured address and answers requests until
+    /// accepting connections fails, each on its own thread. At most
+    /// `max_connections` are open at the same time.
+    ///
+    /// # Errors
+    /// Fails if the address cannot be bound or accepting fails.
+    pub fn serve(self) -> io::Result<()> {
+        let listener = TcpListener::bind(&self.config.address)?;
+        let server = &self;
+        thread::scope(|scope| {
+            loop {
+                let connection = server.connections.acquire();
+                let (stream, _) = listener.accept()?;
+                scope.spawn(move || {
+                    let _connection = connection;
+                    // the client is gone if writing fails, so there is no one to tell
+                    let _ = server.connection(stream);
+                });
+            }
+        })
+    }
+
+    /// Answers the single request of a connection, which is closed after.
+    fn connection(&self, stream: TcpStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let response = self.read_and_answer(&mut reader);
+        write_response(stream, &response)
+    }
+
+    /// Reads a request and answers it. The body is only read once a permit
+    /// is held, so that at most `max_concurrent` bodies are in memory.
+    fn read_and_answer(&self, reader: &mut impl BufRead) -> Response {
+        let (method, path, length) = match self.read_head(reader) {
+            Ok(head) => head,
+            Err(response) => return response,
+        };
+        if let Err(response) = route(&method, &path) {
+            return response;
+        }
+        let _permit = self.permits.acquire();
+        let mut body = vec![0; length];
+        if reader.read_exact(&mut body).is_err() {
+            return Response::error(400, "could not read the request");
+        }
+        self.solve(&body)
+    }
+
+    /// Reads the method, the path and the length of the body, or the
+    /// response to a request that cannot be read.
+    fn read_head(&self, reader: &mut impl BufRead) -> Result<(String, String, usize), Response> {
+        let bad = |_| Response::error(400, "could not read the request");
+        let mut head = reader.by_ref().take(MAX_HEAD);
+        let mut line = String::new();
+        head.read_line(&mut line).map_err(bad)?;
+        let mut parts = line.split_whitespace();
+        let (Some(method), Some(path), Some(_version)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(Response::error(400, "invalid request line"));
+        };
+        let (method, path) = (method.to_owned(), path.to_owned());
+        let mut length = None;
+        loop {
+            line.clear();
+            if head.read_line(&mut line).map_err(bad)? == 0 {
+                return Err(Response::error(431, "request headers too large"));
+            }
+            let header = line.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            let Some((name, value)) = header.split_once(':') else {
+                return Err(Response::error(400, "invalid header"));
+            };
+            if name.eq_ignore_ascii_case("content-length") {
+                let value = value.trim().parse::<usize>();
+                length = Some(value.map_err(|_| Response::error(400, "invalid Content-Length"))?);
+            } else if name.eq_ignore_ascii_case("transfer-encoding") {
+                return Err(Response::error(411, "chunked bodies are not supported"));
+            }
+        }
+        let length = length.unwrap_or(0);
+        if length > self.config.max_body_bytes {
+            let message = format!("body larger than {} bytes", self.config.max_body_bytes);
+            return Err(Response::error(413, &message));
+        }
+        Ok((method, path, length))
+    }
+}
+
+/// Rejects requests other than `POST /solve`.
+fn route(method: &str, path: &str) -> Result<(), Response> {
+    if path != "/solve" {
+        return Err(Response::error(404, "not found, use POST /solve"));
+    }
+    if method != "POST" {
+        return Err(Response::error(405, "method not allowed, use POST /solve"));
+    }
+    Ok(())
+}
+
+fn write_response(mut stream: TcpStream, response: &Response) -> io::Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        411 => "Length Required",
+        413 => "Content Too Large",
+        431 => "Request Header Fields Too Large",
+        _ => "Internal Server Error",
+    };
+    let mut head = format!(
+        "HTTP/1.1 {} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        response.body.len()
+    );
+    if response.status == 405 {
+        head.push_str("Allow: POST\r\n");
+    }
+    if response.gzip {
+        head.push_str("Content-Encoding: gzip\r\n");
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(&response.body)?;
+    stream.flush()
+}
+
+/// Counting semaphore, as the standard library has none.
+struct Semaphore {
+    permits: Mutex<usize>,
+    released: Condvar,
+}
+/// Permit of a [`Semaphore`] that is given back when dropped.
+struct Permit<'a>(&'a Semaphore);
+impl Semaphore {
+    fn new(permits: usize) -> Self {
+        Semaphore {
+            permits: Mutex::new(permits),
+            released: Condvar::new(),
+        }
+    }
+    fn acquire(&self) -> Permit<'_> {
+        let permits = self.permits.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut permits = self
+            .released
+            .wait_while(permits, |permits| *permits == 0)
+            .unwrap_or_else(PoisonError::into_inner);
+        *permits -= 1;
+        Permit(self)
+    }
+}
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        let mut permits = self
+            .0
+            .permits
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        *permits += 1;
+        self.0.released.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    fn server(max_body_bytes: usize) -> Server {
+        Server::new(Config {
+            max_body_bytes,
+            ..Config::default()
+        })
+    }
+
+    fn answer(server: &Server, request: &str) -> Response {
+        server.read_and_answer(&mut request.as_bytes())
+    }
+
+    #[test]
+    fn other_routes_are_answered_before_the_body() {
+        let server = server(1 << 10);
+        // the bodies are missing, so reading them would fail with 400
+        let response = answer(&server, "POST /other HTTP/1.1\r\nContent-Length: 9\r\n\r\n");
+        assert_eq!(response.status, 404);
+        let response = answer(&server, "GET /solve HTTP/1.1\r\nContent-Length: 9\r\n\r\n");
+        assert_eq!(response.status, 405);
+    }
+
+    #[test]
+    fn large_bodies_are_rejected_before_reading_them() {
+        let response = answer(
+            &server(8),
+            "POST /solve HTTP/1.1\r\nContent-Length: 9\r\n\r\n",
+        );
+        assert_eq!(response.status, 413);
+    }
+
+    #[test]
+    fn unreadable_requests_are_rejected() {
+        let server = server(1 << 10);
+        assert_eq!(answer(&server, "POST\r\n\r\n").status, 400);
+        let request = "POST /solve HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n";
+        assert_eq!(answer(&server, request).status, 411);
+        let request = "POST /solve HTTP/1.1\r\nContent-Length: x\r\n\r\n";
+        assert_eq!(answer(&server, request).status, 400);
+        assert_eq!(answer(&server, "POST /solve HTTP/1.1\r\n").status, 431);
+        let request = "POST /solve HTTP/1.1\r\nContent-Length: 9\r\n\r\n{}";
+        assert_eq!(answer(&server, request).status, 400);
+    }
+
+    #[test]
+    fn semaphore_waits_for_a_permit() {
+        let semaphore = Semaphore::new(1);
+        let permit = semaphore.acquire();
+        let (acquired, waiting) = mpsc::channel();
+        thread::scope(|scope| {
+            scope.spawn(|| {
+                let _permit = semaphore.acquire();
+                acquired.send(()).unwrap();
+            });
+            let timeout = Duration::from_millis(50);
+            assert!(waiting.recv_timeout(timeout).is_err());
+            drop(permit);
+            assert!(waiting.recv_timeout(TIMEOUT).is_ok());
+        });
+    }
+}
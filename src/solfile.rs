@@ -0,0 +1,130 @@
+//! Writer of solutions in the format of HiGHS solution files, for tools
+//! that read those instead of the JSON response.
+
+use crate::{MessageSolution, Value};
+use highs::HighsModelStatus;
+use std::fmt::Write;
+
+/// Writes the model status, the primal and the dual solution, and an
+/// invalid basis, like `Highs::writeSolution` in its default style. Rows
+/// without a name are called `R0`, `R1` and so on.
+pub fn write(
+    res: &MessageSolution,
+    status: HighsModelStatus,
+    columns: &[String],
+    rows: &[Option<String>],
+) -> String {
+    let mut text = format!("Model status\n{}\n\n", status_text(status));
+    text.push_str("# Primal solution values\n");
+    if let Some(activities) = res.activities.as_ref().filter(|_| !res.values.is_empty()) {
+        let values: Vec<_> = res.values.iter().map(number).collect();
+        text.push_str("Feasible\n");
+        let _ = writeln!(text, "Objective {}", res.objective.unwrap_or(0.0));
+        section(&mut text, columns, rows, &values, activities);
+    } else {
+        text.push_str("None\n");
+    }
+    text.push_str("\n# Dual solution values\n");
+    if let (Some(reduced_costs), Some(duals)) = (&res.reduced_costs, &res.duals) {
+        text.push_str("Feasible\n");
+        section(&mut text, columns, rows, reduced_costs, duals);
+    } else {
+        text.push_str("None\n");
+    }
+    text.push_str("\n# Basis\nHiGHS v1\nNone\n");
+    text
+}
+
+/// Writes one value per column and per row under their names.
+fn section(
+    text: &mut String,
+    columns: &[String],
+    rows: &[Option<String>],
+    column_values: &[f64],
+    row_values: &[f64],
+) {
+    let _ = writeln!(text, "# Columns {}", column_values.len());
+    for (name, value) in columns.iter().zip(column_values) {
+        let _ = writeln!(text, "{name} {value}");
+    }
+    let _ = writeln!(text, "# Rows {}", row_values.len());
+    for (i, value) in row_values.iter().enumerate() {
+        let _ = match rows.get(i).and_then(Option::as_ref) {
+            Some(name) => writeln!(text, "{name} {value}"),
+            None => writeln!(text, "R{i} {value}"),
+        };
+    }
+}
+
+fn number(value: &Value) -> f64 {
+    match *value {
+        Value::Continuous(v) => v,
+        #[allow(clippy::cast_precision_loss)] // only rounded values are integers
+        Value::Integer(n) => n as f64,
+        Value::NonFinite(s) => s.parse().unwrap_or(f64::NAN),
+    }
+}
+
+/// The name that HiGHS gives the status in its files and logs.
+fn status_text(status: HighsModelStatus) -> &'static str {
+    match status {
+        HighsModelStatus::NotSet => "Not Set",
+        HighsModelStatus::LoadError => "Load error",
+        HighsModelStatus::ModelError => "Model error",
+        HighsModelStatus::PresolveError => "Presolve error",
+        HighsModelStatus::SolveError => "Solve error",
+        HighsModelStatus::PostsolveError => "Postsolve error",
+        HighsModelStatus::ModelEmpty => "Empty",
+        HighsModelStatus::Infeasible => "Infeasible",
+        HighsModelStatus::UnboundedOrInfeasible => "Primal infeasible or unbounded",
+        HighsModelStatus::Unbounded => "Unbounded",
+        HighsModelStatus::Optimal => "Optimal",
+        HighsModelStatus::ObjectiveBound => "Bound on objective reached",
+        HighsModelStatus::ObjectiveTarget => "Target for objective reached",
+        HighsModelStatus::ReachedTimeLimit => "Time limit reached",
+        HighsModelStatus::ReachedIterationLimit => "Iteration limit reached",
+        HighsModelStatus::ReachedSolutionLimit => "Solution limit reached",
+        HighsModelStatus::ReachedInterrupt => "Interrupted by user",
+        HighsModelStatus::ReachedMemoryLimit => "Memory limit reached",
+        _ => "Unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Status;
+
+    #[test]
+    fn writes_both_solutions_under_their_names() {
+        let mut res = MessageSolution::new(Status::Optimal, vec![1.5, 2.0]);
+        res.values[1] = Value::Integer(2);
+        res.objective = Some(7.5);
+        res.activities = Some(vec![3.5, -1.0]);
+        res.reduced_costs = Some(vec![0.0, 0.25]);
+        res.duals = Some(vec![1.0, 0.0]);
+        let columns = ["x".to_owned(), "y".to_owned()];
+        let rows = [Some("cap".to_owned()), None];
+        let text = write(&res, HighsModelStatus::Optimal, &columns, &rows);
+        assert_eq!(
+            text,
+            "Model status\nOptimal\n\n\
+             # Primal solution values\nFeasible\nObjective 7.5\n\
+             # Columns 2\nx 1.5\ny 2\n# Rows 2\ncap 3.5\nR1 -1\n\n\
+             # Dual solution values\nFeasible\n\
+             # Columns 2\nx 0\ny 0.25\n# Rows 2\ncap 1\nR1 0\n\n\
+             # Basis\nHiGHS v1\nNone\n"
+        );
+    }
+
+    #[test]
+    fn solutions_without_values_are_none() {
+        let res = MessageSolution::new(Status::Infeasible, vec![]);
+        let text = write(&res, HighsModelStatus::Infeasible, &[], &[]);
+        assert_eq!(
+            text,
+            "Model status\nInfeasible\n\n# Primal solution values\nNone\n\n\
+             # Dual solution values\nNone\n\n# Basis\nHiGHS v1\nNone\n"
+        );
+    }
+}
@@ -0,0 +1,47 @@
+//! Special ordered sets. HiGHS does not support them, so they are compiled
+//! into binary variables that switch the members on.
+
+use crate::raw;
+use highs::Model;
+use highs_sys::HighsInt;
+
+/// Member of a set, with the finite bounds that link it to its binaries.
+pub struct Member {
+    pub col: HighsInt,
+    pub min: f64,
+    pub max: f64,
+}
+
+/// Adds a set of type 1 (at most one member is nonzero) or of type 2 (at
+/// most two adjacent members are nonzero) over members in their order.
+///
+/// Type 1 gets one binary per member, type 2 one binary per pair of
+/// adjacent members. At most one binary is set, and a member can only be
+/// nonzero if one of its binaries is set.
+pub fn add(model: &mut Model, members: &[Member], adjacent: bool) -> bool {
+    let switches = if adjacent {
+        members.len() - 1
+    } else {
+        members.len()
+    };
+    let Some(switch) = (0..switches)
+        .map(|_| raw::add_binary(model))
+        .collect::<Option<Vec<_>>>()
+    else {
+        return false;
+    };
+    for (i, m) in members.iter().enumerate() {
+        let first = if adjacent { i.saturating_sub(1) } else { i };
+        let on = &switch[first..(i + 1).min(switches)];
+        let index: Vec<_> = [m.col].into_iter().chain(on.iter().copied()).collect();
+        // x <= max * (sum of switches) and x >= min * (sum of switches)
+        let upper: Vec<_> = [1.0].into_iter().chain(on.iter().map(|_| -m.max)).collect();
+        let lower: Vec<_> = [1.0].into_iter().chain(on.iter().map(|_| -m.min)).collect();
+        if !raw::add_row(model, f64::NEG_INFINITY, 0.0, &index, &upper)
+            || !raw::add_row(model, 0.0, f64::INFINITY, &index, &lower)
+        {
+            return false;
+        }
+    }
+    raw::add_row(model, f64::NEG_INFINITY, 1.0, &switch, &vec![1.0; switches])
+}
@@ -0,0 +1,204 @@
+//! Detection of keys that serde silently ignores when it reads a
+//! [`MessageProblem`].
+
+use crate::{
+    AbsTerm, Affine, ConstraintTemplate, DumpModel, Extremum, Indicator, MessageProblem,
+    ObjectiveStage, Options, Piecewise, QuadraticTerm, RangedConstraint, Row, SensedRows, Sos,
+    SparseMatrix, StartValue, TemplateTerm, VariableArray, VariableDef, VariableDefaults,
+};
+use serde::de::{self, Deserialize, Deserializer, Visitor, value};
+use serde::forward_to_deserialize_any;
+use serde_json::{Map, Value};
+
+/// Keys of all forms of a [`crate::CoeffVar`], which serde does not list
+/// because it is untagged.
+const COEFF_FIELDS: &[&str] = &["name", "index", "array", "indices", "factor"];
+
+/// Finds the first key of `problem` that does not belong to the protocol,
+/// and returns its path such as `variables[2].integr`.
+pub fn unknown_key(problem: &Value) -> Option<String> {
+    let Value::Object(problem) = problem else {
+        return None;
+    };
+    let mut found = keys(problem, "", fields::<MessageProblem>());
+    let lists = [
+        ("variables", fields::<VariableDef>()),
+        ("variable_arrays", fields::<VariableArray>()),
+        ("partial_start", fields::<StartValue>()),
+        ("objective", COEFF_FIELDS),
+        ("objectives", fields::<ObjectiveStage>()),
+        ("objective_quadratic", fields::<QuadraticTerm>()),
+        ("piecewise", fields::<Piecewise>()),
+        ("abs_terms", fields::<AbsTerm>()),
+        ("max_of", fields::<Extremum>()),
+        ("min_of", fields::<Extremum>()),
+        ("rows", fields::<Row>()),
+        ("constraint_templates", fields::<ConstraintTemplate>()),
+        ("ranged_constraints", fields::<RangedConstraint>()),
+        ("sos", fields::<Sos>()),
+        ("indicators", fields::<Indicator>()),
+    ];
+    for (key, known) in lists {
+        found.extend(objects(problem.get(key), key, known));
+    }
+    for key in ["constraints", "equalities", "geq_constraints"] {
+        for (i, terms) in items(problem.get(key)).enumerate() {
+            found.extend(objects(Some(terms), &format!("{key}[{i}]"), COEFF_FIELDS));
+        }
+    }
+    if let Some(sensed) = problem.get("sensed_rows") {
+        for (i, terms) in items(sensed.get("terms")).enumerate() {
+            let path = format!("sensed_rows.terms[{i}]");
+            found.extend(objects(Some(terms), &path, COEFF_FIELDS));
+        }
+    }
+    for key in [
+        "objectives",
+        "rows",
+        "ranged_constraints",
+        "indicators",
+        "abs_terms",
+    ] {
+        for (i, row) in items(problem.get(key)).enumerate() {
+            let path = format!("{key}[{i}].terms");
+            found.extend(objects(row.get("terms"), &path, COEFF_FIELDS));
+        }
+    }
+    for (i, template) in items(problem.get("constraint_templates")).enumerate() {
+        let path = format!("constraint_templates[{i}].terms");
+        found.extend(objects(
+            template.get("terms"),
+            &path,
+            fields::<TemplateTerm>(),
+        ));
+    }
+    for key in ["max_of", "min_of"] {
+        for (i, extremum) in items(problem.get(key)).enumerate() {
+            let path = format!("{key}[{i}].expressions");
+            found.extend(objects(
+                extremum.get("expressions"),
+                &path,
+                fields::<Affine>(),
+            ));
+            for (j, expr) in items(extremum.get("expressions")).enumerate() {
+                let path = format!("{path}[{j}].terms");
+                found.extend(objects(expr.get("terms"), &path, COEFF_FIELDS));
+            }
+        }
+    }
+    let nested = [
+        ("variable_defaults", fields::<VariableDefaults>()),
+        ("sensed_rows", fields::<SensedRows>()),
+        ("matrix", fields::<SparseMatrix>()),
+        ("options", fields::<Options>()),
+        ("dump_model", fields::<DumpModel>()),
+    ];
+    for (key, known) in nested {
+        if let Some(Value::Object(map)) = problem.get(key) {
+            found.extend(keys(map, &format!("{key}."), known));
+        }
+    }
+    found.into_iter().next()
+}
+
+fn items(list: Option<&Value>) -> impl Iterator<Item = &Value> {
+    list.and_then(Value::as_array).into_iter().flatten()
+}
+/// Unknown keys of the objects in `list`.
+fn objects(list: Option<&Value>, path: &str, known: &[&str]) -> Vec<String> {
+    items(list)
+        .enumerate()
+        .filter_map(|(i, item)| Some((i, item.as_object()?)))
+        .flat_map(|(i, map)| keys(map, &format!("{path}[{i}]."), known))
+        .collect()
+}
+fn keys(map: &Map<String, Value>, prefix: &str, known: &[&str]) -> Vec<String> {
+    map.keys()
+        .filter(|k| !known.contains(&k.as_str()))
+        .map(|k| format!("{prefix}{k}"))
+        .collect()
+}
+
+/// Field names that the derived [`Deserialize`] impl of a struct accepts.
+fn fields<'de, T: Deserialize<'de>>() -> &'static [&'static str] {
+    let mut fields: &'static [&'static str] = &[];
+    let _ = T::deserialize(FieldNames(&mut fields));
+    fields
+}
+
+/// Deserializer that only records the field names it is asked for.
+struct FieldNames<'a>(&'a mut &'static [&'static str]);
+impl<'de> Deserializer<'de> for FieldNames<'_> {
+    type Error = value::Error;
+    fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Self::Error> {
+        Err(de::Error::custom("not a struct"))
+    }
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        fields: &'static [&'static str],
+        _: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.0 = fields;
+        Err(de::Error::custom("only the fields are needed"))
+    }
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn finds_misspelled_keys_at_any_depth() {
+        let problem = json!({
+            "variables": [{"name": "x", "max": 1}, {"name": "y", "integr": true}],
+            "objective": [{"name": "x", "factor": 1}]
+        });
+        assert_eq!(unknown_key(&problem).unwrap(), "variables[1].integr");
+        let cases = [
+            (json!({"objectiv": []}), "objectiv"),
+            (json!({"options": {"prety": true}}), "options.prety"),
+            (
+                json!({"constraints": [[{"name": "x", "factr": 1}]]}),
+                "constraints[0][0].factr",
+            ),
+            (
+                json!({"rows": [{"terms": [{"index": 0, "weight": 1}]}]}),
+                "rows[0].terms[0].weight",
+            ),
+            (json!({"matrix": {"row_ends": []}}), "matrix.row_ends"),
+            (
+                json!({"max_of": [{"name": "m", "expressions": [{"terms": [], "offest": 1}]}]}),
+                "max_of[0].expressions[0].offest",
+            ),
+            (
+                json!({"sensed_rows": {"terms": [[{"name": "x", "fac": 1}]]}}),
+                "sensed_rows.terms[0][0].fac",
+            ),
+        ];
+        for (problem, key) in cases {
+            assert_eq!(unknown_key(&problem).as_deref(), Some(key), "{problem}");
+        }
+    }
+
+    #[test]
+    fn accepts_every_form_of_a_term() {
+        let problem = json!({
+            "variables": [{"name": "x"}],
+            "objective": [
+                {"name": "x", "factor": 1},
+                {"index": 0, "factor": 1},
+                {"array": "a", "indices": [0], "factor": 1}
+            ],
+            "options": {"pretty_output": true}
+        });
+        assert_eq!(unknown_key(&problem), None);
+        assert_eq!(unknown_key(&json!([])), None);
+    }
+}
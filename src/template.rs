@@ -0,0 +1,75 @@
+//! Placeholders in the variable names of constraint templates, such as
+//! `inv_{t}` or `inv_{t+1}` for an index `t`.
+
+/// Replaces every placeholder of `index` in `pattern` by `value` plus its
+/// shift. Fails with a description of the first malformed placeholder.
+pub fn substitute(pattern: &str, index: &str, value: i64) -> Result<String, String> {
+    let mut name = String::with_capacity(pattern.len());
+    let mut rest = pattern;
+    while let Some(open) = rest.find('{') {
+        name.push_str(&rest[..open]);
+        let Some(close) = rest[open..].find('}') else {
+            return Err(format!("unclosed placeholder in '{pattern}'"));
+        };
+        let placeholder = &rest[open + 1..open + close];
+        let shift = shift(placeholder, index)
+            .ok_or_else(|| format!("unknown placeholder '{{{placeholder}}}' in '{pattern}'"))?;
+        let shifted = value
+            .checked_add(shift)
+            .ok_or_else(|| format!("placeholder '{{{placeholder}}}' overflows"))?;
+        name.push_str(&shifted.to_string());
+        rest = &rest[open + close + 1..];
+    }
+    name.push_str(rest);
+    Ok(name)
+}
+
+/// Reads `t`, `t+k` or `t-k` for the index `t`, ignoring whitespace.
+fn shift(placeholder: &str, index: &str) -> Option<i64> {
+    let compact: String = placeholder.split_whitespace().collect();
+    let offset = compact.strip_prefix(index)?;
+    if offset.is_empty() {
+        return Some(0);
+    }
+    let (sign, digits) = if let Some(digits) = offset.strip_prefix('+') {
+        (1, digits)
+    } else {
+        (-1, offset.strip_prefix('-')?)
+    };
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse::<i64>().ok().map(|k| sign * k)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn substitutes_shifted_placeholders() {
+        assert_eq!(substitute("inv_{t}", "t", 3).unwrap(), "inv_3");
+        assert_eq!(
+            substitute("flow_{t + 1}_{t-2}", "t", 3).unwrap(),
+            "flow_4_1"
+        );
+        assert_eq!(substitute("x_{i}_{i}", "i", -1).unwrap(), "x_-1_-1");
+        assert_eq!(substitute("plain", "t", 3).unwrap(), "plain");
+    }
+
+    #[test]
+    fn rejects_malformed_placeholders() {
+        assert_eq!(
+            substitute("inv_{t", "t", 0).unwrap_err(),
+            "unclosed placeholder in 'inv_{t'"
+        );
+        for pattern in ["x_{s}", "x_{t*2}", "x_{t+}", "x_{t+1a}", "x_{}"] {
+            let error = substitute(pattern, "t", 0).unwrap_err();
+            assert!(error.starts_with("unknown placeholder"), "{pattern}");
+        }
+        assert_eq!(
+            substitute("x_{t+1}", "t", i64::MAX).unwrap_err(),
+            "placeholder '{t+1}' overflows"
+        );
+    }
+}
@@ -0,0 +1,134 @@
+//! Problems that arrive in chunks, for hosts that cap the size of a single
+//! buffer passed across the C ABI.
+
+use crate::MessageError;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+/// Upper bound on the size of an upload, 1 GiB by default.
+pub static LIMIT: AtomicUsize = AtomicUsize::new(1 << 30);
+
+// open uploads by handle, which are never reused so that stale handles are
+// recognised
+static UPLOADS: Mutex<BTreeMap<usize, Upload>> = Mutex::new(BTreeMap::new());
+static NEXT: AtomicUsize = AtomicUsize::new(1);
+
+struct Upload {
+    bytes: Vec<u8>,
+    state: State,
+}
+enum State {
+    Receiving,
+    TooLarge(usize), // the limit that was exceeded, later chunks are dropped
+}
+
+fn uploads() -> std::sync::MutexGuard<'static, BTreeMap<usize, Upload>> {
+    UPLOADS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Opens an upload and returns its handle, which is never 0.
+pub fn begin() -> usize {
+    let handle = NEXT.fetch_add(1, Ordering::Relaxed);
+    let upload = Upload {
+        bytes: vec![],
+        state: State::Receiving,
+    };
+    uploads().insert(handle, upload);
+    handle
+}
+
+/// Adds a chunk to an open upload. Returns whether it was added, which it
+/// is not if the upload is unknown or too large.
+pub fn append(handle: usize, chunk: &[u8]) -> bool {
+    let mut uploads = uploads();
+    let Some(upload) = uploads.get_mut(&handle) else {
+        return false;
+    };
+    let limit = LIMIT.load(Ordering::Relaxed);
+    match upload.state {
+        State::Receiving if upload.bytes.len().saturating_add(chunk.len()) <= limit => {
+            upload.bytes.extend_from_slice(chunk);
+            true
+        }
+        State::Receiving => {
+            // the error is only reported when finishing
+            upload.bytes = vec![];
+            upload.state = State::TooLarge(limit);
+            false
+        }
+        State::TooLarge(_) => false,
+    }
+}
+
+/// Closes an upload and returns everything that was added to it.
+pub fn finish(handle: usize) -> Result<Vec<u8>, MessageError> {
+    let upload = uploads().remove(&handle).ok_or_else(|| {
+        MessageError::parse("unknown upload, it was never begun or is already finished")
+    })?;
+    match upload.state {
+        State::Receiving => Ok(upload.bytes),
+        State::TooLarge(limit) => Err(MessageError::parse(format!(
+            "upload exceeds the limit of {limit} bytes"
+        ))),
+    }
+}
+
+/// Closes an upload without solving it. Returns whether it was open.
+pub fn abort(handle: usize) -> bool {
+    uploads().remove(&handle).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the limit is global, so the tests take turns
+    static SERIAL: Mutex<()> = Mutex::new(());
+
+    #[test]
+    fn chunks_are_joined_in_order() {
+        let _serial = SERIAL.lock().unwrap_or_else(PoisonError::into_inner);
+        let handle = begin();
+        assert_ne!(handle, 0);
+        assert!(append(handle, b"{\"a\":"));
+        assert!(append(handle, b""));
+        assert!(append(handle, b"1}"));
+        assert_eq!(finish(handle).unwrap(), b"{\"a\":1}");
+        // the handle is gone and not reused
+        assert!(!append(handle, b"x"));
+        assert!(finish(handle).is_err());
+        assert_ne!(begin(), handle);
+    }
+
+    #[test]
+    fn aborted_uploads_are_gone() {
+        let _serial = SERIAL.lock().unwrap_or_else(PoisonError::into_inner);
+        let handle = begin();
+        assert!(append(handle, b"{}"));
+        assert!(abort(handle));
+        assert!(!abort(handle));
+        assert_eq!(
+            finish(handle).unwrap_err().message,
+            "unknown upload, it was never begun or is already finished"
+        );
+    }
+
+    #[test]
+    fn uploads_over_the_limit_fail_when_finished() {
+        let _serial = SERIAL.lock().unwrap_or_else(PoisonError::into_inner);
+        let limit = LIMIT.swap(4, Ordering::Relaxed);
+        let handle = begin();
+        let appended = [
+            append(handle, b"1234"),
+            append(handle, b"5"),
+            append(handle, b""),
+        ];
+        LIMIT.store(limit, Ordering::Relaxed);
+        assert_eq!(appended, [true, false, false]);
+        assert_eq!(
+            finish(handle).unwrap_err().message,
+            "upload exceeds the limit of 4 bytes"
+        );
+    }
+}
@@ -0,0 +1,27 @@
+//! The clock of the JavaScript host, since `std::time::Instant` panics on
+//! wasm32-unknown-unknown. It only has to time the phases of a solve.
+
+use std::ops::Sub;
+use std::time::Duration;
+use wasm_bindgen::prelude::wasm_bindgen;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = performance)]
+    fn now() -> f64;
+}
+
+/// A point in time, in milliseconds of `performance.now()`.
+#[derive(Clone, Copy)]
+pub struct Instant(f64);
+impl Instant {
+    pub fn now() -> Self {
+        Self(now())
+    }
+}
+impl Sub for Instant {
+    type Output = Duration;
+    fn sub(self, earlier: Self) -> Duration {
+        Duration::from_secs_f64((self.0 - earlier.0).max(0.0) / 1000.0)
+    }
+}
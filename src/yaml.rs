@@ -0,0 +1,349 @@
+//! Problems in YAML, which are read into JSON values for the usual
+//! deserialiser, and responses in YAML, both with `serde_yaml`.
+
+use crate::MessageError;
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+use serde_yaml::Value as Yaml;
+
+/// Reads a single YAML document with anchors, aliases and merge keys `<<`.
+/// Plain scalars are resolved with the core schema of YAML 1.2, so `y` and
+/// `no` stay strings, and infinite floats such as `.inf` become the strings
+/// `"inf"` and `"-inf"`, which numbers accept. Tags are not supported.
+pub fn parse(text: &str) -> Result<Value, MessageError> {
+    let mut yaml: Yaml = serde_yaml::from_str(text).map_err(|e| invalid(&e))?;
+    yaml.apply_merge().map_err(|e| invalid(&e))?;
+    json(yaml).map_err(|message| MessageError::parse(format!("invalid YAML: {message}")))
+}
+
+/// Writes a document as YAML. Strings that would read back as something
+/// else, like `"no"` or `"1"`, are quoted.
+pub fn emit(value: &impl Serialize) -> String {
+    serde_yaml::to_string(value).expect("JSON values can be written as YAML")
+}
+
+/// A parse error, at the position `serde_yaml` reports.
+fn invalid(e: &serde_yaml::Error) -> MessageError {
+    let location = e.location();
+    let error = MessageError::parse(format!("invalid YAML: {e}"));
+    match location {
+        Some(location) => error.on_line(location.line()).in_column(location.column()),
+        None => error,
+    }
+}
+
+fn json(yaml: Yaml) -> Result<Value, String> {
+    Ok(match yaml {
+        Yaml::Null => Value::Null,
+        Yaml::Bool(b) => Value::Bool(b),
+        Yaml::Number(n) => number(&n)?,
+        Yaml::String(s) => Value::String(s),
+        Yaml::Sequence(items) => {
+            Value::Array(items.into_iter().map(json).collect::<Result<_, _>>()?)
+        }
+        Yaml::Mapping(mapping) => {
+            let mut map = Map::new();
+            for (key, value) in mapping {
+                map.insert(key_text(key)?, json(value)?);
+            }
+            Value::Object(map)
+        }
+        Yaml::Tagged(tagged) => return Err(format!("tag {} is not supported", tagged.tag)),
+    })
+}
+
+fn number(n: &serde_yaml::Number) -> Result<Value, String> {
+    if let Some(u) = n.as_u64() {
+        return Ok(Value::from(u));
+    }
+    if let Some(i) = n.as_i64() {
+        return Ok(Value::from(i));
+    }
+    let f = n.as_f64().unwrap_or(f64::NAN);
+    if f.is_nan() {
+        return Err("NaN is not a number".to_owned());
+    }
+    Ok(Number::from_f64(f).map_or_else(
+        || Value::from(if f > 0.0 { "inf" } else { "-inf" }),
+        Value::Number,
+    ))
+}
+
+/// Keys that YAML reads as numbers or booleans are taken as written.
+fn key_text(key: Yaml) -> Result<String, String> {
+    match key {
+        Yaml::String(s) => Ok(s),
+        Yaml::Bool(b) => Ok(b.to_string()),
+        Yaml::Number(n) => Ok(n.to_string()),
+        _ => Err("keys have to be strings".to_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::fmt::Write;
+
+    #[test]
+    fn reads_anchors_aliases_and_merge_keys() {
+        let text = "\
+bounds: &bounds {min: 0, max: 10}
+variables:
+  - &x
+    name: x
+    <<: *bounds
+  - name: y
+    <<: [*bounds, {integer: true}]
+    max: 5
+  - *x
+";
+        let x = json!({"name": "x", "min": 0, "max": 10});
+        let y = json!({"name": "y", "min": 0, "max": 5, "integer": true});
+        let expected = json!({"bounds": {"min": 0, "max": 10}, "variables": [x, y, x]});
+        assert_eq!(parse(text).unwrap(), expected);
+    }
+
+    #[test]
+    fn skips_comments() {
+        let text = "\
+# a problem
+direction: max # to the right
+objective: # the terms
+  - name: x   # first
+    factor: 2#3
+notes: 'a # b'
+";
+        let expected = json!({
+            "direction": "max",
+            "objective": [{"name": "x", "factor": "2#3"}],
+            "notes": "a # b"
+        });
+        assert_eq!(parse(text).unwrap(), expected);
+    }
+
+    #[test]
+    fn resolves_scalars_with_the_core_schema() {
+        let text = "\
+hex: 0x1f
+octal: 0o17
+float: 1.5e3
+int: -42
+big: 18446744073709551615
+leading: .5
+null_word: null
+tilde: ~
+empty:
+true_word: true
+quoted_true: 'true'
+version: 1.2.3
+bounds: [.inf, -.Inf]
+2: two
+";
+        let expected = json!({
+            "hex": 31, "octal": 15, "float": 1500.0, "int": -42, "big": u64::MAX,
+            "leading": 0.5, "null_word": null, "tilde": null, "empty": null,
+            "true_word": true, "quoted_true": "true", "version": "1.2.3",
+            "bounds": ["inf", "-inf"], "2": "two"
+        });
+        assert_eq!(parse(text).unwrap(), expected);
+        // YAML 1.1 reads all but y and n as booleans
+        let words = parse("[y, n, yes, no, on, off, Yes, NO]").unwrap();
+        assert_eq!(
+            words,
+            json!(["y", "n", "yes", "no", "on", "off", "Yes", "NO"])
+        );
+    }
+
+    #[test]
+    fn rejects_what_json_cannot_hold() {
+        let error = |text: &str| parse(text).unwrap_err().message;
+        assert_eq!(error("x: .nan"), "invalid YAML: NaN is not a number");
+        assert_eq!(
+            error("x: !point 3"),
+            "invalid YAML: tag !point is not supported"
+        );
+        assert_eq!(error("[1]: x"), "invalid YAML: keys have to be strings");
+    }
+
+    #[test]
+    fn reads_block_scalars_and_quoted_strings() {
+        let text = "\
+literal: |
+  line one
+    indented
+
+  last
+folded: >
+  folded
+  text
+
+  next
+keep: |+
+  kept
+
+strip: >-
+  stripped
+double: \"tab\\there \\u00e9 \\x41 \\\"q\\\"\"
+single: 'it''s'
+multiline: \"one
+  two
+
+  three\"
+plain: a long
+  plain scalar
+";
+        let expected = json!({
+            "literal": "line one\n  indented\n\nlast\n",
+            "folded": "folded text\nnext\n",
+            "keep": "kept\n\n",
+            "strip": "stripped",
+            "double": "tab\there \u{e9} A \"q\"",
+            "single": "it's",
+            "multiline": "one two\nthree",
+            "plain": "a long plain scalar"
+        });
+        assert_eq!(parse(text).unwrap(), expected);
+    }
+
+    #[test]
+    fn reads_flow_collections() {
+        let text = "\
+matrix: {row_starts: [0, 2], values: [1, -1.5], \"key with, comma\": [a, 'b, c', {x: y}]}
+nested: [[1, 2], [], {}]
+";
+        let expected = json!({
+            "matrix": {"row_starts": [0, 2], "values": [1, -1.5], "key with, comma": ["a", "b, c", {"x": "y"}]},
+            "nested": [[1, 2], [], {}]
+        });
+        assert_eq!(parse(text).unwrap(), expected);
+    }
+
+    #[test]
+    fn locates_errors() {
+        let located = parse("x: 1\ny: [1,\n  2}").unwrap_err();
+        assert_eq!((located.line, located.column), (Some(3), Some(4)));
+        // at the start of the mapping
+        let located = parse("a: 1\na: 2\n").unwrap_err();
+        assert_eq!(
+            located.message,
+            "invalid YAML: duplicate entry with key \"a\""
+        );
+        assert_eq!((located.line, located.column), (Some(1), Some(1)));
+        assert!(parse("--- 1\n--- 2\n").is_err());
+    }
+
+    #[test]
+    fn limits_nesting_and_aliases() {
+        let deep = format!("{}{}", "[".repeat(200), "]".repeat(200));
+        assert!(parse(&deep).is_err());
+        // ten times ten times ... copies of the first list
+        let mut laughs = "a0: &a0 [1, 1, 1, 1, 1, 1, 1, 1, 1, 1]\n".to_owned();
+        for i in 1..9 {
+            let aliases = vec![format!("*a{}", i - 1); 10].join(", ");
+            let _ = writeln!(laughs, "a{i}: &a{i} [{aliases}]");
+        }
+        assert!(parse(&laughs).is_err());
+    }
+
+    #[test]
+    fn emits_strings_that_read_back() {
+        let strings: &[&str] = &[
+            "y",
+            "no",
+            "true",
+            "null",
+            "~",
+            "123",
+            "0x1f",
+            "1e3",
+            ".inf",
+            "On",
+            "017",
+            "1_000",
+            "+.5",
+            "=",
+            "<<",
+            "",
+            " lead",
+            "trail ",
+            "- a",
+            "a: b",
+            "a:",
+            "#x",
+            "a #b",
+            "multi\nline",
+            "tab\t",
+            "it's",
+            "\"q\"",
+            "[a]",
+            "{a}",
+            "a, b",
+            "&x",
+            "*x",
+            "!x",
+            "|",
+            ">",
+            "%",
+            "@",
+            "`",
+            "?",
+            "plain text",
+        ];
+        // serde_yaml writes a key `<<` plain, which reads back as a merge key
+        let keys: Map<_, _> = strings
+            .iter()
+            .filter(|s| **s != "<<")
+            .map(|s| ((*s).to_owned(), json!(s)))
+            .collect();
+        let value = json!({
+            "strings": strings,
+            "nested": [{"key": strings}, {"keys": keys}],
+            "numbers": [1, -2.5, 1e300],
+            "empty": [[], {}],
+            "other": [true, null]
+        });
+        assert_eq!(parse(&emit(&value)).unwrap(), value);
+    }
+
+    /// Solves through the C entry point.
+    fn solve(text: &str) -> String {
+        let response = unsafe { crate::solve_yaml(text.as_ptr(), text.len()) };
+        let text = unsafe { std::ffi::CStr::from_ptr(response) }
+            .to_str()
+            .unwrap()
+            .to_owned();
+        unsafe { crate::free_string(response.cast_mut()) };
+        text
+    }
+
+    #[test]
+    fn solves_like_json() {
+        let text = "\
+direction: max
+defaults: &binary {binary: true}
+variables:
+  - {name: a, <<: *binary}
+  - name: b
+    max: 2
+    integer: true
+objective: [{name: a, factor: 3}, {name: b, factor: 2}]
+expressions:
+  - 2*a + b <= 4 # the capacity
+verbose: false
+options: {yaml_output: true}
+";
+        let solution = parse(&solve(text)).unwrap();
+        let mut problem = parse(text).unwrap();
+        problem["options"] = json!({});
+        let json = problem.to_string();
+        let response = unsafe { crate::solve(json.as_ptr(), json.len()) };
+        let text = unsafe { std::ffi::CStr::from_ptr(response) };
+        let json: Value = serde_json::from_slice(text.to_bytes()).unwrap();
+        unsafe { crate::free_string(response.cast_mut()) };
+        assert_eq!(solution["objective"], 7.0);
+        for key in ["status", "values", "objective", "activities"] {
+            assert_eq!(solution[key], json[key], "{key}");
+        }
+    }
+}
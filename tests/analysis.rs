@@ -0,0 +1,180 @@
+#![cfg(feature = "highs")]
+//! What the solver finds out beyond a single optimum: conflicts of
+//! infeasible problems, further solutions and several objectives.
+
+mod common;
+
+use common::{solve, values};
+use serde_json::{Value, json};
+
+fn term(name: &str, factor: f64) -> Value {
+    json!({"name": name, "factor": factor})
+}
+
+fn close(a: &[f64], b: &[f64]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-6)
+}
+
+fn numbers(value: &Value) -> Vec<f64> {
+    let numbers = value.as_array().expect("a list of numbers").iter();
+    numbers.map(|n| n.as_f64().unwrap()).collect()
+}
+
+// `low` and `high` conflict, `other` has nothing to do with it
+fn conflict() -> Value {
+    json!({
+        "direction": "min",
+        "explain_infeasibility": true,
+        "variables": [
+            {"name": "x", "min": 0, "integer": false},
+            {"name": "y", "min": 0, "integer": false}
+        ],
+        "objective": [term("x", 1.0)],
+        "objective_offset": 0,
+        "constraints": [[term("x", 1.0)], [term("x", -1.0)], [term("y", 1.0)]],
+        "constraint_offsets": [-1, 2, -5],
+        "constraint_names": ["low", "high", "other"],
+        "equalities": [],
+        "equalities_offsets": [],
+        "verbose": false
+    })
+}
+
+#[test]
+fn conflicts_name_the_rows_and_bounds_involved() {
+    let solution = solve(&conflict());
+    assert_eq!(solution["status"], "infeasible");
+    assert_eq!(
+        solution["infeasibility"],
+        json!({"bounds": [], "minimal": true, "row_names": ["low", "high"], "rows": [0, 1]})
+    );
+
+    // a bound instead of `low`
+    let mut problem = conflict();
+    problem["variables"][0]["max"] = json!(1);
+    problem["constraints"][0] = json!([term("y", -1.0)]);
+    problem["constraint_offsets"][0] = json!(0);
+    let solution = solve(&problem);
+    assert_eq!(
+        solution["infeasibility"],
+        json!({"bounds": [0], "minimal": true, "row_names": ["high"], "rows": [1]})
+    );
+
+    let mut problem = conflict();
+    problem["constraint_offsets"][1] = json!(0);
+    let solution = solve(&problem);
+    assert!(close(&values(&solution), &[0.0, 0.0]));
+    assert!(solution.get("infeasibility").is_none());
+}
+
+#[test]
+fn conflicts_stay_large_when_the_solves_run_out() {
+    let mut problem = conflict();
+    problem["explain_infeasibility_max_solves"] = json!(1);
+    let infeasibility = &solve(&problem)["infeasibility"];
+    assert_eq!(infeasibility["minimal"], false);
+    assert_eq!(infeasibility["rows"], json!([0, 1, 2]));
+}
+
+// three ways of choosing one of the binaries, and choosing none
+fn choices() -> Value {
+    let binary = |name: &str| json!({"name": name, "min": 0, "max": 1, "integer": true});
+    json!({
+        "direction": "max",
+        "variables": [binary("a"), binary("b"), binary("c")],
+        "objective": [term("a", 3.0), term("b", 2.0), term("c", 1.0)],
+        "objective_offset": 0,
+        "constraints": [[term("a", 1.0), term("b", 1.0), term("c", 1.0)]],
+        "constraint_offsets": [-1],
+        "equalities": [],
+        "equalities_offsets": [],
+        "verbose": false
+    })
+}
+
+#[test]
+fn the_pool_starts_with_the_incumbent_and_gets_worse() {
+    let mut problem = choices();
+    problem["solution_limit"] = json!(3);
+    let solution = solve(&problem);
+    assert!(close(&values(&solution), &[1.0, 0.0, 0.0]));
+    assert_eq!(
+        solution["solutions"],
+        json!([
+            {"objective": 3.0, "values": [1.0, 0.0, 0.0]},
+            {"objective": 2.0, "values": [0.0, 1.0, 0.0]},
+            {"objective": 1.0, "values": [0.0, 0.0, 1.0]}
+        ])
+    );
+    // there are no more than four solutions
+    problem["solution_limit"] = json!(10);
+    let solutions = solve(&problem)["solutions"].as_array().unwrap().len();
+    assert_eq!(solutions, 4);
+}
+
+#[test]
+fn the_pool_needs_bounded_integer_variables() {
+    let mut problem = choices();
+    problem["solution_limit"] = json!(2);
+    problem["variables"][0]["max"] = json!(null);
+    let solution = solve(&problem);
+    assert_eq!(solution["kind"], "validation");
+    assert_eq!(
+        solution["message"],
+        "solution_limit requires bounds on integer variable 'a'"
+    );
+}
+
+// x and y compete for the room of the row
+fn competing() -> Value {
+    json!({
+        "direction": "max",
+        "variables": [{"name": "x", "min": 0, "max": 2}, {"name": "y", "min": 0, "max": 2}],
+        "objective": {"x": 1},
+        "objectives": [{"direction": "max", "terms": {"y": 1}}],
+        "rows": [{"terms": [term("x", 1.0), term("y", 1.0)], "op": "<=", "rhs": 3}],
+        "verbose": false
+    })
+}
+
+#[test]
+fn later_objectives_keep_the_earlier_ones_optimal() {
+    let mut problem = competing();
+    problem["objective_tolerance"] = json!(0);
+    let solution = solve(&problem);
+    assert!(close(&values(&solution), &[2.0, 1.0]));
+    assert!(close(&numbers(&solution["stage_objectives"]), &[2.0, 1.0]));
+
+    // the first objective may give up as much as its tolerance
+    let mut problem = competing();
+    problem["objective_tolerance"] = json!(0.5);
+    let solution = solve(&problem);
+    assert!(close(&values(&solution), &[1.5, 1.5]));
+    assert!(close(&numbers(&solution["stage_objectives"]), &[2.0, 1.5]));
+
+    problem["objective_quadratic"] = json!([{"name_a": "x", "name_b": "x", "factor": 1}]);
+    let solution = solve(&problem);
+    assert_eq!(
+        solution["message"],
+        "objectives cannot be combined with objective_quadratic"
+    );
+}
+
+#[test]
+fn blended_objectives_are_weighted_into_one() {
+    let mut problem = competing();
+    problem["blend"] = json!(true);
+    problem["objectives"][0]["weight"] = json!(2);
+    problem["objective_breakdown"] = json!(true);
+    let solution = solve(&problem);
+    assert!(close(&values(&solution), &[1.0, 2.0]));
+    assert_eq!(solution["objective"], 5.0);
+    assert!(close(&numbers(&solution["stage_objectives"]), &[1.0, 2.0]));
+    assert_eq!(solution["objective_breakdown"]["blended"], json!([4.0]));
+
+    problem["blend"] = json!(false);
+    assert_eq!(
+        solve(&problem)["message"],
+        "weights of objectives require blend"
+    );
+}
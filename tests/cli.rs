@@ -0,0 +1,83 @@
+#![cfg(not(target_arch = "wasm32"))]
+//! Runs the command line interface as a process.
+
+use assert_cmd::Command;
+use serde_json::{Value, json};
+use std::process::Output;
+
+fn run(args: &[&str], stdin: &[u8]) -> Output {
+    Command::cargo_bin("knorpelsolve")
+        .expect("the binary is built")
+        .args(args)
+        .write_stdin(stdin)
+        .output()
+        .unwrap()
+}
+
+fn problem(max: f64) -> Value {
+    json!({
+        "direction": "max",
+        "variables": [{"name": "x", "min": 0, "max": max}],
+        "objective": [{"name": "x", "factor": 2}],
+        "expressions": ["x >= 1"],
+        "verbose": false
+    })
+}
+
+#[test]
+fn solves_from_stdin() {
+    let output = run(&[], problem(3.0).to_string().as_bytes());
+    assert!(output.status.success());
+    let solution: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(solution["status"], "optimal");
+    assert_eq!(solution["objective"], 6.0);
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn solves_from_a_file() {
+    let path = std::env::temp_dir().join(format!("knorpelsolve-cli-{}.json", std::process::id()));
+    std::fs::write(&path, problem(3.0).to_string()).unwrap();
+    let output = run(&["--pretty", path.to_str().unwrap()], b"");
+    std::fs::remove_file(&path).unwrap();
+    assert!(output.status.success());
+    let text = String::from_utf8(output.stdout).unwrap();
+    assert!(text.contains("\n  \"status\": \"optimal\""));
+}
+
+#[test]
+fn parse_errors_go_to_stderr() {
+    let output = run(&[], b"{\"direction\": ");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    let error: Value = serde_json::from_slice(&output.stderr).unwrap();
+    assert_eq!(error["status"], "error");
+    assert_eq!(error["kind"], "parse");
+}
+
+#[test]
+fn infeasible_is_a_solution() {
+    let output = run(&[], problem(0.5).to_string().as_bytes());
+    assert!(output.status.success());
+    let solution: Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(solution["status"], "infeasible");
+}
+
+#[test]
+fn time_limit_is_an_option() {
+    let output = run(&["--time-limit", "abc"], b"");
+    assert_eq!(output.status.code(), Some(2));
+    let message = String::from_utf8(output.stderr).unwrap();
+    assert!(message.contains("invalid time limit 'abc'"));
+    let output = run(&["--time-limit", "10"], problem(3.0).to_string().as_bytes());
+    assert!(output.status.success());
+}
+
+#[test]
+fn unknown_options_are_usage_errors() {
+    let output = run(&["--solve-fast"], b"");
+    assert_eq!(output.status.code(), Some(2));
+    let message = String::from_utf8(output.stderr).unwrap();
+    assert!(message.contains("unknown option '--solve-fast'"));
+    assert!(message.contains("usage: knorpelsolve"));
+}
@@ -0,0 +1,17 @@
+//! Helpers shared by the integration tests.
+
+use serde_json::Value;
+
+/// Solves a problem through the safe entry point and decodes the response.
+pub fn solve(problem: &Value) -> Value {
+    let response = knorpelsolve::solve_json(&problem.to_string());
+    serde_json::from_str(&response).expect("the response is JSON")
+}
+
+/// The values of a solution as numbers.
+pub fn values(solution: &Value) -> Vec<f64> {
+    let values = solution["values"]
+        .as_array()
+        .expect("the solution has values");
+    values.iter().map(|v| v.as_f64().unwrap()).collect()
+}
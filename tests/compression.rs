@@ -0,0 +1,120 @@
+//! Problems that are compressed with gzip or zstd, made with the command line
+//! tools of both, and the limit on their decompressed size.
+
+use knorpelsolve::{Encoding, set_decompression_limit, solve_bytes};
+use serde_json::{Map, Value, json};
+use std::sync::{Mutex, PoisonError};
+
+// `gzip -n -9` and `zstd -19` of the knapsack below
+const PROBLEM_GZIP: &[u8] = &[
+    0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0x85, 0xd2, 0xdf, 0x0a, 0x82, 0x30,
+    0x14, 0x06, 0xf0, 0x57, 0x19, 0xbb, 0x5c, 0x5e, 0xb8, 0xbf, 0x46, 0xd4, 0x93, 0x84, 0x17, 0xd3,
+    0x16, 0x2c, 0x4a, 0x63, 0x9a, 0x9c, 0x10, 0xdf, 0xbd, 0xa3, 0xc1, 0x08, 0x1a, 0xec, 0xc6, 0xe3,
+    0xf6, 0x3b, 0x37, 0xdf, 0xc7, 0x66, 0x3a, 0xd9, 0xe0, 0x6d, 0x73, 0x77, 0x03, 0x3d, 0x90, 0xf3,
+    0x4c, 0x3b, 0xfb, 0x70, 0xf8, 0x47, 0xa1, 0xa4, 0x05, 0xa1, 0x8d, 0xef, 0x6c, 0x78, 0xe3, 0x79,
+    0x0c, 0x2f, 0xb7, 0x14, 0xe4, 0xc7, 0x79, 0xc6, 0x45, 0xc6, 0x65, 0xc6, 0x55, 0xc6, 0x75, 0xc6,
+    0x4d, 0xc6, 0xab, 0x7f, 0xaf, 0xf1, 0xa6, 0x6f, 0x6e, 0xae, 0x1d, 0xfd, 0xe4, 0x52, 0x75, 0x5c,
+    0x6d, 0x3b, 0xf6, 0x01, 0xcf, 0x3a, 0xd1, 0x45, 0x44, 0x95, 0x28, 0x22, 0xa2, 0x4c, 0xb4, 0x10,
+    0xb1, 0x4a, 0x54, 0x10, 0xd1, 0x24, 0xf2, 0x47, 0x14, 0x89, 0xf0, 0x11, 0xf7, 0x89, 0xe4, 0x11,
+    0xf9, 0x16, 0xfb, 0xe2, 0xc3, 0x1a, 0xbb, 0xef, 0xd6, 0x8d, 0x87, 0x85, 0x75, 0xc5, 0xc1, 0x33,
+    0xb8, 0x61, 0xc0, 0xcb, 0xed, 0x6d, 0x50, 0xc9, 0xa0, 0x24, 0x3b, 0x22, 0x18, 0x70, 0x1c, 0x8a,
+    0x81, 0xc0, 0xa1, 0x19, 0x48, 0x1c, 0x68, 0x0a, 0x07, 0x68, 0xfc, 0x18, 0x06, 0xe6, 0xbb, 0x57,
+    0x91, 0xe3, 0x89, 0x70, 0x41, 0xeb, 0xe5, 0x03, 0xd3, 0x43, 0x2d, 0xd8, 0x68, 0x02, 0x00, 0x00,
+];
+const PROBLEM_ZSTD: &[u8] = &[
+    0x28, 0xb5, 0x2f, 0xfd, 0x64, 0x68, 0x01, 0xed, 0x05, 0x00, 0xb2, 0xc8, 0x1c, 0x19, 0x60, 0xcf,
+    0x03, 0xc3, 0x25, 0xa3, 0x5e, 0xa3, 0xe0, 0x83, 0xa1, 0x1f, 0x04, 0x89, 0x8f, 0x46, 0x92, 0x44,
+    0x95, 0x9c, 0x69, 0x11, 0x04, 0x8b, 0x07, 0x17, 0xe2, 0xae, 0xb5, 0x51, 0xf4, 0x94, 0xcf, 0x64,
+    0x7c, 0xf8, 0xec, 0xbd, 0x67, 0xd1, 0x3d, 0x83, 0xed, 0x99, 0x6b, 0xa0, 0x85, 0x67, 0xcf, 0x2f,
+    0xb6, 0x51, 0x54, 0x33, 0xf4, 0xd5, 0x9f, 0x3e, 0x7e, 0x71, 0xb6, 0x4b, 0x9b, 0x83, 0x74, 0x51,
+    0xc2, 0xf9, 0x9e, 0x83, 0x2d, 0x66, 0x2c, 0x29, 0x94, 0xa9, 0x94, 0x95, 0x94, 0xda, 0x24, 0x18,
+    0x99, 0x32, 0xc2, 0xe7, 0x5a, 0x07, 0x57, 0x9d, 0xb2, 0x54, 0x65, 0x7a, 0x6d, 0xe2, 0x1d, 0xb8,
+    0xf0, 0x5e, 0x1f, 0x7a, 0xdd, 0x3a, 0x9a, 0xf0, 0xa8, 0x3a, 0x09, 0xb5, 0x99, 0x52, 0x6e, 0x05,
+    0x26, 0xa8, 0x11, 0x70, 0x92, 0xc1, 0x60, 0x26, 0x2c, 0x6c, 0x6b, 0x06, 0x50, 0x13, 0x8d, 0xd2,
+    0x01, 0x41, 0x0d, 0x32, 0xc2, 0x8c, 0x00, 0x63, 0xe1, 0x84, 0xc9, 0xcf, 0x03, 0xab, 0x1e, 0xd6,
+    0xd5, 0x19, 0xa2, 0x71, 0xfe, 0x43, 0x84, 0xc9, 0x3e, 0xda, 0x61, 0xfa, 0xfa, 0x55, 0xb0, 0x30,
+    0x36, 0x78, 0x03, 0x9a, 0x3d, 0xf4, 0x1c, 0xa2, 0x84, 0xac, 0x9a, 0x2c, 0xa4, 0x85, 0x2c, 0xe9,
+    0xb0, 0x7c, 0xe2, 0xbf, 0xd7, 0x50, 0x03, 0x39, 0x90, 0x93, 0x71,
+];
+
+// the limit is global, so the tests that compress take turns
+static SERIAL: Mutex<()> = Mutex::new(());
+
+fn knapsack() -> Value {
+    let weights = [3, 2, 4, 5, 3, 1, 6, 2];
+    let profits = [5, 4, 3, 7, 6, 2, 8, 1];
+    let variables: Vec<_> = (0..8)
+        .map(|i| json!({"name": format!("x{i}"), "binary": true}))
+        .collect();
+    let objective: Vec<_> = profits
+        .iter()
+        .enumerate()
+        .map(|(i, p)| json!({"name": format!("x{i}"), "factor": p}))
+        .collect();
+    let weighted: Vec<_> = weights
+        .iter()
+        .enumerate()
+        .map(|(i, w)| format!("{w}*x{i}"))
+        .collect();
+    json!({
+        "variables": variables,
+        "objective": objective,
+        "direction": "max",
+        "expressions": [format!("{} <= 12", weighted.join(" + "))]
+    })
+}
+
+/// The response without the stats, which hold timings.
+fn solve(input: &[u8]) -> Value {
+    let response = solve_bytes(input, Encoding::Json, &Map::new()).unwrap_or_else(|e| e);
+    let mut response: Value = serde_json::from_slice(&response).expect("the response is JSON");
+    response.as_object_mut().unwrap().remove("stats");
+    response
+}
+
+#[test]
+fn compressed_problems_solve_like_plain_ones() {
+    let _serial = SERIAL.lock().unwrap_or_else(PoisonError::into_inner);
+    let plain = solve(knapsack().to_string().as_bytes());
+    assert_eq!(plain["status"], "optimal");
+    assert_eq!(plain["objective"], 20.0);
+    assert_eq!(solve(PROBLEM_GZIP), plain);
+    assert_eq!(solve(PROBLEM_ZSTD), plain);
+}
+
+#[test]
+fn the_limit_rejects_larger_output() {
+    let _serial = SERIAL.lock().unwrap_or_else(PoisonError::into_inner);
+    set_decompression_limit(616);
+    let at_the_limit = [solve(PROBLEM_GZIP), solve(PROBLEM_ZSTD)];
+    set_decompression_limit(615);
+    let above = [solve(PROBLEM_GZIP), solve(PROBLEM_ZSTD)];
+    set_decompression_limit(1 << 30);
+    for solution in at_the_limit {
+        assert_eq!(solution["status"], "optimal");
+    }
+    for error in above {
+        assert_eq!(error["status"], "error");
+        assert_eq!(error["kind"], "parse");
+        assert_eq!(
+            error["message"],
+            "decompressed input exceeds the limit of 615 bytes"
+        );
+    }
+}
+
+#[test]
+fn corrupt_input_is_a_parse_error() {
+    let _serial = SERIAL.lock().unwrap_or_else(PoisonError::into_inner);
+    for (name, compressed) in [("gzip", PROBLEM_GZIP), ("zstd", PROBLEM_ZSTD)] {
+        let mut corrupt = compressed.to_vec();
+        corrupt.truncate(compressed.len() - 20);
+        let error = solve(&corrupt);
+        assert_eq!(error["kind"], "parse");
+        let message = error["message"].as_str().unwrap();
+        assert!(
+            message.starts_with(&format!("invalid {name} data: ")),
+            "{message}"
+        );
+    }
+}
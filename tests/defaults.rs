@@ -0,0 +1,65 @@
+#![cfg(feature = "highs")]
+//! Options that `set_default_options` sets for every later problem, which
+//! are global and therefore set by one test at a time.
+
+use knorpelsolve::{free_string, get_default_options, set_default_options, solve_lp_format};
+use serde_json::{Value, json};
+use std::ffi::{CStr, c_char};
+use std::sync::{Mutex, PoisonError};
+
+static SERIAL: Mutex<()> = Mutex::new(());
+
+fn set(options: &str) -> bool {
+    unsafe { set_default_options(options.as_ptr(), options.len()) }
+}
+
+fn json(response: *const c_char) -> Value {
+    let value = serde_json::from_slice(unsafe { CStr::from_ptr(response) }.to_bytes()).unwrap();
+    unsafe { free_string(response.cast_mut()) };
+    value
+}
+
+fn stats(options: &Value) -> Value {
+    let problem = json!({
+        "direction": "max",
+        "variables": [{"name": "x", "min": 0, "max": 1}],
+        "objective": {"x": 1},
+        "verbose": false,
+        "options": options
+    });
+    let response = knorpelsolve::solve_json(&problem.to_string());
+    let solution: Value = serde_json::from_str(&response).unwrap();
+    solution["stats"].clone()
+}
+
+#[test]
+fn defaults_apply_to_every_later_problem() {
+    let _serial = SERIAL.lock().unwrap_or_else(PoisonError::into_inner);
+    assert!(set(r#"{"seed": 5, "primal_feasibility_tolerance": 1e-6}"#));
+    let merged = stats(&json!({"seed": 9}));
+    assert_eq!(merged["seed"], 9);
+    assert_eq!(merged["primal_feasibility_tolerance"], 1e-6);
+
+    // also to the text formats, which have no options of their own
+    let lp = "max\n obj: x\nbounds\n 0 <= x <= 1\nend\n";
+    let solution = json(unsafe { solve_lp_format(lp.as_ptr(), lp.len()) });
+    assert_eq!(solution["stats"]["seed"], 5);
+
+    assert!(set("{}"));
+    assert_eq!(stats(&json!({}))["seed"], 0);
+}
+
+#[test]
+fn invalid_defaults_keep_the_previous_ones() {
+    let _serial = SERIAL.lock().unwrap_or_else(PoisonError::into_inner);
+    assert!(set(r#"{"seed": 5}"#));
+    assert!(!set(r#"{"seed": -1}"#));
+    assert!(!set(r#"{"mip_gap": 2}"#));
+    assert!(!set("[]"));
+    assert!(!unsafe { set_default_options(std::ptr::null(), 0) });
+    assert_eq!(json(get_default_options()), json!({"seed": 5}));
+    assert_eq!(stats(&json!({}))["seed"], 5);
+
+    assert!(set("{}"));
+    assert_eq!(json(get_default_options()), json!({}));
+}
@@ -0,0 +1,273 @@
+//! The C entry points besides `solve_json`, which read the problem from
+//! buffers, chunks, text formats and files, and hand out the response in
+//! different ways.
+
+use knorpelsolve::{
+    Encoding, SOLVE_INTO_NULL_POINTER, free_buffer, free_string, solve, solve_abort, solve_append,
+    solve_batch, solve_begin, solve_bytes, solve_file, solve_file_mmap, solve_finish, solve_into,
+    solve_lp_format, solve_mps, solve2,
+};
+use serde_json::{Map, Value, json};
+use std::ffi::{CStr, CString, c_char};
+use std::path::PathBuf;
+
+// optimal at x = 2, where `cap` holds
+fn problem() -> Value {
+    json!({
+        "direction": "max",
+        "variables": [{"name": "x", "min": 0, "max": 3}],
+        "objective": {"x": 1},
+        "rows": [{"name": "cap", "terms": [{"name": "x", "factor": 1}], "op": "<=", "rhs": 2}],
+        "verbose": false
+    })
+}
+
+const LP: &str = "max\n obj: x\nst\n cap: x <= 2\nbounds\n 0 <= x <= 3\nend\n";
+
+const MPS: &str = "NAME test
+OBJSENSE
+    MAX
+ROWS
+ N obj
+ L cap
+COLUMNS
+    x obj 1 cap 1
+RHS
+    rhs cap 2
+BOUNDS
+ UP bnd x 3
+ENDATA
+";
+
+/// Decodes and frees a response of the C entry points.
+fn text(response: *const c_char) -> String {
+    assert!(!response.is_null());
+    let text = unsafe { CStr::from_ptr(response) }
+        .to_str()
+        .unwrap()
+        .to_owned();
+    unsafe { free_string(response.cast_mut()) };
+    text
+}
+
+fn json(response: *const c_char) -> Value {
+    serde_json::from_str(&text(response)).expect("the response is JSON")
+}
+
+/// A path in the temporary directory that is unique to this test run.
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("knorpelsolve-{}-{name}", std::process::id()))
+}
+
+fn solve_path(
+    name: &str,
+    contents: &str,
+    solve: unsafe extern "C" fn(*const c_char) -> *const c_char,
+) -> Value {
+    let path = temp_path(name);
+    std::fs::write(&path, contents).unwrap();
+    let c_path = CString::new(path.to_str().unwrap()).unwrap();
+    let solution = json(unsafe { solve(c_path.as_ptr()) });
+    std::fs::remove_file(path).unwrap();
+    solution
+}
+
+#[test]
+fn buffers_are_solved_into_c_strings() {
+    let input = problem().to_string();
+    let solution = json(unsafe { solve(input.as_ptr(), input.len()) });
+    assert_eq!(solution["values"], json!([2.0]));
+    assert!(unsafe { solve(std::ptr::null(), 0) }.is_null());
+
+    // only buffers of solve2 can hold compressed output
+    let mut input = problem();
+    input["options"] = json!({"compress_output": true});
+    let input = input.to_string();
+    let solution = json(unsafe { solve(input.as_ptr(), input.len()) });
+    assert_eq!(
+        solution["message"],
+        "compress_output needs solve2, as the response is no C string then"
+    );
+}
+
+#[test]
+fn buffers_are_solved_into_buffers() {
+    let input = problem().to_string();
+    let mut len = 0;
+    let output = unsafe { solve2(input.as_ptr(), input.len(), &raw mut len) };
+    let bytes = unsafe { std::slice::from_raw_parts(output, len) };
+    let solution: Value = serde_json::from_slice(bytes).unwrap();
+    unsafe { free_buffer(output, len) };
+    assert_eq!(solution["values"], json!([2.0]));
+
+    let mut input = problem();
+    input["options"] = json!({"compress_output": true});
+    let input = input.to_string();
+    let output = unsafe { solve2(input.as_ptr(), input.len(), &raw mut len) };
+    let bytes = unsafe { std::slice::from_raw_parts(output, len) };
+    assert_eq!(bytes[..2], [0x1f, 0x8b]);
+    unsafe { free_buffer(output, len) };
+
+    let output = unsafe { solve2(input.as_ptr(), input.len(), std::ptr::null_mut()) };
+    assert!(output.is_null());
+}
+
+#[test]
+fn bytes_are_written_in_the_order_of_solve() {
+    let mut input = problem();
+    input["id"] = json!("p");
+    let input = input.to_string();
+    let expected = r#"{"id":"p","status":"optimal","values":[2.0],"objective":2.0,"#;
+    let solution = text(unsafe { solve(input.as_ptr(), input.len()) });
+    assert!(solution.starts_with(expected), "{solution}");
+    let bytes = solve_bytes(input.as_bytes(), Encoding::Json, &Map::new()).unwrap();
+    let solution = String::from_utf8(bytes).unwrap();
+    assert!(solution.starts_with(expected), "{solution}");
+
+    let bytes = solve_bytes(b"{", Encoding::Json, &Map::new()).unwrap_err();
+    let error = String::from_utf8(bytes).unwrap();
+    assert!(
+        error.starts_with(r#"{"status":"error","kind":"parse","#),
+        "{error}"
+    );
+}
+
+#[test]
+fn buffers_are_solved_into_the_callers_buffer() {
+    let input = problem().to_string();
+    let size = unsafe { solve_into(input.as_ptr(), input.len(), std::ptr::null_mut(), 0) };
+    assert!(size < 0 && size != SOLVE_INTO_NULL_POINTER);
+    // the timings in `stats` may take more digits the next time
+    let mut output = vec![0; size.unsigned_abs() + 64];
+    let written = unsafe {
+        solve_into(
+            input.as_ptr(),
+            input.len(),
+            output.as_mut_ptr(),
+            output.len(),
+        )
+    };
+    let written = usize::try_from(written).unwrap();
+    let solution: Value = serde_json::from_slice(&output[..written]).unwrap();
+    assert_eq!(solution["values"], json!([2.0]));
+
+    let mut small = [0; 4];
+    let written = unsafe { solve_into(input.as_ptr(), input.len(), small.as_mut_ptr(), 4) };
+    assert!(written < -4);
+    assert_eq!(small, [0; 4]);
+    let written = unsafe { solve_into(input.as_ptr(), input.len(), std::ptr::null_mut(), 4) };
+    assert_eq!(written, SOLVE_INTO_NULL_POINTER);
+}
+
+#[test]
+fn batches_answer_line_by_line() {
+    let mut pretty = problem();
+    pretty["options"] = json!({"pretty_output": true});
+    pretty["id"] = json!(1);
+    let input = format!("{pretty}\n\n  \n{{\"variables\": 1}}\n{}\n", problem());
+    let responses = text(unsafe { solve_batch(input.as_ptr(), input.len()) });
+    let responses: Vec<Value> = responses
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(responses.len(), 3);
+    assert_eq!(responses[0]["id"], 1);
+    assert_eq!(responses[0]["values"], json!([2.0]));
+    assert_eq!(responses[1]["kind"], "parse");
+    assert_eq!(responses[2]["values"], json!([2.0]));
+}
+
+#[test]
+fn uploads_are_solved_once_finished() {
+    let input = problem().to_string();
+    let session = solve_begin();
+    for chunk in input.as_bytes().chunks(7) {
+        assert!(unsafe { solve_append(session, chunk.as_ptr(), chunk.len()) });
+    }
+    let solution = json(solve_finish(session));
+    assert_eq!(solution["values"], json!([2.0]));
+    // the upload is closed
+    assert!(!unsafe { solve_append(session, input.as_ptr(), input.len()) });
+    assert_eq!(json(solve_finish(session))["status"], "error");
+
+    let session = solve_begin();
+    assert!(unsafe { solve_append(session, input.as_ptr(), 3) });
+    assert!(solve_abort(session));
+    assert!(!solve_abort(session));
+    assert_eq!(json(solve_finish(session))["status"], "error");
+}
+
+#[test]
+fn lp_and_mps_texts_are_solved() {
+    let solution = json(unsafe { solve_lp_format(LP.as_ptr(), LP.len()) });
+    assert_eq!(solution["values"], json!([2.0]));
+    assert_eq!(solution["named_slacks"], json!({"cap": 0.0}));
+    let solution = json(unsafe { solve_mps(MPS.as_ptr(), MPS.len()) });
+    assert_eq!(solution["values"], json!([2.0]));
+    assert_eq!(solution["objective"], 2.0);
+    assert_eq!(solution["named_slacks"], json!({"cap": 0.0}));
+
+    let broken = "ROWS\n N obj\n X c\n";
+    let solution = json(unsafe { solve_mps(broken.as_ptr(), broken.len()) });
+    assert_eq!(solution["kind"], "parse");
+    assert_eq!(solution["line"], 3);
+    let broken = "max\n obj: x +\n";
+    let solution = json(unsafe { solve_lp_format(broken.as_ptr(), broken.len()) });
+    assert_eq!(solution["kind"], "parse");
+}
+
+#[test]
+fn files_are_read_by_their_extension_or_their_start() {
+    for solve in [solve_file, solve_file_mmap] {
+        let json_problem = problem().to_string();
+        let files = [
+            ("problem.json", json_problem.as_str()),
+            ("problem.lp", LP),
+            ("problem.mps", MPS),
+            ("lp-problem", LP),
+            ("mps-problem", MPS),
+            ("json-problem", json_problem.as_str()),
+        ];
+        for (name, contents) in files {
+            let solution = solve_path(name, contents, solve);
+            assert_eq!(solution["values"], json!([2.0]), "{name}");
+        }
+    }
+
+    let missing = CString::new(temp_path("missing.json").to_str().unwrap()).unwrap();
+    let solution = json(unsafe { solve_file(missing.as_ptr()) });
+    assert_eq!(solution["status"], "error");
+}
+
+#[cfg(feature = "highs")]
+#[test]
+fn models_are_dumped_as_highs_holds_them() {
+    let mut input = problem();
+    input["dump_model"] = json!({"format": "lp"});
+    let input = input.to_string();
+    let solution = json(unsafe { solve(input.as_ptr(), input.len()) });
+    let dump = &solution["model_dump"];
+    assert_eq!(dump["format"], "lp");
+    let lp = dump["text"].as_str().unwrap();
+    assert!(lp.contains("cap: 1 x <= 2"), "{lp}");
+    // the dump reads back to the same solution
+    assert_eq!(
+        json(unsafe { solve_lp_format(lp.as_ptr(), lp.len()) })["objective"],
+        2.0
+    );
+}
+
+#[cfg(feature = "highs")]
+#[test]
+fn solution_files_are_written_like_highs_writes_them() {
+    let path = temp_path("solution.sol");
+    let mut input = problem();
+    input["write_solution_file"] = json!(path);
+    let input = input.to_string();
+    let solution = json(unsafe { solve(input.as_ptr(), input.len()) });
+    assert_eq!(solution["status"], "optimal");
+    let file = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(path).unwrap();
+    assert!(file.starts_with("Model status\nOptimal\n"), "{file}");
+    assert!(file.contains("\nx 2\n"), "{file}");
+}
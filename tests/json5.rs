@@ -0,0 +1,66 @@
+#![cfg(feature = "json5")]
+//! Hand-written problems in JSON5 through the C entry points, which read them
+//! only while relaxed JSON is turned on.
+
+use knorpelsolve::{free_string, set_relaxed_json, solve};
+use serde_json::{Value, json};
+use std::ffi::CStr;
+
+fn solve_text(text: &str) -> Value {
+    unsafe {
+        let response = solve(text.as_ptr(), text.len());
+        let value = serde_json::from_slice(CStr::from_ptr(response).to_bytes()).unwrap();
+        free_string(response.cast_mut());
+        value
+    }
+}
+
+const PROBLEM: &str = "
+// a small knapsack
+{
+  direction: 'max',
+  variables: [
+    {name: 'a', binary: true},
+    {name: 'b', max: 0x2, integer: true,}, // hexadecimal bound
+  ],
+  objective: [{name: 'a', factor: 3}, {name: 'b', factor: 2.}],
+  expressions: ['2*a + b <= 4' /* the capacity */],
+  verbose: false,
+}
+";
+
+// one test, since relaxed JSON is global
+#[test]
+fn solves_json5_only_while_relaxed() {
+    let strict = solve_text(PROBLEM);
+    assert!(
+        strict["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("invalid JSON:")
+    );
+
+    set_relaxed_json(true);
+    let relaxed = solve_text(PROBLEM);
+    let plain = json!({
+        "direction": "max",
+        "variables": [{"name": "a", "binary": true}, {"name": "b", "max": 2, "integer": true}],
+        "objective": [{"name": "a", "factor": 3}, {"name": "b", "factor": 2.0}],
+        "expressions": ["2*a + b <= 4"],
+        "verbose": false,
+    });
+    let json = solve_text(&plain.to_string());
+    let error = solve_text("{variables: [], objective: [], id: 'x', x: NaN}");
+    set_relaxed_json(false);
+
+    assert_eq!(relaxed["objective"], 7.0);
+    for key in ["status", "values", "objective", "activities"] {
+        assert_eq!(relaxed[key], json[key], "{key}");
+    }
+    assert_eq!(
+        error["message"],
+        "invalid JSON5: NaN is not a number at line 1 column 44"
+    );
+    assert_eq!(error["line"], 1);
+    assert!(solve_text(PROBLEM)["message"].is_string());
+}
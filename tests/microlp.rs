@@ -0,0 +1,155 @@
+#![cfg(all(feature = "highs", feature = "microlp"))]
+//! The same problems solved with HiGHS and with microlp, which have to agree
+//! on the status, the objective and the values.
+
+mod common;
+
+use common::{solve, values};
+use knorpelsolve::{free_string, get_capabilities};
+use serde_json::{Value, json};
+use std::ffi::CStr;
+
+/// Solves a problem with the given solver.
+fn solve_with(problem: &Value, solver: &str) -> Value {
+    let mut problem = problem.clone();
+    problem["options"]["solver"] = json!(solver);
+    solve(&problem)
+}
+
+fn close(a: &[f64], b: &[f64]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-6)
+}
+
+/// Solves a problem with both solvers and checks that they agree.
+fn assert_agree(problem: &Value) -> (Value, Value) {
+    let highs = solve_with(problem, "highs");
+    let microlp = solve_with(problem, "microlp");
+    assert_eq!(highs["status"], microlp["status"]);
+    if highs["status"] == "optimal" {
+        let objective = |s: &Value| s["objective"].as_f64().unwrap();
+        assert!(close(&[objective(&highs)], &[objective(&microlp)]));
+        assert!(close(&values(&highs), &values(&microlp)));
+    }
+    (highs, microlp)
+}
+
+// with a unique optimum, so that the values agree as well
+fn lp() -> Value {
+    json!({
+        "direction": "max",
+        "variables": [
+            {"name": "x", "min": 0},
+            {"name": "y", "min": 0, "max": 3},
+            {"name": "z", "min": 0, "cost": 0.5}
+        ],
+        "objective": [{"name": "x", "factor": 3}, {"name": "y", "factor": 2}],
+        "objective_offset": 1,
+        "rows": [
+            {"name": "capacity", "terms": [{"name": "x", "factor": 1}, {"name": "y", "factor": 1}], "op": "<=", "rhs": 4},
+            {"terms": [{"name": "x", "factor": 1}, {"name": "z", "factor": -1}], "op": "==", "rhs": 0}
+        ],
+        "ranged_constraints": [
+            {"terms": [{"name": "x", "factor": 1}, {"name": "y", "factor": 3}], "lower": 2, "upper": 6}
+        ]
+    })
+}
+
+#[test]
+fn lps_agree() {
+    let (highs, microlp) = assert_agree(&lp());
+    assert_eq!(microlp["status"], "optimal");
+    assert_eq!(microlp["objective"], 15.0);
+    let activities = |s: &Value| {
+        let activities = s["activities"].as_array().unwrap().iter();
+        activities.map(|a| a.as_f64().unwrap()).collect::<Vec<_>>()
+    };
+    assert!(close(&activities(&highs), &activities(&microlp)));
+    assert_eq!(microlp["named_slacks"]["capacity"], 0.0);
+    // only HiGHS has duals
+    assert!(highs["duals"].is_array());
+    assert!(microlp.get("duals").is_none());
+}
+
+#[test]
+fn milps_agree() {
+    let weights = [23, 31, 29, 44, 53, 38, 63, 85];
+    let profits = [92, 57, 49, 68, 60, 43, 67, 84];
+    let name = |i: usize| format!("x{i}");
+    let variables: Vec<_> = (0..weights.len())
+        .map(|i| json!({"name": name(i), "binary": true}))
+        .collect();
+    let terms = |factors: &[i32]| -> Vec<_> {
+        let terms = factors.iter().enumerate();
+        terms
+            .map(|(i, f)| json!({"name": name(i), "factor": f}))
+            .collect()
+    };
+    let problem = json!({
+        "direction": "max",
+        "variables": variables,
+        "objective": terms(&profits),
+        "rows": [{"terms": terms(&weights), "op": "<=", "rhs": 165}]
+    });
+    let (highs, microlp) = assert_agree(&problem);
+    assert_eq!(microlp["status"], "optimal");
+    assert_eq!(highs["values"], microlp["values"]);
+    assert_eq!(microlp["integral"], true);
+}
+
+#[test]
+fn infeasible_and_unbounded_problems_agree() {
+    let mut infeasible = lp();
+    infeasible["rows"][0]["rhs"] = json!(-1);
+    let (_, microlp) = assert_agree(&infeasible);
+    assert_eq!(microlp["status"], "infeasible");
+
+    let mut unbounded = lp();
+    unbounded["rows"] = json!([]);
+    unbounded["ranged_constraints"] = json!([]);
+    let (_, microlp) = assert_agree(&unbounded);
+    assert_eq!(microlp["status"], "unbounded");
+}
+
+#[test]
+fn microlp_rejects_what_only_highs_supports() {
+    let mut problem = lp();
+    problem["sos"] = json!([{"type": 1, "variables": ["x", "y"]}]);
+    let solution = solve_with(&problem, "microlp");
+    assert_eq!(solution["status"], "error");
+    assert_eq!(
+        solution["message"],
+        "sos is not supported by the solver microlp"
+    );
+
+    let mut problem = lp();
+    problem["options"]["threads"] = json!(2);
+    let solution = solve_with(&problem, "microlp");
+    assert_eq!(
+        solution["warnings"][0],
+        "the solver microlp ignores threads, which only HiGHS supports"
+    );
+}
+
+#[test]
+fn both_solvers_are_available() {
+    let capabilities: Value = unsafe {
+        let response = get_capabilities();
+        let value = serde_json::from_slice(CStr::from_ptr(response).to_bytes()).unwrap();
+        free_string(response.cast_mut());
+        value
+    };
+    assert_eq!(capabilities["solvers"], json!(["highs", "microlp"]));
+    assert!(
+        capabilities["features"]
+            .as_array()
+            .unwrap()
+            .contains(&json!("microlp"))
+    );
+
+    let problem = json!({"variables": [], "objective": []});
+    let solution = solve_with(&problem, "glpk");
+    assert_eq!(
+        solution["message"],
+        "solver 'glpk' is not available, the available solvers are highs, microlp"
+    );
+}
@@ -0,0 +1,730 @@
+#![cfg(feature = "highs")]
+//! The ways of writing down variables, rows and objective terms, which have
+//! to build the problems they describe.
+
+mod common;
+
+use common::{solve, values};
+use serde_json::{Value, json};
+
+fn term(name: &str, factor: f64) -> Value {
+    json!({"name": name, "factor": factor})
+}
+
+fn close(a: &[f64], b: &[f64]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-6)
+}
+
+fn objective(solution: &Value) -> f64 {
+    solution["objective"].as_f64().expect("an objective value")
+}
+
+/// Solves a problem that has to fail validation and returns the message.
+fn invalid(problem: &Value) -> String {
+    let solution = solve(problem);
+    assert_eq!(solution["status"], "error", "{solution}");
+    assert_eq!(solution["kind"], "validation", "{solution}");
+    solution["message"].as_str().unwrap().to_owned()
+}
+
+// the optimum of max x + y is where both rows hold, at x = 1.6 and y = 1.2
+fn two_rows() -> Value {
+    json!({
+        "direction": "max",
+        "variables": [
+            {"name": "x", "min": 0, "integer": false},
+            {"name": "y", "min": 0, "integer": false}
+        ],
+        "objective": [term("x", 1.0), term("y", 1.0)],
+        "objective_offset": 0,
+        "constraints": [],
+        "constraint_offsets": [],
+        "equalities": [],
+        "equalities_offsets": [],
+        "verbose": false
+    })
+}
+
+#[test]
+fn every_form_of_rows_builds_the_same_problem() {
+    let forms = [
+        json!({"rows": [
+            {"terms": [term("x", 1.0), term("y", 2.0)], "op": "<=", "rhs": 4},
+            {"terms": [term("x", -3.0), term("y", -1.0)], "op": ">=", "rhs": -6}
+        ]}),
+        json!({
+            "constraints": [[term("x", 1.0), term("y", 2.0)], [term("x", 3.0), term("y", 1.0)]],
+            "constraint_offsets": [-4, -6]
+        }),
+        json!({
+            "geq_constraints": [[term("x", -1.0), term("y", -2.0)], [term("x", -3.0), term("y", -1.0)]],
+            "geq_offsets": [4, 6]
+        }),
+        json!({"sensed_rows": {
+            "terms": [[term("x", 1.0), term("y", 2.0)], [term("x", 3.0), term("y", 1.0)]],
+            "senses": ["<=", "<="],
+            "rhs": [4, 6]
+        }}),
+        json!({"matrix": {
+            "row_starts": [0, 2, 4],
+            "col_indices": [0, 1, 0, 1],
+            "values": [1, 2, 3, 1],
+            "ops": ["<=", "<="],
+            "rhs": [4, 6]
+        }}),
+        json!({"dense_rows": [[1, 2], [3, 1]], "dense_ops": ["<=", "<="], "dense_rhs": [4, 6]}),
+        json!({"ranged_constraints": [
+            {"terms": [term("x", 1.0), term("y", 2.0)], "lower": "-inf", "upper": 4},
+            {"terms": [term("x", 3.0), term("y", 1.0)], "lower": 0, "upper": 6}
+        ]}),
+        json!({"expressions": ["x + 2*y <= 4", "3*x + y - 6 <= 0"]}),
+    ];
+    for form in forms {
+        let mut problem = two_rows();
+        problem
+            .as_object_mut()
+            .unwrap()
+            .extend(form.as_object().unwrap().clone());
+        let solution = solve(&problem);
+        assert_eq!(solution["status"], "optimal", "{form}");
+        assert!(close(&values(&solution), &[1.6, 1.2]), "{form}");
+        assert!(close(&[objective(&solution)], &[2.8]), "{form}");
+    }
+}
+
+#[test]
+fn ranged_rows_hold_at_both_ends() {
+    let mut problem = two_rows();
+    problem["direction"] = json!("min");
+    problem["ranged_constraints"] = json!([
+        {"name": "band", "terms": [term("x", 1.0), term("y", 1.0)], "lower": 2, "upper": 5}
+    ]);
+    assert_eq!(solve(&problem)["objective"], 2.0);
+    problem["direction"] = json!("max");
+    assert_eq!(solve(&problem)["objective"], 5.0);
+
+    problem["ranged_constraints"][0]["lower"] = json!(6);
+    assert_eq!(
+        invalid(&problem),
+        "ranged constraint 'band' has lower bound 6 above upper bound 5"
+    );
+}
+
+#[test]
+fn row_objects_may_be_equalities() {
+    let mut problem = two_rows();
+    problem["rows"] = json!([
+        {"terms": [term("x", 1.0), term("y", 1.0)], "op": "<=", "rhs": 4},
+        {"terms": [term("x", 1.0), term("y", -1.0)], "op": "==", "rhs": 1}
+    ]);
+    let solution = solve(&problem);
+    assert!(close(&values(&solution), &[2.5, 1.5]));
+}
+
+#[test]
+fn rows_are_reported_and_referred_to_by_name() {
+    let mut problem = two_rows();
+    problem["direction"] = json!("min");
+    problem["objective"] = json!([term("x", 1.0), term("y", 2.0)]);
+    problem["geq_constraints"] = json!([[term("x", 1.0), term("y", 1.0)]]);
+    problem["geq_offsets"] = json!([-3]);
+    problem["geq_names"] = json!(["demand"]);
+    problem["equalities"] = json!([[term("y", 2.0)]]);
+    problem["equalities_offsets"] = json!([-1]);
+    problem["equality_names"] = json!(["half"]);
+    let solution = solve(&problem);
+    assert!(close(&values(&solution), &[2.5, 0.5]));
+    assert_eq!(
+        solution["named_slacks"],
+        json!({"demand": 0.0, "half": 0.0})
+    );
+    // of the expressions, which have the offsets in them
+    assert_eq!(
+        solution["named_duals"],
+        json!({"demand": -1.0, "half": 0.5})
+    );
+
+    problem["geq_constraints"][0][0]["name"] = json!("z");
+    assert_eq!(invalid(&problem), "unknown variable 'z' in row 'demand'");
+    problem["geq_names"] = json!(["half"]);
+    assert_eq!(invalid(&problem), "duplicate row name 'half'");
+    problem["geq_names"] = json!(["demand", "supply"]);
+    assert_eq!(invalid(&problem), "got 1 geq rows but 2 geq names");
+}
+
+#[test]
+fn terms_refer_to_names_positions_and_array_elements() {
+    let solution = solve(&json!({
+        "direction": "max",
+        "output": "named",
+        "variables": [{"name": "x", "min": 0, "max": 3}],
+        "variable_arrays": [{"name": "z", "shape": [1, 2], "min": 0, "max": 1}],
+        "objective": [
+            {"index": 0, "factor": 1},
+            {"array": "z", "indices": [0, 1], "factor": 2},
+            {"name": "z[0][0]", "factor": -1}
+        ],
+        "verbose": false
+    }));
+    assert_eq!(solution["values"], json!([3.0, 0.0, 1.0]));
+    assert_eq!(
+        solution["named_values"],
+        json!({"x": 3.0, "z[0][0]": 0.0, "z[0][1]": 1.0})
+    );
+
+    let mut problem = two_rows();
+    problem["constraints"] = json!([
+        [{"index": 0, "factor": 1}, term("y", 2.0)],
+        [term("x", 3.0), {"index": 1, "factor": 1}]
+    ]);
+    problem["constraint_offsets"] = json!([-4, -6]);
+    assert!(close(&values(&solve(&problem)), &[1.6, 1.2]));
+
+    problem["constraints"][0][0]["index"] = json!(2);
+    assert_eq!(
+        invalid(&problem),
+        "variable index 2 is out of range for 2 variables in row 0"
+    );
+}
+
+#[test]
+fn matrices_have_to_be_well_formed() {
+    let mut problem = two_rows();
+    problem["matrix"] = json!({
+        "row_starts": [0, 2, 4],
+        "col_indices": [0, 1, 0, 2],
+        "values": [1, 2, 3, 1],
+        "ops": ["<=", "<="],
+        "rhs": [4, 6]
+    });
+    assert_eq!(
+        invalid(&problem),
+        "matrix column index 2 at position 3 is out of range for 2 variables"
+    );
+    problem["matrix"]["row_starts"] = json!([0, 3, 2]);
+    assert_eq!(invalid(&problem), "matrix row_starts decrease after row 1");
+    problem["matrix"]["row_starts"] = json!([0, 2, 3]);
+    assert_eq!(
+        invalid(&problem),
+        "matrix row_starts must end at 4, the number of values"
+    );
+
+    let mut problem = two_rows();
+    problem["dense_rows"] = json!([[1, 2], [3]]);
+    problem["dense_ops"] = json!(["<=", "<="]);
+    problem["dense_rhs"] = json!([4]);
+    assert_eq!(
+        invalid(&problem),
+        "got 2 dense rows but 2 ops and 1 right-hand sides"
+    );
+    problem["dense_rhs"] = json!([4, 6]);
+    assert_eq!(
+        invalid(&problem),
+        "dense row 1 has 1 coefficients but there are 2 variables"
+    );
+}
+
+#[test]
+fn the_objective_may_map_names_to_factors() {
+    let mut problem = two_rows();
+    problem["rows"] = json!([
+        {"terms": [term("x", 1.0), term("y", 2.0)], "op": "<=", "rhs": 4},
+        {"terms": [term("x", 3.0), term("y", 1.0)], "op": "<=", "rhs": 6}
+    ]);
+    problem["objective"] = json!({"y": 3, "x": 1});
+    let solution = solve(&problem);
+    assert!(close(&values(&solution), &[0.0, 2.0]));
+    assert!(close(&[objective(&solution)], &[6.0]));
+
+    problem["objective"] = json!({"z": 1});
+    assert_eq!(invalid(&problem), "unknown variable 'z'");
+}
+
+#[test]
+fn omitted_sections_and_offsets_are_empty_or_zero() {
+    let solution = solve(&json!({
+        "direction": "max",
+        "variables": [
+            {"name": "x", "min": 0, "max": 2, "integer": false},
+            {"name": "y", "min": 0, "integer": false}
+        ],
+        "objective": {"x": 1, "y": 1},
+        // y <= x without an offset
+        "constraints": [[term("y", 1.0), term("x", -1.0)]],
+        "verbose": false
+    }));
+    assert!(close(&values(&solution), &[2.0, 2.0]));
+    assert_eq!(solution["objective"], 4.0);
+}
+
+#[test]
+fn strict_problems_reject_unknown_keys() {
+    let mut problem = two_rows();
+    problem["rows"] = json!([{"terms": [term("x", 1.0), term("y", 1.0)], "op": "<=", "rhs": 1}]);
+    problem["varaibles"] = json!([]);
+    assert_eq!(solve(&problem)["status"], "optimal");
+    problem["strict"] = json!(true);
+    let solution = solve(&problem);
+    assert_eq!(solution["kind"], "parse");
+    assert_eq!(solution["message"], "unknown field 'varaibles'");
+}
+
+#[test]
+fn duplicate_terms_follow_the_policy() {
+    let problem = |policy: &str| {
+        json!({
+            "direction": "max",
+            "duplicate_policy": policy,
+            "variables": [{"name": "x", "min": 0, "max": 1, "integer": false}],
+            "objective": [term("x", 1.0), term("x", 2.0)],
+            "verbose": false
+        })
+    };
+    assert_eq!(solve(&problem("sum"))["objective"], 3.0);
+    assert_eq!(solve(&problem("last"))["objective"], 2.0);
+    assert_eq!(invalid(&problem("error")), "duplicate variable 'x'");
+
+    let mut rows = problem("error");
+    rows["objective"] = json!([term("x", 1.0)]);
+    rows["constraints"] = json!([[term("x", 1.0), term("x", 1.0)]]);
+    assert_eq!(invalid(&rows), "duplicate variable 'x' in row 0");
+}
+
+#[test]
+fn binary_and_fixed_variables_are_checked() {
+    let problem = |variable: Value| {
+        json!({
+            "direction": "max",
+            "variables": [variable],
+            "objective": [term("b", 1.0)],
+            "constraints": [[term("b", 2.0)]],
+            "constraint_offsets": [-1.5],
+            "verbose": false
+        })
+    };
+    // without integrality b would be 0.75
+    let solution = solve(&problem(json!({"name": "b", "binary": true})));
+    assert_eq!(solution["values"], json!([0]));
+    assert_eq!(
+        invalid(&problem(json!({"name": "b", "binary": true, "max": 2}))),
+        "binary variable 'b' has bounds [0, 2] outside of [0, 1]"
+    );
+    assert_eq!(
+        invalid(&problem(
+            json!({"name": "b", "min": 0, "max": 3, "fixed": 5})
+        )),
+        "variable 'b' is fixed to 5 outside of its bounds [0, 3]"
+    );
+    assert_eq!(
+        invalid(&problem(
+            json!({"name": "b", "integer": true, "fixed": 1.5})
+        )),
+        "integer variable 'b' is fixed to the fractional value 1.5"
+    );
+    // fixed although the row allows at most 0.75
+    let solution = solve(&problem(
+        json!({"name": "b", "min": 0, "max": 3, "fixed": 2}),
+    ));
+    assert_eq!(solution["status"], "infeasible");
+    let mut problem = problem(json!({"name": "b", "min": 0, "max": 3, "fixed": 0.5}));
+    problem["direction"] = json!("min");
+    assert_eq!(solve(&problem)["values"], json!([0.5]));
+}
+
+#[test]
+fn semicontinuous_variables_are_zero_or_within_their_bounds() {
+    let mut problem = json!({
+        "direction": "min",
+        "variables": [{"name": "s", "min": 2, "max": 5, "semicontinuous": true}],
+        "objective": [term("s", 1.0)],
+        "rows": [{"terms": [term("s", 1.0)], "op": ">=", "rhs": 1}],
+        "verbose": false
+    });
+    assert_eq!(solve(&problem)["values"], json!([2.0]));
+    problem["rows"] = json!([]);
+    assert_eq!(solve(&problem)["values"], json!([0.0]));
+
+    problem["variables"][0]["max"] = json!(null);
+    assert_eq!(
+        invalid(&problem),
+        "semicontinuous variable 's' needs a finite min and max"
+    );
+}
+
+#[test]
+fn special_ordered_sets_limit_the_nonzeros() {
+    let mut problem = json!({
+        "direction": "max",
+        "variables": [
+            {"name": "a", "min": 0, "max": 1},
+            {"name": "b", "min": 0, "max": 1},
+            {"name": "c", "min": 0, "max": 1}
+        ],
+        "objective": [term("a", 1.0), term("b", 0.5), term("c", 2.0)],
+        "sos": [{"type": 1, "variables": ["a", "b", "c"]}],
+        "verbose": false
+    });
+    let solution = solve(&problem);
+    assert!(close(&values(&solution), &[0.0, 0.0, 1.0]));
+    // the nonzeros of type 2 sets have to be neighbours
+    problem["sos"][0]["type"] = json!(2);
+    let solution = solve(&problem);
+    assert!(close(&values(&solution), &[0.0, 1.0, 1.0]));
+    problem["sos"][0]["weights"] = json!([1, 3, 2]);
+    let solution = solve(&problem);
+    assert!(close(&values(&solution), &[1.0, 0.0, 1.0]));
+
+    problem["sos"][0]["type"] = json!(3);
+    assert_eq!(invalid(&problem), "sos 0 has type 3 instead of 1 or 2");
+    problem["sos"][0]["type"] = json!(1);
+    problem["variables"][2]["max"] = json!(null);
+    assert_eq!(invalid(&problem), "sos 0 needs finite bounds on 'c'");
+}
+
+#[test]
+fn indicators_hold_while_the_binary_has_the_active_value() {
+    let mut problem = json!({
+        "direction": "max",
+        "variables": [{"name": "x", "min": 0, "max": 10}, {"name": "z", "binary": true}],
+        "objective": [term("x", 1.0), term("z", -3.0)],
+        "indicators": [{"variable": "z", "active_value": 0, "terms": [term("x", 1.0)], "op": "<=", "rhs": 2}],
+        "verbose": false
+    });
+    assert!(close(&values(&solve(&problem)), &[10.0, 1.0]));
+    problem["objective"][1]["factor"] = json!(-9);
+    assert!(close(&values(&solve(&problem)), &[2.0, 0.0]));
+
+    problem["variables"][0]["max"] = json!(null);
+    assert_eq!(
+        invalid(&problem),
+        "cannot derive a finite big_m from the variable bounds in indicator 0"
+    );
+    // a big_m that is too small cuts off the large values of x
+    problem["indicators"][0]["big_m"] = json!(100);
+    assert!(close(&values(&solve(&problem)), &[102.0, 1.0]));
+    problem["indicators"][0]["variable"] = json!("x");
+    assert_eq!(
+        invalid(&problem),
+        "variable 'x' is not binary in indicator 0"
+    );
+}
+
+#[test]
+fn quadratic_objectives_are_minimised() {
+    let mut problem = json!({
+        "direction": "min",
+        "variables": [{"name": "x"}, {"name": "y"}],
+        "objective": [term("x", -2.0), term("y", -4.0)],
+        "objective_quadratic": [
+            {"name_a": "x", "name_b": "x", "factor": 1},
+            {"name_a": "y", "name_b": "y", "factor": 1}
+        ],
+        "verbose": false
+    });
+    let solution = solve(&problem);
+    assert!(close(&values(&solution), &[1.0, 2.0]));
+    assert!(close(&[objective(&solution)], &[-5.0]));
+
+    problem["variables"][0]["integer"] = json!(true);
+    assert_eq!(
+        invalid(&problem),
+        "quadratic objective terms require a problem without integer variables"
+    );
+}
+
+#[test]
+fn piecewise_terms_fill_their_segments_in_order() {
+    let problem = |slopes: [f64; 2]| {
+        json!({
+            "direction": "min",
+            "variables": [{"name": "x", "min": 0, "max": 10}],
+            "objective": [],
+            "piecewise": [{"variable": "x", "breakpoints": [0, 2, 10], "slopes": slopes}],
+            "rows": [{"terms": [term("x", 1.0)], "op": ">=", "rhs": 4}],
+            "verbose": false
+        })
+    };
+    // convex, so the objective fills the cheap segment first by itself
+    assert!(close(&[objective(&solve(&problem([1.0, 3.0])))], &[8.0]));
+    // concave, where binaries keep the expensive segment first
+    assert!(close(&[objective(&solve(&problem([3.0, 1.0])))], &[8.0]));
+
+    let mut problem = problem([1.0, 3.0]);
+    problem["piecewise"][0]["breakpoints"] = json!([0, 2, 2]);
+    assert_eq!(
+        invalid(&problem),
+        "breakpoints do not increase in piecewise term 0"
+    );
+}
+
+#[test]
+fn absolute_values_are_linearised() {
+    let mut problem = json!({
+        "direction": "min",
+        "variables": [{"name": "x", "min": 0, "max": 10}],
+        "objective": [],
+        "abs_terms": [{"terms": [term("x", 1.0)], "offset": -3, "factor": 2}],
+        "rows": [{"terms": [term("x", 1.0)], "op": "<=", "rhs": 1}],
+        "verbose": false
+    });
+    let solution = solve(&problem);
+    assert!(close(&values(&solution), &[1.0]));
+    assert!(close(&[objective(&solution)], &[4.0]));
+
+    problem["abs_terms"][0]["factor"] = json!(-2);
+    assert_eq!(
+        invalid(&problem),
+        "factor -2 would reward a large absolute value, which the objective cannot express in abs term 0"
+    );
+}
+
+#[test]
+fn extrema_are_helper_variables() {
+    // the helper variables are not reported
+    let solution = solve(&json!({
+        "direction": "min",
+        "variables": [{"name": "x", "min": 0}, {"name": "y", "min": 0}],
+        "max_of": [{"name": "m", "expressions": [{"terms": [term("x", 1.0)]}, {"terms": [term("y", 1.0)]}]}],
+        "objective": [term("m", 1.0)],
+        "rows": [{"terms": [term("x", 1.0), term("y", 1.0)], "op": ">=", "rhs": 4}],
+        "verbose": false
+    }));
+    assert!(close(&values(&solution), &[2.0, 2.0]));
+    let solution = solve(&json!({
+        "direction": "max",
+        "variables": [{"name": "x", "min": 0, "max": 5}],
+        "min_of": [{"name": "n", "expressions": [
+            {"terms": [term("x", 1.0)], "offset": 1},
+            {"terms": [term("x", -1.0)], "offset": 5}
+        ]}],
+        "objective": [term("n", 1.0)],
+        "verbose": false
+    }));
+    assert!(close(&values(&solution), &[2.0]));
+    assert!(close(&[objective(&solution)], &[3.0]));
+}
+
+#[test]
+fn costs_add_to_the_objective() {
+    let problem = json!({
+        "direction": "min",
+        "variables": [{"name": "x", "min": 0, "max": 1.5, "cost": 1}, {"name": "y", "min": 0}],
+        "objective": {"y": 3, "x": 1},
+        "rows": [{"terms": [term("x", 1.0), term("y", 1.0)], "op": ">=", "rhs": 2}],
+        "verbose": false
+    });
+    let solution = solve(&problem);
+    assert!(close(&values(&solution), &[1.5, 0.5]));
+    assert_eq!(solution["objective"], 4.5);
+
+    // costs alone are an objective as well
+    let mut problem = problem;
+    problem["objective"] = json!({});
+    problem["variables"][1]["cost"] = json!(3);
+    assert_eq!(solve(&problem)["objective"], 3.0);
+}
+
+#[test]
+fn bounds_may_be_infinite() {
+    let mut problem = json!({
+        "direction": "max",
+        "variables": [{"name": "x", "min": "-INF", "max": "+inf"}],
+        "objective": [term("x", -1.0)],
+        "ranged_constraints": [{"terms": [term("x", 1.0)], "lower": -2, "upper": "inf"}],
+        "verbose": false
+    });
+    assert_eq!(solve(&problem)["values"], json!([-2.0]));
+
+    problem["variables"][0]["max"] = json!("infinity");
+    let solution = solve(&problem);
+    assert_eq!(solution["kind"], "parse");
+    let message = solution["message"].as_str().unwrap();
+    assert!(
+        message.contains(
+            "max must be a number, a fraction p/q, \"inf\" or \"-inf\", not \"infinity\""
+        ),
+        "{message}"
+    );
+}
+
+#[test]
+fn objective_expressions_add_to_the_objective() {
+    let mut problem = json!({
+        "direction": "max",
+        "variables": [{"name": "x", "min": 0}, {"name": "y", "min": 0}],
+        "objective": [term("x", 1.0)],
+        "objective_expr": "x + y + 1",
+        "expressions": ["x + y <= 3", "x <= 2"],
+        "verbose": false
+    });
+    let solution = solve(&problem);
+    assert!(close(&values(&solution), &[2.0, 1.0]));
+    assert_eq!(solution["objective"], 6.0);
+
+    // errors point at the offending token
+    problem["expressions"][1] = json!("x * y <= 2");
+    let solution = solve(&problem);
+    assert_eq!(solution["kind"], "parse");
+    assert_eq!(solution["offset"], 4);
+    assert_eq!(solution["token"], "y");
+    problem["expressions"][1] = json!("z <= 2");
+    let solution = solve(&problem);
+    assert_eq!(
+        solution["message"],
+        "expression 1 at offset 0: unknown variable 'z'"
+    );
+    assert_eq!(solution["token"], "z");
+}
+
+#[test]
+fn names_match_as_requested() {
+    let problem = |matching: &str, name: &str| {
+        json!({
+            "direction": "max",
+            "name_matching": matching,
+            "variables": [{"name": "Stock", "min": 0, "max": 2}],
+            "objective": [term(name, 1.0)],
+            "verbose": false
+        })
+    };
+    assert_eq!(
+        solve(&problem("case_insensitive", "stock"))["objective"],
+        2.0
+    );
+    assert_eq!(solve(&problem("trim", " Stock "))["objective"], 2.0);
+    assert_eq!(
+        invalid(&problem("exact", "stock")),
+        "unknown variable 'stock'"
+    );
+}
+
+#[test]
+fn variable_defaults_fill_in_what_variables_leave_open() {
+    let solution = solve(&json!({
+        "direction": "max",
+        "variable_defaults": {"min": 0, "max": 10, "integer": true},
+        "variables": [
+            {"name": "x"},
+            {"name": "y", "max": 2.5, "integer": false},
+            {"name": "b", "binary": true}
+        ],
+        "objective": [term("x", 1.0), term("y", 1.0), term("b", 1.0)],
+        "verbose": false
+    }));
+    assert_eq!(solution["values"], json!([10, 2.5, 1]));
+}
+
+#[test]
+fn relaxed_problems_ignore_integrality() {
+    let mut problem = json!({
+        "direction": "max",
+        "variables": [{"name": "n", "min": 0, "integer": true}],
+        "objective": [term("n", 1.0)],
+        "rows": [{"terms": [term("n", 2.0)], "op": "<=", "rhs": 3}],
+        "verbose": false
+    });
+    let solution = solve(&problem);
+    assert_eq!(solution["values"], json!([1]));
+    assert!(solution.get("relaxed").is_none());
+    problem["relax_integrality"] = json!(true);
+    let solution = solve(&problem);
+    assert_eq!(solution["values"], json!([1.5]));
+    assert_eq!(solution["relaxed"], true);
+    assert!(solution["duals"].is_array());
+}
+
+#[test]
+fn numbers_may_be_fractions_or_infinities() {
+    let solution = solve(&json!({
+        "direction": "max",
+        "variables": [{"name": "x", "min": "-inf", "max": "1/4"}],
+        "objective": [{"name": "x", "factor": "2/1"}],
+        "objective_offset": "1/2",
+        "rows": [{"terms": [term("x", 1.0)], "op": "<=", "rhs": "inf"}],
+        "verbose": false
+    }));
+    assert_eq!(solution["objective"], 1.0);
+    // factors have to be finite
+    let solution = solve(&json!({
+        "direction": "max",
+        "variables": [{"name": "x"}],
+        "objective": [{"name": "x", "factor": "inf"}],
+        "verbose": false
+    }));
+    assert_eq!(solution["kind"], "parse");
+}
+
+#[test]
+fn small_coefficients_are_dropped_and_counted() {
+    let solution = solve(&json!({
+        "direction": "max",
+        "drop_coefficients_below": 1e-6,
+        "variables": [{"name": "x", "min": 0}, {"name": "y", "min": 0, "max": 1}],
+        "objective": [term("x", 1.0), term("y", 1e-9)],
+        "rows": [{"terms": [term("x", 1.0), term("y", 1e-7)], "op": "<=", "rhs": 2}],
+        "verbose": false
+    }));
+    assert_eq!(solution["objective"], 2.0);
+    assert_eq!(solution["stats"]["dropped_coefficients"], 2);
+}
+
+#[test]
+fn templates_add_a_row_per_index() {
+    let solution = solve(&json!({
+        "direction": "min",
+        "output": "named",
+        "variables": [
+            {"name": "x0", "fixed": 0},
+            {"name": "x1", "min": 0},
+            {"name": "x2", "min": 0}
+        ],
+        "objective": [term("x2", 1.0)],
+        "constraint_templates": [{
+            "name": "step",
+            "index": "t",
+            "range": [0, 2],
+            "terms": [{"name": "x{t+1}", "factor": 1}, {"name": "x{t}", "factor": -1}],
+            "op": ">=",
+            "rhs": 1
+        }],
+        "verbose": false
+    }));
+    assert!(close(&values(&solution), &[0.0, 1.0, 2.0]));
+    assert_eq!(
+        solution["named_slacks"],
+        json!({"step[0]": 0.0, "step[1]": 0.0})
+    );
+}
+
+#[test]
+fn problems_without_an_objective_ask_for_a_feasible_point() {
+    let solution = solve(&json!({
+        "variables": [{"name": "x", "min": 1, "max": 2}],
+        "objective": [],
+        "verbose": false
+    }));
+    assert_eq!(solution["status"], "optimal");
+    assert_eq!(solution["feasibility"], true);
+    let message = invalid(&json!({
+        "variables": [{"name": "x", "min": 1, "max": 2}],
+        "objective": [term("x", 1.0)],
+        "verbose": false
+    }));
+    assert!(message.contains("direction"), "{message}");
+}
+
+#[test]
+fn sensed_rows_need_a_sense_and_rhs_per_row() {
+    let mut problem = two_rows();
+    problem["sensed_rows"] = json!({
+        "terms": [[term("x", 1.0)], [term("y", 1.0)]],
+        "senses": ["<=", "L"],
+        "rhs": [1, 1]
+    });
+    assert_eq!(invalid(&problem), "sense 1 is 'L' instead of <=, >= or ==");
+    problem["sensed_rows"]["senses"] = json!(["<="]);
+    assert_eq!(
+        invalid(&problem),
+        "sensed_rows has 2 term lists, 1 senses and 2 right-hand sides, so row 1 is incomplete"
+    );
+}
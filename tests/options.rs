@@ -0,0 +1,318 @@
+#![cfg(feature = "highs")]
+//! The settings in `options`, which are checked before solving and then
+//! passed to HiGHS, which reports the ones it used in `stats`.
+
+mod common;
+
+use common::{solve, values};
+use serde_json::{Value, json};
+
+// a small MIP with its optimum at a = 1 and b = 2
+fn mip(options: &Value) -> Value {
+    json!({
+        "direction": "max",
+        "variables": [
+            {"name": "a", "binary": true},
+            {"name": "b", "integer": true, "min": 0, "max": 3}
+        ],
+        "objective": {"a": 3, "b": 2},
+        "rows": [{"terms": [{"name": "a", "factor": 2}, {"name": "b", "factor": 1}], "op": "<=", "rhs": 4}],
+        "verbose": false,
+        "options": options
+    })
+}
+
+// an LP with its optimum at x = 1
+fn lp(options: &Value) -> Value {
+    json!({
+        "direction": "max",
+        "variables": [{"name": "x", "min": 0, "max": 2}],
+        "objective": {"x": 1},
+        "rows": [{"terms": [{"name": "x", "factor": 1}], "op": "<=", "rhs": 1}],
+        "verbose": false,
+        "options": options
+    })
+}
+
+#[test]
+fn invalid_settings_are_rejected_before_solving() {
+    let cases = [
+        (
+            json!({"time_limit_seconds": 0}),
+            "time_limit_seconds must be positive, not 0",
+        ),
+        (
+            json!({"time_limit_seconds": -1}),
+            "time_limit_seconds must be positive, not -1",
+        ),
+        (
+            json!({"mip_gap": 1}),
+            "mip_gap must be at least 0 and less than 1, not 1",
+        ),
+        (
+            json!({"seed": 3_000_000_000_u32}),
+            "seed must be at most 2147483647, not 3000000000",
+        ),
+        (
+            json!({"primal_feasibility_tolerance": 0.2}),
+            "primal_feasibility_tolerance must be above 0 and at most 0.1, not 0.2",
+        ),
+        (
+            json!({"dual_feasibility_tolerance": 0}),
+            "dual_feasibility_tolerance must be above 0 and at most 0.1, not 0",
+        ),
+        (
+            json!({"integrality_tolerance": 0.5}),
+            "integrality_tolerance must be between 0 and 0.5, not 0.5",
+        ),
+        (
+            json!({"improving_solution_limit": 0}),
+            "improving_solution_limit must be at least 1",
+        ),
+    ];
+    for (options, message) in cases {
+        let solution = solve(&mip(&options));
+        assert_eq!(solution["kind"], "validation");
+        assert_eq!(solution["message"], message);
+    }
+
+    let solution = solve(&mip(&json!({"presolve": "sometimes"})));
+    assert_eq!(solution["kind"], "parse");
+
+    let solution = solve(&mip(&json!({"time_limit_seconds": 60, "mip_gap": 0.5})));
+    assert_eq!(values(&solution), [1.0, 2.0]);
+    assert!(solution["mip_gap"].as_f64().unwrap() <= 0.5);
+}
+
+#[test]
+fn the_settings_used_are_reported() {
+    let solution = solve(&mip(&json!({
+        "threads": 1,
+        "seed": 7,
+        "primal_feasibility_tolerance": 1e-6,
+        "dual_feasibility_tolerance": 1e-5
+    })));
+    assert_eq!(solution["status"], "optimal");
+    let stats = &solution["stats"];
+    assert_eq!(stats["threads"], 1);
+    assert_eq!(stats["seed"], 7);
+    assert_eq!(stats["primal_feasibility_tolerance"], 1e-6);
+    assert_eq!(stats["dual_feasibility_tolerance"], 1e-5);
+
+    // the defaults of HiGHS otherwise
+    let stats = &solve(&mip(&json!({})))["stats"];
+    assert!(stats.get("threads").is_none());
+    assert_eq!(stats["seed"], 0);
+    assert_eq!(stats["primal_feasibility_tolerance"], 1e-7);
+}
+
+#[test]
+fn verbosity_is_an_option() {
+    for options in [
+        json!({}),
+        json!({"verbose": false}),
+        json!({"verbose": true}),
+    ] {
+        let mut problem = mip(&options);
+        problem.as_object_mut().unwrap().remove("verbose");
+        assert_eq!(values(&solve(&problem)), [1.0, 2.0], "{options}");
+    }
+    let mut problem = mip(&json!({"verbose": "loud"}));
+    problem.as_object_mut().unwrap().remove("verbose");
+    assert_eq!(solve(&problem)["kind"], "parse");
+}
+
+#[test]
+fn verbosity_lengthens_the_captured_log() {
+    let log = |options: &Value| {
+        let mut problem = mip(options);
+        problem.as_object_mut().unwrap().remove("verbose");
+        let solution = solve(&problem);
+        assert_eq!(solution["status"], "optimal");
+        solution["log"].as_str().unwrap().len()
+    };
+    let quiet = log(&json!({"capture_log": true}));
+    let verbose = log(&json!({"capture_log": true, "verbose": true}));
+    assert!(
+        quiet < verbose,
+        "{quiet} bytes of quiet log, {verbose} of verbose log"
+    );
+}
+
+#[test]
+fn presolve_reports_nothing_removed_when_it_is_off() {
+    let solution = solve(&mip(&json!({"presolve": "off"})));
+    assert_eq!(values(&solution), [1.0, 2.0]);
+    assert_eq!(
+        solution["stats"]["presolve"],
+        json!({"rows_removed": 0, "columns_removed": 0, "nonzeros_removed": 0})
+    );
+    let presolve = &solve(&mip(&json!({"presolve": "on"})))["stats"]["presolve"];
+    for key in ["rows_removed", "columns_removed", "nonzeros_removed"] {
+        assert!(presolve[key].as_i64().is_some(), "{presolve}");
+    }
+}
+
+#[test]
+fn every_algorithm_finds_the_optimum() {
+    for algorithm in ["simplex", "dual_simplex", "ipm"] {
+        let options = json!({"solver_algorithm": algorithm});
+        let solution = solve(&lp(&options));
+        assert_eq!(solution["status"], "optimal", "{algorithm}");
+        assert!((values(&solution)[0] - 1.0).abs() < 1e-6, "{algorithm}");
+        // the relaxations of MIPs are solved with the simplex method anyway
+        assert_eq!(values(&solve(&mip(&options))), [1.0, 2.0], "{algorithm}");
+    }
+    let solution = solve(&lp(&json!({"solver_algorithm": "barrier"})));
+    assert_eq!(solution["kind"], "parse");
+}
+
+#[test]
+fn raw_options_that_highs_rejects_are_warnings() {
+    let solution = solve(&mip(&json!({"raw_options": {
+        "mip_heuristic_effort": 0.5,
+        "no_such_option": 1,
+        "presolve": 2
+    }})));
+    assert_eq!(solution["status"], "optimal");
+    assert_eq!(
+        solution["warnings"],
+        json!([
+            "HiGHS rejected the raw options 'no_such_option', 'presolve', which are unknown or have invalid values"
+        ])
+    );
+}
+
+#[test]
+fn crossover_only_applies_to_the_interior_point_solver() {
+    let solution = solve(&mip(&json!({"crossover": false})));
+    assert_eq!(
+        solution["warnings"],
+        json!(["crossover is ignored unless solver_algorithm is ipm"])
+    );
+
+    let solution = solve(&lp(&json!({"solver_algorithm": "ipm", "crossover": false})));
+    assert_eq!(solution["status"], "optimal");
+    assert_eq!(solution["interior"], true);
+    assert_eq!(solution["duals"], json!([1.0]));
+    let solution = solve(&lp(&json!({"solver_algorithm": "ipm", "crossover": true})));
+    assert!(solution.get("interior").is_none());
+}
+
+#[test]
+fn scaling_is_an_option_next_to_the_coefficient_range() {
+    for scaling in [
+        "off",
+        "choose",
+        "equilibration",
+        "forced_equilibration",
+        "max_value",
+    ] {
+        let solution = solve(&mip(&json!({"scaling": scaling})));
+        assert_eq!(values(&solution), [1.0, 2.0], "{scaling}");
+        assert_eq!(
+            solution["stats"]["coefficients"],
+            json!({"min_abs": 1.0, "max_abs": 2.0})
+        );
+    }
+    let solution = solve(&mip(&json!({"scaling": "geometric"})));
+    assert_eq!(solution["kind"], "parse");
+}
+
+#[test]
+fn emphasis_is_reported_with_the_options_it_sets() {
+    let solution = solve(&mip(&json!({"emphasis": "feasibility"})));
+    assert_eq!(solution["values"], json!([1, 2]));
+    let emphasis = solution["stats"]["emphasis"].as_object().unwrap();
+    assert_eq!(emphasis["mip_heuristic_effort"], "0.3");
+    // balanced sets the defaults of HiGHS back
+    let solution = solve(&mip(&json!({"emphasis": "balanced"})));
+    assert_eq!(
+        solution["stats"]["emphasis"]["mip_heuristic_effort"],
+        "0.05"
+    );
+    assert!(solve(&mip(&json!({})))["stats"].get("emphasis").is_none());
+}
+
+#[test]
+fn branch_priorities_are_reported_as_hints() {
+    let mut problem = mip(&json!({}));
+    problem["variables"][1]["branch_priority"] = json!(1);
+    let solution = solve(&problem);
+    assert_eq!(values(&solution), [1.0, 2.0]);
+    assert_eq!(solution["stats"]["branch_priorities_native"], false);
+    assert!(
+        solve(&mip(&json!({})))["stats"]
+            .get("branch_priorities_native")
+            .is_none()
+    );
+
+    let mut problem = lp(&json!({}));
+    problem["variables"][0]["branch_priority"] = json!(1);
+    assert_eq!(
+        solve(&problem)["warnings"],
+        json!(["branch_priority is ignored for the continuous variables 'x'"])
+    );
+}
+
+#[test]
+fn the_time_limit_stops_the_solve() {
+    // a market split problem, which takes branch and bound seconds to prove
+    // infeasible
+    let n = 40;
+    let coefficient = |row: u64, i: u64| {
+        let x = (row * n + i + 1).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        (x >> 32) % 100
+    };
+    let variables: Vec<_> = (0..n)
+        .map(|i| json!({"name": format!("x{i}"), "binary": true}))
+        .collect();
+    let rows: Vec<_> = (0..4)
+        .map(|row| {
+            let terms: Vec<_> = (0..n)
+                .map(|i| json!({"name": format!("x{i}"), "factor": coefficient(row, i)}))
+                .collect();
+            let rhs = (0..n).map(|i| coefficient(row, i)).sum::<u64>() / 2;
+            json!({"terms": terms, "op": "==", "rhs": rhs})
+        })
+        .collect();
+    let problem = json!({
+        "direction": "max",
+        "variables": variables,
+        "objective": {},
+        "rows": rows,
+        "verbose": false,
+        "options": {"time_limit_seconds": 0.01}
+    });
+    let start = std::time::Instant::now();
+    let solution = solve(&problem);
+    assert!(start.elapsed().as_secs() < 5);
+    assert_eq!(solution["status"], "timelimit", "{solution}");
+}
+
+#[test]
+fn the_memory_estimate_is_checked_against_the_limit() {
+    let solution = solve(&mip(&json!({"memory_limit_mb": 1})));
+    let memory = &solution["stats"]["memory"];
+    assert_eq!(memory["columns"], 2);
+    assert_eq!(memory["rows"], 1);
+    assert_eq!(memory["nonzeros"], 2);
+    assert_eq!(memory["limit_mb"], 1);
+
+    let solution = solve(&mip(&json!({"memory_limit_mb": 0})));
+    assert_eq!(solution["kind"], "resource_limit");
+    let message = solution["message"].as_str().unwrap();
+    assert!(
+        message.contains("more than memory_limit_mb of 0"),
+        "{message}"
+    );
+}
+
+#[test]
+fn settings_outside_of_options_give_way_to_them() {
+    let mut problem = mip(&json!({}));
+    problem["integrality_tolerance"] = json!(0.6);
+    assert_eq!(solve(&problem)["kind"], "validation");
+    problem["options"] = json!({"integrality_tolerance": 0.1});
+    assert_eq!(solve(&problem)["status"], "optimal");
+}
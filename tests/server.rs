@@ -0,0 +1,81 @@
+#![cfg(feature = "server")]
+
+use knorpelsolve::server::{Config, Response, Server};
+use serde_json::{Value, json};
+
+fn handle(problem: &Value) -> (u16, Value) {
+    let server = Server::new(Config::default());
+    let response = server.handle("POST", "/solve", problem.to_string().as_bytes());
+    (response.status, body(&response))
+}
+
+fn body(response: &Response) -> Value {
+    serde_json::from_slice(&response.body).unwrap()
+}
+
+fn problem(max: f64) -> Value {
+    json!({
+        "direction": "max",
+        "variables": [{"name": "x", "min": 0, "max": max}],
+        "objective": [{"name": "x", "factor": 2}],
+        "expressions": ["x >= 1"],
+        "verbose": false
+    })
+}
+
+#[test]
+fn solutions_are_ok() {
+    let (status, solution) = handle(&problem(3.0));
+    assert_eq!(status, 200);
+    assert_eq!(solution["status"], "optimal");
+    assert_eq!(solution["objective"], 6.0);
+    let (status, solution) = handle(&problem(0.5));
+    assert_eq!(status, 200);
+    assert_eq!(solution["status"], "infeasible");
+}
+
+#[test]
+fn parse_and_validation_errors_are_bad_requests() {
+    let server = Server::new(Config::default());
+    let response = server.handle("POST", "/solve", b"{\"variables\": ");
+    assert_eq!(response.status, 400);
+    assert_eq!(body(&response)["kind"], "parse");
+    let mut problem = problem(3.0);
+    problem["objective"] = json!([{"name": "y", "factor": 1}]);
+    let (status, error) = handle(&problem);
+    assert_eq!(status, 400);
+    assert_eq!(error["kind"], "validation");
+}
+
+#[test]
+fn problems_over_the_memory_limit_are_too_large() {
+    let mut problem = problem(3.0);
+    problem["variables"] = json!(
+        (0..5000)
+            .map(|i| json!({"name": format!("x{i}")}))
+            .collect::<Vec<_>>()
+    );
+    problem["objective"] = json!([{"name": "x0", "factor": 1}]);
+    problem["expressions"] = json!([]);
+    problem["options"] = json!({"memory_limit_mb": 1});
+    let (status, error) = handle(&problem);
+    assert_eq!(status, 413);
+    assert_eq!(error["kind"], "resource_limit");
+}
+
+#[test]
+fn other_routes_are_rejected() {
+    let server = Server::new(Config::default());
+    let body = problem(3.0).to_string();
+    assert_eq!(server.handle("POST", "/", body.as_bytes()).status, 404);
+    assert_eq!(server.handle("GET", "/solve", body.as_bytes()).status, 405);
+}
+
+#[test]
+fn pretty_output_applies_to_the_body() {
+    let mut problem = problem(3.0);
+    problem["options"] = json!({"pretty_output": true});
+    let server = Server::new(Config::default());
+    let response = server.handle("POST", "/solve", problem.to_string().as_bytes());
+    assert!(response.body.starts_with(b"{\n"));
+}
@@ -0,0 +1,416 @@
+#![cfg(feature = "highs")]
+//! What a solution reports besides the values.
+
+mod common;
+
+use common::{solve, values};
+use serde_json::{Value, json};
+use std::ffi::CStr;
+
+fn term(name: &str, factor: f64) -> Value {
+    json!({"name": name, "factor": factor})
+}
+
+// optimal at x = 3 and y = 1, where only the first row and the bound of x hold
+fn lp() -> Value {
+    json!({
+        "direction": "max",
+        "variables": [
+            {"name": "x", "min": 0, "max": 3, "integer": false},
+            {"name": "y", "min": 0, "integer": false}
+        ],
+        "objective": [term("x", 3.0), term("y", 2.0)],
+        "objective_offset": 1,
+        "constraints": [[term("x", 1.0), term("y", 1.0)], [term("x", 1.0), term("y", 3.0)]],
+        "constraint_offsets": [-4, -7],
+        "equalities": [],
+        "equalities_offsets": [],
+        "verbose": false
+    })
+}
+
+fn knapsack() -> Value {
+    let binary = |name: &str| json!({"name": name, "min": 0, "max": 1, "integer": true});
+    json!({
+        "direction": "max",
+        "variables": [
+            binary("a"),
+            binary("b"),
+            binary("c"),
+            {"name": "d", "min": 0, "max": 2.5, "integer": false}
+        ],
+        "objective": [term("a", 5.0), term("b", 4.0), term("c", 3.0), term("d", 1.0)],
+        "objective_offset": 0,
+        "constraints": [[term("a", 2.0), term("b", 3.0), term("c", 1.0), term("d", 1.0)]],
+        "constraint_offsets": [-5],
+        "equalities": [],
+        "equalities_offsets": [],
+        "verbose": false
+    })
+}
+
+/// Solves a problem through the C entry point and returns the response as it is.
+fn response(input: &str) -> String {
+    let response = unsafe { knorpelsolve::solve(input.as_ptr(), input.len()) };
+    let text = unsafe { CStr::from_ptr(response) }
+        .to_str()
+        .unwrap()
+        .to_owned();
+    unsafe { knorpelsolve::free_string(response.cast_mut()) };
+    text
+}
+
+fn numbers(value: &Value) -> Vec<f64> {
+    let numbers = value.as_array().expect("a list of numbers").iter();
+    numbers.map(|n| n.as_f64().unwrap()).collect()
+}
+
+fn close(a: &[f64], b: &[f64]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-6)
+}
+
+#[test]
+fn the_objective_includes_the_offset() {
+    let solution = solve(&lp());
+    assert_eq!(solution["status"], "optimal");
+    assert!(close(&values(&solution), &[3.0, 1.0]));
+    assert_eq!(solution["objective"], 12.0);
+
+    // x + y <= -1 cannot hold
+    let mut problem = lp();
+    problem["constraint_offsets"][0] = json!(1);
+    let solution = solve(&problem);
+    assert_eq!(solution["status"], "infeasible");
+    assert!(solution["objective"].is_null());
+}
+
+#[test]
+fn named_values_keep_unusual_names() {
+    let mut problem = lp();
+    problem["output"] = json!("named");
+    problem["variables"][0]["name"] = json!("größe x");
+    problem["variables"][1]["name"] = json!(" \ty\n");
+    problem["objective"] = json!([term("größe x", 3.0), term(" \ty\n", 2.0)]);
+    problem["constraints"] = json!([
+        [term("größe x", 1.0), term(" \ty\n", 1.0)],
+        [term("größe x", 1.0), term(" \ty\n", 3.0)]
+    ]);
+    let solution = solve(&problem);
+    assert_eq!(
+        solution["named_values"],
+        json!({"größe x": 3.0, " \ty\n": 1.0})
+    );
+    // the positional values stay
+    assert!(close(&values(&solution), &[3.0, 1.0]));
+
+    assert!(solve(&lp()).get("named_values").is_none());
+}
+
+#[test]
+fn lps_report_the_duals_of_their_rows() {
+    let solution = solve(&lp());
+    assert!(close(&numbers(&solution["duals"]), &[2.0, 0.0]));
+
+    let mut problem = lp();
+    problem["variables"][1]["integer"] = json!(true);
+    assert!(solve(&problem).get("duals").is_none());
+}
+
+#[test]
+fn lps_report_the_reduced_costs_of_their_variables() {
+    // x is worth 1 more than the row makes it cost, y is basic
+    let solution = solve(&lp());
+    assert!(close(&numbers(&solution["reduced_costs"]), &[1.0, 0.0]));
+
+    let mut problem = lp();
+    problem["variables"][1]["integer"] = json!(true);
+    assert!(solve(&problem).get("reduced_costs").is_none());
+}
+
+#[test]
+fn rows_report_their_activities_and_slacks() {
+    let solution = solve(&lp());
+    assert!(close(&numbers(&solution["activities"]), &[0.0, -1.0]));
+    assert!(close(&numbers(&solution["slacks"]), &[0.0, 1.0]));
+
+    // the equalities come after the constraints
+    let mut problem = lp();
+    problem["equalities"] = json!([[term("y", 2.0)]]);
+    problem["equalities_offsets"] = json!([-2]);
+    let solution = solve(&problem);
+    assert!(close(&numbers(&solution["activities"]), &[0.0, -1.0, 0.0]));
+}
+
+#[test]
+fn solutions_report_what_the_solver_did() {
+    let stats = &solve(&lp())["stats"];
+    for key in ["parse_ms", "build_ms", "solve_ms"] {
+        assert!(stats[key].as_f64().unwrap() >= 0.0, "{key}");
+    }
+    assert!(stats["simplex_iterations"].as_i64().unwrap() >= 0);
+    assert!(stats["nodes"].is_null());
+
+    let stats = &solve(&knapsack())["stats"];
+    assert!(stats["nodes"].as_i64().unwrap() >= 0);
+}
+
+#[test]
+fn mips_report_their_gap_and_bound() {
+    let mut problem = knapsack();
+    problem["objective_offset"] = json!(1);
+    let solution = solve(&problem);
+    assert!(close(&values(&solution), &[1.0, 0.0, 1.0, 2.0]));
+    assert_eq!(solution["objective"], 11.0);
+    // with the offset, which HiGHS does not know about
+    assert_eq!(solution["best_bound"], 11.0);
+    assert_eq!(solution["mip_gap"], 0.0);
+
+    assert!(solve(&lp()).get("mip_gap").is_none());
+}
+
+#[test]
+fn the_log_is_captured_on_request() {
+    let mut problem = lp();
+    problem["options"] = json!({"capture_log": true});
+    let solution = solve(&problem);
+    assert!(solution["log"].as_str().unwrap().contains("HiGHS"));
+    assert!(solve(&lp()).get("log").is_none());
+}
+
+#[test]
+fn errors_are_structured_documents() {
+    let solution: Value = serde_json::from_str(&response("{\"variables\": 1")).unwrap();
+    assert_eq!(solution["status"], "error");
+    assert_eq!(solution["kind"], "parse");
+
+    let mut problem = lp();
+    problem["objective"][0]["name"] = json!("z");
+    let solution = solve(&problem);
+    assert_eq!(solution["kind"], "validation");
+    assert_eq!(solution["message"], "unknown variable 'z'");
+
+    problem["constraint_offsets"] = json!([-4]);
+    let solution = solve(&problem);
+    assert_eq!(
+        solution["message"],
+        "got 2 constraints but 1 constraint offsets"
+    );
+}
+
+#[test]
+fn lps_report_their_basis() {
+    // x is at its upper bound, the first row holds with y in the basis
+    assert_eq!(
+        solve(&lp())["basis"],
+        json!({"columns": ["upper", "basic"], "rows": ["upper", "basic"]})
+    );
+    assert!(solve(&knapsack()).get("basis").is_none());
+}
+
+#[test]
+fn unbounded_problems_have_a_ray() {
+    // without rows, presolve finds the problem unbounded without a ray
+    let mut problem = lp();
+    problem["constraints"] = json!([[term("x", 1.0), term("y", -1.0)]]);
+    problem["constraint_offsets"] = json!([-1]);
+    problem["variables"][0]["max"] = json!(null);
+    let solution = solve(&problem);
+    assert_eq!(solution["status"], "unbounded");
+    let ray = numbers(&solution["ray"]);
+    assert!(ray.iter().all(|&r| r >= 0.0) && ray.iter().any(|&r| r > 0.0));
+}
+
+#[test]
+fn sensitivity_ranges_costs_and_right_hand_sides() {
+    let mut problem = lp();
+    problem["sensitivity"] = json!(true);
+    let solution = solve(&problem);
+    let ranges = |key: &str| -> Vec<Vec<f64>> {
+        let ranges = solution[key].as_array().unwrap().iter();
+        ranges.map(numbers).collect()
+    };
+    // x stays at its bound as long as it is worth more than y, without end
+    assert_eq!(solution["cost_ranges"][0], json!([2.0, null]));
+    assert!(close(&numbers(&solution["cost_ranges"][1]), &[0.0, 3.0]));
+    // the first row may grow until the second one holds as well
+    let rhs = ranges("rhs_ranges");
+    assert!(close(&rhs[0], &[3.0, 4.333_333_333_333_333]));
+    assert!(close(&rhs[1], &[3.0, 7.0]));
+
+    assert!(solve(&lp()).get("cost_ranges").is_none());
+    let mut problem = knapsack();
+    problem["sensitivity"] = json!(true);
+    assert!(solve(&problem).get("cost_ranges").is_none());
+}
+
+#[test]
+fn sparse_output_lists_the_nonzeros() {
+    let mut problem = knapsack();
+    problem["sparse_output"] = json!(true);
+    problem["output"] = json!("named");
+    let solution = solve(&problem);
+    assert_eq!(
+        solution["nonzeros"],
+        json!([
+            {"index": 0, "name": "a", "value": 1},
+            {"index": 2, "name": "c", "value": 1},
+            {"index": 3, "name": "d", "value": 2.0}
+        ])
+    );
+    assert_eq!(solution["values"], json!([]));
+    assert!(solution.get("named_values").is_none());
+
+    // values up to the tolerance count as zero
+    let mut problem = lp();
+    problem["sparse_output"] = json!(true);
+    problem["zero_tolerance"] = json!(1.5);
+    let solution = solve(&problem);
+    assert_eq!(solution["nonzeros"], json!([{"index": 0, "value": 3.0}]));
+}
+
+#[test]
+fn integer_variables_have_integer_values() {
+    let solution = solve(&knapsack());
+    assert_eq!(solution["values"], json!([1, 0, 1, 2.0]));
+    assert!(solution.get("warnings").is_none());
+}
+
+#[test]
+fn finite_values_stay_numbers_under_either_policy() {
+    // x is free and has no cost, so HiGHS may put it anywhere it likes
+    let mut problem = lp();
+    problem["variables"][0] = json!({"name": "x", "integer": false});
+    problem["objective"] = json!([term("y", 1.0)]);
+    problem["constraints"] = json!([[term("y", 1.0)]]);
+    problem["constraint_offsets"] = json!([-1]);
+    for policy in ["error", "string"] {
+        problem["non_finite"] = json!(policy);
+        let solution = solve(&problem);
+        assert_eq!(solution["status"], "optimal", "{policy}");
+        assert!(solution["values"][0].is_number(), "{policy}");
+    }
+    problem["non_finite"] = json!("null");
+    assert_eq!(solve(&problem)["kind"], "parse");
+}
+
+#[test]
+fn the_breakdown_sums_up_to_the_objective() {
+    let mut problem = lp();
+    problem["objective_breakdown"] = json!(true);
+    let solution = solve(&problem);
+    let breakdown = &solution["objective_breakdown"];
+    assert!(close(&numbers(&breakdown["terms"]), &[9.0, 2.0]));
+    assert_eq!(breakdown["offset"], 1.0);
+    assert!(solve(&lp()).get("objective_breakdown").is_none());
+}
+
+#[test]
+fn the_id_is_passed_through() {
+    let mut problem = lp();
+    problem["id"] = json!({"run": 7});
+    assert_eq!(solve(&problem)["id"], json!({"run": 7}));
+
+    // also into errors, as long as the id can be read
+    problem["direction"] = json!("sideways");
+    let solution = solve(&problem);
+    assert_eq!(solution["kind"], "parse");
+    assert_eq!(solution["id"], json!({"run": 7}));
+    assert!(solve(&lp()).get("id").is_none());
+}
+
+#[test]
+fn mips_report_whether_they_came_back_integral() {
+    let solution = solve(&knapsack());
+    assert_eq!(solution["integral"], true);
+    assert!(solution.get("nonintegral_variables").is_none());
+    assert!(solve(&lp()).get("integral").is_none());
+}
+
+#[test]
+fn pretty_output_indents_the_response() {
+    let mut problem = lp();
+    problem["options"] = json!({"pretty_output": true});
+    let pretty = response(&problem.to_string());
+    assert!(pretty.starts_with("{\n  \""), "{pretty}");
+    let compact = response(&lp().to_string());
+    assert!(!compact.contains('\n'));
+    let solution: Value = serde_json::from_str(&pretty).unwrap();
+    assert_eq!(solution["objective"], 12.0);
+
+    // errors as well, if the options can be read
+    problem["direction"] = json!("sideways");
+    assert!(response(&problem.to_string()).starts_with("{\n"));
+}
+
+#[test]
+fn decimal_places_round_the_reported_numbers() {
+    let mut problem = lp();
+    problem["objective_offset"] = json!(1.0 / 3.0);
+    problem["options"] = json!({"decimal_places": 6});
+    assert_eq!(solve(&problem)["objective"], 11.333_333);
+    problem["options"] = json!({"decimal_places": 0});
+    let solution = solve(&problem);
+    assert_eq!(solution["objective"], 11.0);
+    assert_eq!(solution["values"], json!([3.0, 1.0]));
+    problem["options"] = json!({});
+    assert_eq!(solve(&problem)["objective"], 11.0 + 1.0 / 3.0);
+
+    // what rounds to zero is zero, not minus zero
+    let mut problem = lp();
+    problem["objective_offset"] = json!(-0.4);
+    problem["objective_breakdown"] = json!(true);
+    problem["options"] = json!({"decimal_places": 0});
+    let response = response(&problem.to_string());
+    assert!(response.contains(r#""offset":0.0"#), "{response}");
+}
+
+#[test]
+fn decimal_places_round_the_breakdown() {
+    let mut problem = lp();
+    problem["objective_offset"] = json!(1.0 / 3.0);
+    problem["variables"][1]["cost"] = json!(1.0 / 9.0);
+    problem["objective_quadratic"] = json!([{"name_a": "x", "name_b": "x", "factor": -1.0 / 7.0}]);
+    problem["abs_terms"] =
+        json!([{"terms": [term("x", 1.0)], "offset": -0.5, "factor": -1.0 / 3.0}]);
+    problem["objective_breakdown"] = json!(true);
+    problem["options"] = json!({"decimal_places": 2});
+    // the penalties move the optimum to x = 2.5 and y = 1.5
+    let solution = solve(&problem);
+    let breakdown = &solution["objective_breakdown"];
+    assert_eq!(breakdown["offset"], 0.33);
+    assert_eq!(breakdown["costs"], json!([0.17]));
+    assert_eq!(breakdown["quadratic"], json!([-0.89]));
+    assert_eq!(breakdown["abs"], json!([-0.67]));
+    assert_eq!(breakdown["terms"], json!([7.5, 3.0]));
+}
+
+#[test]
+fn presolve_reports_what_it_removed() {
+    // x <= 10 always holds, since x is at most 3
+    let mut problem = lp();
+    problem["constraints"] = json!([
+        [term("x", 1.0), term("y", 1.0)],
+        [term("x", 1.0), term("y", 3.0)],
+        [term("x", 1.0)]
+    ]);
+    problem["constraint_offsets"] = json!([-4, -7, -10]);
+    let solution = solve(&problem);
+    assert!(close(&values(&solution), &[3.0, 1.0]));
+    let presolve = &solution["stats"]["presolve"];
+    assert!(
+        presolve["rows_removed"].as_i64().unwrap() >= 1,
+        "{presolve}"
+    );
+    assert!(presolve["columns_removed"].as_i64().unwrap() >= 0);
+    assert!(presolve["nonzeros_removed"].as_i64().unwrap() >= 1);
+
+    let presolve = &solve(&knapsack())["stats"]["presolve"];
+    assert!(presolve["rows_removed"].as_i64().is_some(), "{presolve}");
+    // without a solution, what presolve did is not reported
+    let mut problem = lp();
+    problem["constraint_offsets"][0] = json!(1);
+    let solution = solve(&problem);
+    assert_eq!(solution["status"], "infeasible");
+    assert!(solution["stats"].is_object());
+    assert!(solution["stats"].get("presolve").is_none());
+}
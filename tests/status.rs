@@ -0,0 +1,189 @@
+#![cfg(feature = "highs")]
+//! The status of a solution, which tells whether its values are optimal,
+//! only feasible, or missing.
+
+mod common;
+
+use common::{solve, values};
+use serde_json::{Value, json};
+
+// a knapsack that presolve and the root node do not solve on their own
+fn knapsack() -> Value {
+    let weights = [
+        23, 31, 29, 44, 53, 38, 63, 85, 89, 82, 47, 61, 37, 72, 58, 91, 36, 42, 77, 56,
+    ];
+    let profits = [
+        92, 57, 49, 68, 60, 43, 67, 84, 87, 72, 51, 67, 40, 80, 63, 95, 39, 44, 81, 59,
+    ];
+    let name = |i: usize| format!("x{i}");
+    let variables: Vec<_> = (0..weights.len())
+        .map(|i| json!({"name": name(i), "min": 0, "max": 1, "integer": true}))
+        .collect();
+    let objective: Vec<_> = profits
+        .iter()
+        .enumerate()
+        .map(|(i, p)| json!({"name": name(i), "factor": p}))
+        .collect();
+    let capacity: Vec<_> = weights
+        .iter()
+        .enumerate()
+        .map(|(i, w)| json!({"name": name(i), "factor": w}))
+        .collect();
+    json!({
+        "direction": "max",
+        "variables": variables,
+        "objective": objective,
+        "objective_offset": 0,
+        "constraints": [capacity],
+        "constraint_offsets": [-500],
+        "equalities": [],
+        "equalities_offsets": [],
+        "verbose": false
+    })
+}
+
+#[test]
+fn proven_optimum_is_optimal() {
+    let solution = solve(&knapsack());
+    assert_eq!(solution["status"], "optimal");
+    assert_eq!(values(&solution).len(), 20);
+    assert_eq!(solution["mip_gap"], 0.0);
+}
+
+#[test]
+fn objective_target_stops_with_a_feasible_solution() {
+    let mut problem = knapsack();
+    problem["options"] = json!({"presolve": "off", "raw_options": {"objective_target": 10}});
+    let solution = solve(&problem);
+    assert_eq!(solution["status"], "feasible");
+    assert_eq!(values(&solution).len(), 20);
+    assert!(solution["mip_gap"].as_f64().unwrap() > 0.0);
+}
+
+#[test]
+fn node_limit_keeps_the_incumbent() {
+    let mut problem = knapsack();
+    problem["options"] = json!({"presolve": "off", "node_limit": 1});
+    let solution = solve(&problem);
+    assert_eq!(solution["status"], "nodelimit");
+    assert_eq!(values(&solution).len(), 20);
+}
+
+#[test]
+fn improving_solution_limit_stops_with_a_feasible_solution() {
+    let mut problem = knapsack();
+    problem["options"] = json!({"presolve": "off", "improving_solution_limit": 1});
+    let solution = solve(&problem);
+    assert_eq!(solution["status"], "feasible");
+    assert_eq!(values(&solution).len(), 20);
+    assert!(solution["mip_gap"].as_f64().unwrap() > 0.0);
+}
+
+#[test]
+fn infeasible_has_no_values() {
+    let mut problem = knapsack();
+    problem["equalities"] = json!([[{"name": "x0", "factor": 1}]]);
+    problem["equalities_offsets"] = json!([-2]);
+    let solution = solve(&problem);
+    assert_eq!(solution["status"], "infeasible");
+    assert_eq!(solution["values"], json!([]));
+    assert_eq!(solution["objective"], Value::Null);
+}
+
+#[test]
+fn unbounded_has_no_values() {
+    let problem = json!({
+        "direction": "max",
+        "variables": [{"name": "x", "min": 0, "integer": false}],
+        "objective": [{"name": "x", "factor": 1}],
+        "objective_offset": 0,
+        "constraints": [],
+        "constraint_offsets": [],
+        "equalities": [],
+        "equalities_offsets": [],
+        "verbose": false
+    });
+    let solution = solve(&problem);
+    assert_eq!(solution["status"], "unbounded");
+    assert_eq!(solution["values"], json!([]));
+}
+
+// x + y >= 1 rules out the start of the simplex at the origin
+fn covering() -> Value {
+    json!({
+        "direction": "min",
+        "variables": [
+            {"name": "x", "min": 0, "integer": false},
+            {"name": "y", "min": 0, "integer": false}
+        ],
+        "objective": [{"name": "x", "factor": 1}, {"name": "y", "factor": 2}],
+        "rows": [{"terms": [{"name": "x", "factor": 1}, {"name": "y", "factor": 1}], "op": ">=", "rhs": 1}],
+        "verbose": false
+    })
+}
+
+#[test]
+fn iteration_limit_without_an_incumbent_has_no_values() {
+    let mut problem = covering();
+    problem["options"] = json!({"presolve": "off", "raw_options": {"simplex_iteration_limit": 0}});
+    let solution = solve(&problem);
+    assert_eq!(solution["status"], "iterationlimit", "{solution}");
+    assert_eq!(solution["values"], json!([]));
+}
+
+#[test]
+fn iteration_limit_with_an_incumbent_is_feasible() {
+    // the origin is feasible once the row is x + y <= 1
+    let mut problem = covering();
+    problem["direction"] = json!("max");
+    problem["rows"][0]["op"] = json!("<=");
+    problem["options"] = json!({"presolve": "off", "raw_options": {"simplex_iteration_limit": 0}});
+    let solution = solve(&problem);
+    assert_eq!(solution["status"], "feasible", "{solution}");
+    assert_eq!(values(&solution).len(), 2);
+}
+
+#[test]
+fn solution_limit_without_an_incumbent_has_no_values() {
+    // a market split, whose relaxations hold long before a leaf does
+    let rows = [
+        ([78, 31, 53, 73, 45, 19, 64, 64, 39, 86, 95, 75], 361),
+        ([32, 80, 49, 62, 5, 99, 96, 51, 36, 93, 54, 98], 377),
+        ([53, 4, 40, 45, 93, 32, 52, 17, 50, 75, 73, 39], 286),
+    ];
+    let name = |i: usize| format!("x{i}");
+    let variables: Vec<_> = (0..12)
+        .map(|i| json!({"name": name(i), "min": 0, "max": 1, "integer": true}))
+        .collect();
+    let rows: Vec<_> = rows
+        .iter()
+        .map(|(factors, rhs)| {
+            let terms: Vec<_> = factors
+                .iter()
+                .enumerate()
+                .map(|(i, f)| json!({"name": name(i), "factor": f}))
+                .collect();
+            json!({"terms": terms, "op": "==", "rhs": rhs})
+        })
+        .collect();
+    let problem = json!({
+        "direction": "min",
+        "variables": variables,
+        "objective": [],
+        "rows": rows,
+        "options": {"presolve": "off", "raw_options": {"mip_max_leaves": 1}},
+        "verbose": false
+    });
+    let solution = solve(&problem);
+    assert_eq!(solution["status"], "solutionlimit", "{solution}");
+    assert_eq!(solution["values"], json!([]));
+}
+
+#[test]
+fn solution_limit_with_an_incumbent_is_feasible() {
+    let mut problem = knapsack();
+    problem["options"] = json!({"presolve": "off", "raw_options": {"mip_max_leaves": 1}});
+    let solution = solve(&problem);
+    assert_eq!(solution["status"], "feasible", "{solution}");
+    assert_eq!(values(&solution).len(), 20);
+}
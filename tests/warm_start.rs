@@ -0,0 +1,105 @@
+#![cfg(feature = "highs")]
+
+mod common;
+
+use common::{solve, values};
+use serde_json::{Value, json};
+
+fn knapsack() -> Value {
+    json!({
+        "direction": "max",
+        "variables": [
+            {"name": "a", "min": 0, "max": 1, "integer": true},
+            {"name": "b", "min": 0, "max": 1, "integer": true},
+            {"name": "c", "min": 0, "max": 1, "integer": true}
+        ],
+        "objective": [
+            {"name": "a", "factor": 5},
+            {"name": "b", "factor": 4},
+            {"name": "c", "factor": 3}
+        ],
+        "rows": [{"terms": [
+            {"name": "a", "factor": 2}, {"name": "b", "factor": 3}, {"name": "c", "factor": 1}
+        ], "op": "<=", "rhs": 4}],
+        "verbose": false
+    })
+}
+
+#[test]
+fn previous_values_solve_to_the_same_optimum() {
+    let first = solve(&knapsack());
+    assert_eq!(first["status"], "optimal");
+    let mut problem = knapsack();
+    problem["warm_start"] = json!(values(&first));
+    let second = solve(&problem);
+    assert_eq!(second["status"], "optimal");
+    assert_eq!(second["objective"], first["objective"]);
+    assert_eq!(second.get("warnings"), None);
+}
+
+#[test]
+fn warm_start_with_sos_columns() {
+    let mut problem = knapsack();
+    problem["sos"] = json!([{"type": 1, "variables": ["a", "b"]}]);
+    problem["warm_start"] = json!([1, 0, 1]);
+    let solution = solve(&problem);
+    assert_eq!(solution["status"], "optimal");
+    assert_eq!(values(&solution), [1.0, 0.0, 1.0]);
+}
+
+#[test]
+fn warm_start_with_max_of() {
+    let mut problem = knapsack();
+    problem["max_of"] = json!([{
+        "name": "largest",
+        "expressions": [{"terms": [{"name": "a", "factor": 1}]}, {"terms": [{"name": "b", "factor": 1}]}]
+    }]);
+    problem["objective"]
+        .as_array_mut()
+        .unwrap()
+        .push(json!({"name": "largest", "factor": -1}));
+    problem["partial_start"] = json!([{"name": "a", "value": 1}]);
+    let solution = solve(&problem);
+    assert_eq!(solution["status"], "optimal");
+    assert_eq!(solution["objective"], 7.0);
+}
+
+#[test]
+fn out_of_bounds_values_are_warnings() {
+    let mut problem = knapsack();
+    problem["warm_start"] = json!([2, 0, 0.5]);
+    let solution = solve(&problem);
+    assert_eq!(solution["status"], "optimal");
+    assert_eq!(
+        solution["warnings"],
+        json!([
+            "warm start value 2 of 'a' is outside of its bounds [0, 1]",
+            "warm start value 0.5 of integer variable 'c' is fractional"
+        ])
+    );
+}
+
+#[test]
+fn warm_start_needs_a_value_per_variable() {
+    let mut problem = knapsack();
+    problem["warm_start"] = json!([1, 0]);
+    let solution = solve(&problem);
+    assert_eq!(solution["kind"], "validation");
+    assert_eq!(
+        solution["message"],
+        "warm_start has 2 values for 3 variables"
+    );
+}
+
+#[test]
+fn partial_starts_name_some_of_the_variables() {
+    let mut problem = knapsack();
+    problem["partial_start"] = json!([{"name": "a", "value": 1}, {"name": "c", "value": 1}]);
+    let solution = solve(&problem);
+    assert_eq!(solution["status"], "optimal");
+    assert_eq!(solution["objective"], 8.0);
+
+    problem["partial_start"][1]["name"] = json!("d");
+    let solution = solve(&problem);
+    assert_eq!(solution["message"], "unknown variable 'd' in partial_start");
+}
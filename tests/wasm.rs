@@ -0,0 +1,45 @@
+#![cfg(all(feature = "wasm", target_arch = "wasm32"))]
+//! `solve_json` as JavaScript calls it, on wasm32-unknown-unknown where
+//! microlp is the only solver. Run with wasm-bindgen-test-runner.
+
+mod common;
+
+use common::{solve, values};
+use serde_json::json;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[wasm_bindgen_test]
+fn solves_a_milp() {
+    let problem = json!({
+        "direction": "max",
+        "variables": [
+            {"name": "x", "min": 0, "max": 10, "integer": true},
+            {"name": "y", "min": 0, "max": 10, "integer": true}
+        ],
+        "objective": [{"name": "x", "factor": 5}, {"name": "y", "factor": 4}],
+        "rows": [
+            {"terms": [{"name": "x", "factor": 6}, {"name": "y", "factor": 4}], "op": "<=", "rhs": 24},
+            {"terms": [{"name": "x", "factor": 1}, {"name": "y", "factor": 2}], "op": "<=", "rhs": 6.5}
+        ]
+    });
+    let solution = solve(&problem);
+    assert_eq!(solution["status"], "optimal");
+    assert_eq!(solution["objective"], 20.0);
+    assert_eq!(values(&solution), [4.0, 0.0]);
+    assert!(solution["stats"]["solve_ms"].as_f64().unwrap() >= 0.0);
+}
+
+#[wasm_bindgen_test]
+fn reports_errors() {
+    let solution =
+        solve(&json!({"variables": [], "objective": [], "options": {"solver": "highs"}}));
+    assert_eq!(solution["status"], "error");
+    assert_eq!(
+        solution["message"],
+        "solver 'highs' is not available, the available solvers are microlp"
+    );
+
+    let response = knorpelsolve::solve_json("{\"variables\": [");
+    let solution: serde_json::Value = serde_json::from_str(&response).unwrap();
+    assert_eq!(solution["status"], "error");
+}